@@ -1,18 +1,42 @@
 use std::{
     io::{self, Write},
     process::exit,
-    time::Instant,
+    sync::{Mutex, Once},
+    thread,
+    time::{Duration, Instant, SystemTime},
 };
 
-use colored::Colorize;
+use colored::{Color, ColoredString, Colorize};
 use humantime::format_duration;
 
 use crate::{
-    game::types::{MainSelection, Message, MessageType, UserRequest},
-    sudoku::{CellState, HintStatus, InsertStatus, Position, Sudoku},
-    util::{confirm, prompt, prompt_select},
+    display_error, display_warn,
+    game::{
+        history::MoveLog,
+        stats::{GameKind, GameResult, Outcome, Stats},
+        types::{DatabaseAction, MainSelection, Message, MessageType, MoveCommand, UserRequest},
+    },
+    sudoku::{
+        BoardSize, CancellationToken, CellState, ClueMask, DEFAULT_FALSE_POSITIVE_RATE, ExtraRule,
+        Grade, HintStatus, InsertStatus, LogicalStep, Position, Progress, PuzzleDb, REGION_COLOURS,
+        Samurai, Sudoku, Symmetry, Variant, coordinate_to_char, digit_to_char,
+    },
+    util::{confirm, prompt, prompt_multi_select, prompt_select},
 };
 
+const DAILY_PUZZLE_CLUES: u8 = 32;
+
+/// clues of a samurai board, fewer take a lot longer to generate
+const SAMURAI_CLUES: usize = 120;
+
+/// batch run Ctrl-C stops, outside of one Ctrl-C quits as usual
+static ACTIVE_RUN: Mutex<Option<CancellationToken>> = Mutex::new(None);
+
+static INTERRUPT_HANDLER: Once = Once::new();
+
+const NO_LOGICAL_STEP_FOUND: &str =
+    "No logical step found with the known techniques, try a hint on a cell instead";
+
 #[derive(Default)]
 pub struct Game {
     board: Option<Sudoku>,
@@ -20,18 +44,31 @@ pub struct Game {
     main_selection: MainSelection,
     mistakes: u8,
     additional_clues: u8,
-    undo_buffer: Vec<(Position, Option<u8>)>,
-    redo_buffer: Vec<(Position, Option<u8>)>,
+    undo_buffer: Vec<MoveCommand>,
+    redo_buffer: Vec<MoveCommand>,
     initital_board_layout: String,
+    give_up: bool,
+    history: MoveLog,
+    explain_ladder: Option<(LogicalStep, u8)>,
+    /// seed of the daily puzzle being played, the date unless another one was entered
+    daily: Option<String>,
+    /// key of the puzzle being played when it was picked from the puzzle database
+    db_key: Option<String>,
 }
 
 impl Game {
     pub fn start_game(&mut self) {
+        Game::install_interrupt_handler();
+
         loop {
             let main_selection_options = vec![
                 MainSelection::New,
+                MainSelection::Daily,
+                MainSelection::Samurai,
                 MainSelection::Load,
+                MainSelection::Replay,
                 MainSelection::Generate,
+                MainSelection::Database,
                 MainSelection::Exit,
             ];
 
@@ -51,12 +88,24 @@ impl Game {
                         continue;
                     }
 
+                    if str.trim().starts_with("samurai:") {
+                        match Samurai::from_str(&str) {
+                            Ok(b) => self.samurai_loop(b),
+                            Err(e) => println!(
+                                "invalid puzzle input loaded, please fix that and try again later: {}",
+                                e
+                            ),
+                        }
+
+                        continue;
+                    }
+
                     let board = match Sudoku::from_str(&str) {
                         Ok(b) => b,
                         Err(e) => {
                             println!(
                                 "invalid puzzle input loaded, please fix that and try again later: {}",
-                                e.to_string()
+                                e
                             );
                             continue;
                         }
@@ -66,53 +115,337 @@ impl Game {
                     self.game_loop();
                 }
                 MainSelection::New => {
-                    let clues = prompt("How many clues do you want in the puzzle?", "40");
+                    let size_options = BoardSize::SUPPORTED.to_vec();
+                    let size = size_options[prompt_select("Select the board size", &size_options)];
+
+                    let variant_options = Variant::ALL
+                        .into_iter()
+                        .filter(|v| v.supports(size))
+                        .collect::<Vec<Variant>>();
+                    let variant =
+                        variant_options[prompt_select("Select the rule set", &variant_options)];
+
+                    let rule_options = ExtraRule::ALL
+                        .into_iter()
+                        .filter(|r| r.supports(size, variant))
+                        .collect::<Vec<ExtraRule>>();
+                    let rules = prompt_multi_select(
+                        "Select extra rules (space to toggle, enter to confirm)",
+                        &rule_options,
+                    )
+                    .into_iter()
+                    .map(|i| rule_options[i])
+                    .collect::<Vec<ExtraRule>>();
+
+                    // killer boards are generated with as few givens as possible, up to this many
+                    let default_clues = match variant.has_cages() {
+                        true => size.default_killer_givens(),
+                        false => size.default_clues(),
+                    };
+
+                    let clues = prompt(
+                        "How many clues do you want in the puzzle?",
+                        &default_clues.to_string(),
+                    );
 
                     let clues = match clues.parse::<u8>() {
                         Ok(c) => c,
                         Err(e) => {
-                            println!("expected a number but found characters: {}", e.to_string());
+                            println!("expected a number but found characters: {}", e);
                             continue;
                         }
                     };
 
+                    let symmetry = Game::prompt_symmetry();
+
+                    let givens = clues.clamp(size.min_clues(), size.max_clues());
+
+                    if !variant.has_cages() && !symmetry.reaches(size.side(), givens as usize) {
+                        println!("{givens} givens can't be laid out with the symmetry: {symmetry}");
+                        continue;
+                    }
+
                     // clears the board completely
                     self.hard_reset();
 
-                    let board = Sudoku::generate_random_board(clues, |c| {
+                    let board = Sudoku::generate_random_board(
+                        size,
+                        variant,
+                        &rules,
+                        clues,
+                        symmetry,
+                        |c| {
+                            print!("\rFiltered: {c}");
+                            io::stdout().flush().unwrap();
+                        },
+                    );
+
+                    let board = match board {
+                        Some(b) => b,
+                        None => {
+                            println!(
+                                "\nno board with at most {clues} givens was found, try again with more clues"
+                            );
+                            continue;
+                        }
+                    };
+
+                    self.set_board(board);
+                    self.game_loop();
+                }
+                MainSelection::Samurai => {
+                    let clues = prompt(
+                        "How many clues do you want in the puzzle?",
+                        &SAMURAI_CLUES.to_string(),
+                    );
+
+                    let clues = match clues.parse::<usize>() {
+                        Ok(c) => c,
+                        Err(e) => {
+                            println!("expected a number but found characters: {}", e);
+                            continue;
+                        }
+                    };
+
+                    let board = Samurai::generate(
+                        clues,
+                        Some(&mut |c| {
+                            print!("\rCells tried: {c}");
+                            io::stdout().flush().unwrap();
+                        }),
+                        &mut rand::rng(),
+                    );
+
+                    self.samurai_loop(board);
+                }
+                MainSelection::Daily => {
+                    // any seed string gives everyone who enters it the same puzzle
+                    let seed = prompt(
+                        "Seed of the puzzle (leave empty for today's date)",
+                        &Game::today(),
+                    );
+                    let seed = match seed.trim() {
+                        "" => Game::today(),
+                        s => s.to_string(),
+                    };
+
+                    self.hard_reset();
+
+                    let board = Sudoku::generate_seeded_board(DAILY_PUZZLE_CLUES, &seed, |c| {
                         print!("\rFiltered: {c}");
                         io::stdout().flush().unwrap();
                     });
 
-                    self.set_board(board.unwrap());
+                    let board = match board {
+                        Some(b) => b,
+                        None => {
+                            println!("\nunable to generate the puzzle of the seed: {seed}");
+                            continue;
+                        }
+                    };
+
+                    self.set_board(board);
+                    self.daily = Some(seed);
                     self.game_loop();
                 }
-                MainSelection::Generate => {
-                    let clues = prompt("How many clues do you want to have?", "40");
+                MainSelection::Replay => {
+                    let filename = prompt("Path to the move history file", "history.txt");
 
-                    let clues = match clues.parse::<u8>() {
-                        Ok(c) => c,
+                    let log = match MoveLog::load_from_file(&*filename) {
+                        Ok(l) => l,
                         Err(e) => {
-                            println!("expected a number but found characters: {}", e.to_string());
+                            println!("unable to load the move history: {}", e);
+                            continue;
+                        }
+                    };
+
+                    let speed = prompt(
+                        "Playback speed (1 is real time, 2 is twice as fast, 0 steps manually)",
+                        "1",
+                    );
+
+                    let speed = match speed.parse::<f64>() {
+                        Ok(s) if s >= 0.0 => s,
+                        _ => {
+                            println!("expected a non negative number for the playback speed");
                             continue;
                         }
                     };
 
+                    self.replay(&log, speed);
+                }
+                MainSelection::Generate => {
+                    let source = prompt(
+                        "Puzzle to derive equivalent copies from (leave empty to generate new boards)",
+                        "",
+                    );
+
+                    if !source.trim().is_empty() {
+                        Game::derive_copies(&source);
+                        continue;
+                    }
+
+                    let mask = prompt(
+                        "Clue mask to fill, 81 cells with x for a given and . for an empty one (leave empty for random layouts)",
+                        "",
+                    );
+
+                    let mask = match mask.trim() {
+                        "" => None,
+                        m => match ClueMask::from_str(m) {
+                            Ok(m) => Some(m),
+                            Err(e) => {
+                                println!("invalid clue mask given: {}", e);
+                                continue;
+                            }
+                        },
+                    };
+
+                    // the mask fixes the givens, the layout is only asked for without one
+                    let (clues, symmetry, minimal_only) = match &mask {
+                        Some(m) => (m.number_of_clues(), Symmetry::None, false),
+                        None => {
+                            let clues = prompt("How many clues do you want to have?", "40");
+
+                            let clues = match clues.parse::<u8>() {
+                                Ok(c) => c,
+                                Err(e) => {
+                                    println!("expected a number but found characters: {}", e);
+                                    continue;
+                                }
+                            };
+
+                            let symmetry = Game::prompt_symmetry();
+
+                            if !symmetry.reaches(BoardSize::CLASSIC.side(), clues as usize) {
+                                println!(
+                                    "{clues} givens can't be laid out with the symmetry: {symmetry}"
+                                );
+                                continue;
+                            }
+
+                            let minimal_only = confirm(
+                                "Only keep minimal puzzles (no clue can be removed without losing uniqueness)?",
+                                false,
+                            );
+
+                            (clues, symmetry, minimal_only)
+                        }
+                    };
+
                     let number_of_boards =
                         prompt("How many boards do you want to generate?", "100");
 
                     let number_of_boards = match number_of_boards.parse::<usize>() {
                         Ok(c) => c,
                         Err(e) => {
-                            println!("expected a number but found characters: {}", e.to_string());
+                            println!("expected a number but found characters: {}", e);
                             continue;
                         }
                     };
 
                     let just_print = confirm("Do you want to just print it here?", true);
 
-                    let boards =
-                        Sudoku::generate_random_boards(clues, number_of_boards, just_print);
+                    // the cache of invalid boards is only read when the boards are stored
+                    let false_positive_rate = match just_print {
+                        true => DEFAULT_FALSE_POSITIVE_RATE,
+                        false => {
+                            let rate = prompt(
+                                "Share of the boards the invalid board cache may wrongly skip",
+                                &DEFAULT_FALSE_POSITIVE_RATE.to_string(),
+                            );
+
+                            match rate.trim().parse::<f64>() {
+                                Ok(r) if r > 0.0 && r < 1.0 => r,
+                                Ok(r) => {
+                                    println!("expected a share between 0 and 1 but found: {r}");
+                                    continue;
+                                }
+                                Err(e) => {
+                                    println!("expected a number but found characters: {}", e);
+                                    continue;
+                                }
+                            }
+                        }
+                    };
+
+                    let seed = prompt("Seed to generate from (leave empty for a random one)", "");
+
+                    let seed = if seed.trim().is_empty() {
+                        None
+                    } else {
+                        match seed.trim().parse::<u64>() {
+                            Ok(s) => Some(s),
+                            Err(e) => {
+                                println!("expected a number but found characters: {}", e);
+                                continue;
+                            }
+                        }
+                    };
+
+                    // the boards of a seeded run are stored with the seed, so a stopped run can
+                    // pick up where it left off. Without one the stored boards with the same
+                    // settings are counted instead
+                    let resume = !just_print
+                        && match seed {
+                            Some(_) => confirm(
+                                "Keep the boards an earlier run with the seed stored?",
+                                true,
+                            ),
+                            None => confirm(
+                                "Keep the stored boards generated with these settings and only generate the rest?",
+                                false,
+                            ),
+                        };
+
+                    let cancel = CancellationToken::new();
+                    *ACTIVE_RUN.lock().expect("the run lock isn't poisoned") = Some(cancel.clone());
+
+                    println!("Press Ctrl-C to stop early, the boards found so far are kept");
+
+                    // the last report is kept for the per thread summary
+                    let mut last_progress = None;
+                    let mut observer = |p: &Progress| {
+                        Game::print_progress(p);
+                        last_progress = Some(p.clone());
+                    };
+
+                    let boards = match &mask {
+                        Some(m) => Sudoku::generate_masked_boards(
+                            m,
+                            number_of_boards,
+                            just_print,
+                            false_positive_rate,
+                            seed,
+                            resume,
+                            &cancel,
+                            &mut observer,
+                        ),
+                        None => Sudoku::generate_random_boards(
+                            clues,
+                            symmetry,
+                            minimal_only,
+                            number_of_boards,
+                            just_print,
+                            false_positive_rate,
+                            seed,
+                            resume,
+                            &cancel,
+                            &mut observer,
+                        ),
+                    };
+
+                    *ACTIVE_RUN.lock().expect("the run lock isn't poisoned") = None;
+
+                    // the seed is the only way back into the run, random ones are shown too
+                    if cancel.is_cancelled() {
+                        display_warn!(format!(
+                            "\nStopped early, {} of {} boards found. Run again with the seed {} (and the same settings) to generate the rest",
+                            boards.0.len(),
+                            number_of_boards,
+                            boards.2
+                        ));
+                    }
 
                     println!("\n\nUnqiue and valid boards");
 
@@ -120,186 +453,648 @@ impl Game {
                         println!("{}", board.to_thonky_str());
                     }
 
-                    println!("\nBoards ({} with {} threads)", boards.0.len(), boards.1);
+                    println!(
+                        "\nBoards ({} with {} threads, seed {})",
+                        boards.0.len(),
+                        boards.1,
+                        boards.2
+                    );
+
+                    if let Some(p) = last_progress {
+                        for (i, t) in p.get_threads().iter().enumerate() {
+                            println!(
+                                "Thread {}: {} found, {} tried, {} invalid",
+                                i + 1,
+                                t.get_found(),
+                                t.get_searched(),
+                                t.get_invalid()
+                            );
+                        }
+                    }
                 }
+                MainSelection::Database => self.database_menu(),
                 MainSelection::Exit => exit(1),
             }
         }
     }
 
     fn set_board(&mut self, board: Sudoku) {
-        self.initital_board_layout = board.to_str().into();
+        self._r();
+        self.give_up = false;
+        self.initital_board_layout = board.to_str();
+        self.history = MoveLog::new(&self.initital_board_layout);
+        self.explain_ladder = None;
+        self.daily = None;
+        self.db_key = None;
         self.starting_clues = board.number_of_initial_clues();
         self.board = Some(board);
     }
 
     fn game_loop(&mut self) {
-        match &self.board {
-            None => {
-                return;
-            }
-            Some(_) => (),
-        };
+        if self.board.is_none() {
+            return;
+        }
 
-        let mut give_up = false;
-        let mut msg;
         let mut message: Option<Message> = None;
         let start_time = Instant::now();
-        let mut won = false;
 
         loop {
-            if self.board.as_mut().unwrap().is_board_solved_completely() {
-                if !give_up {
-                    if self.mistakes > 0 {
-                        msg = format!(
-                            "{}\nTime taken: {}\n\n{}",
-                            "Even though you made some mistake(s), you made it. Congragulations!",
-                            format_duration(start_time.elapsed()),
-                            self.initital_board_layout
-                        );
-                    } else {
-                        msg = format!(
-                            "{}\nTime taken: {}\n\n{}",
-                            "Congragulations!",
-                            format_duration(start_time.elapsed()),
-                            self.initital_board_layout
-                        );
-                    }
-
-                    message = Some(Message::new(&msg, MessageType::Success));
+            // end of the puzzle
+            if self.is_solved() {
+                match self.completion_message(start_time.elapsed()) {
+                    Some(m) => self.draw(&Some(m)),
+                    None => self.draw(&message),
                 }
-                won = true;
-            }
 
-            self.draw(&message);
-            message = None;
-
-            // end of the puzzle
-            if won {
+                self.record_result(start_time.elapsed());
                 break;
             }
 
+            self.draw(&message);
+
             let ans = prompt(
                 "Enter your guess (ex: g007 - means fill grid location 0 (x), 0 (y) with 7)",
                 "",
             );
 
-            let v = match UserRequest::parse(&ans) {
+            let side = match &self.board {
+                Some(b) => b.get_size().side(),
+                None => BoardSize::CLASSIC.side(),
+            };
+
+            let v = match UserRequest::parse(&ans, side) {
                 Ok(v) => v,
                 Err(e) => {
-                    msg = format!("Error parsing your request: {}", e.to_string());
-                    message = Some(Message::new(&msg, MessageType::Error));
+                    message = Some(Message::new(
+                        format!("Error parsing your request: {}", e),
+                        MessageType::Error,
+                    ));
                     continue;
                 }
             };
 
-            match v {
-                UserRequest::Guess(pos, val) => {
-                    match self.board.as_mut().unwrap().insert_at(&pos, Some(val)) {
-                        InsertStatus::Wrong => {
-                            self.mistakes += 1;
-                            message = Some(Message::new("Value doesn't fit in this cell, please try again".into(), MessageType::Error));
-                        },
-                        InsertStatus::ValuePresent =>
-                            message = Some(Message::new("Value is already present in the cell/block/row/column, try clearing the cell before inserting a new value or force insert".into(), MessageType::Warn)),
-                        _ => (),
-                    };
+            if let UserRequest::Exit = v {
+                break;
+            }
+
+            self.history.record(start_time.elapsed(), &v);
+            message = self.apply_request(&v, start_time.elapsed());
+        }
+
+        if !self.history.is_empty() && confirm("Do you want to export the move history?", false) {
+            let filename = prompt("File to export the move history to", "history.txt");
 
-                    self.undo_buffer.push((pos.clone(), Some(val)));
-                    self.redo_buffer.clear();
+            match self.history.export_to_file(&*filename) {
+                Ok(_) => println!("Move history exported to {filename}"),
+                Err(e) => display_error!(format!("exporting the move history: {e}")),
+            }
+        }
+    }
+
+    /// Ctrl-C during a batch run stops it and keeps the boards found so far
+    fn install_interrupt_handler() {
+        INTERRUPT_HANDLER.call_once(|| {
+            let res = ctrlc::set_handler(|| match ACTIVE_RUN.lock().ok().and_then(|r| r.clone()) {
+                Some(cancel) => cancel.cancel(),
+                None => exit(130),
+            });
+
+            if let Err(e) = res {
+                display_warn!(format!("Ctrl-C won't stop batch runs early: {e}"));
+            }
+        });
+    }
+
+    /// status line of a batch run, redrawn in place
+    fn print_progress(p: &Progress) {
+        // whole seconds, the rest is noise this far out
+        let eta = match p.eta() {
+            Some(d) => format_duration(Duration::from_secs(d.as_secs())).to_string(),
+            None => "unknown".to_string(),
+        };
+
+        print!(
+            "\rFound {}/{} | tried {} ({:.0}/s, {:.1}% invalid) | {:.2} boards/s | ETA {}          ",
+            p.get_found(),
+            p.get_target(),
+            p.get_searched(),
+            p.searched_per_sec(),
+            p.invalid_rate() * 100.0,
+            p.puzzles_per_sec(),
+            eta
+        );
+        io::stdout().flush().unwrap();
+    }
+
+    fn prompt_symmetry() -> Symmetry {
+        let symmetry_options = Symmetry::ALL.to_vec();
+        symmetry_options[prompt_select("Select the symmetry of the givens", &symmetry_options)]
+    }
+
+    /// prints relabelled, shuffled and rotated copies of the puzzle, all with the same solution
+    /// up to the transformation
+    fn derive_copies(inp: &str) {
+        let board = match Sudoku::from_str(inp) {
+            Ok(b) => b,
+            Err(e) => {
+                println!("invalid puzzle input given: {}", e);
+                return;
+            }
+        };
+
+        let count = prompt("How many copies do you want?", "100");
+
+        let count = match count.parse::<usize>() {
+            Ok(c) => c,
+            Err(e) => {
+                println!("expected a number but found characters: {}", e);
+                return;
+            }
+        };
+
+        let seed = prompt("Seed to derive from (leave empty for a random one)", "");
+
+        let seed = match seed.trim() {
+            "" => rand::random::<u64>(),
+            s => match s.parse::<u64>() {
+                Ok(s) => s,
+                Err(e) => {
+                    println!("expected a number but found characters: {}", e);
+                    return;
                 }
-                UserRequest::RemoveGuess(pos) => {
-                    match self.board.as_mut().unwrap().insert_at(&pos, None) {
-                        InsertStatus::ValuePresent =>
-                            message = Some(Message::new("Please check the position that you are trying to remove at. Maybe it's not filled to begin with".into(), MessageType::Warn)),
-                        _ => (),
+            },
+        };
+
+        let copies = match board.equivalent_boards(count, seed) {
+            Ok(c) => c,
+            Err(e) => {
+                println!("unable to derive copies of the board: {e}");
+                return;
+            }
+        };
+
+        for copy in copies {
+            match copy.get_variant() {
+                Variant::Classic => println!("{}", copy.to_thonky_str()),
+                _ => println!("{}", copy.to_str()),
+            }
+        }
+
+        println!("\nCopies ({count}, seed {seed})");
+    }
+
+    /// plays an unplayed puzzle matching the clue count and grade, or imports the flat files
+    fn database_menu(&mut self) {
+        let mut db = match PuzzleDb::open() {
+            Ok(d) => d,
+            Err(e) => {
+                display_error!(format!("opening the puzzle database: {e}"));
+                return;
+            }
+        };
+
+        let actions = vec![DatabaseAction::Play, DatabaseAction::Import];
+
+        match actions[prompt_select(
+            &format!("{} puzzles stored, select one of the following", db.len()),
+            &actions,
+        )] {
+            DatabaseAction::Play => {
+                let clues = prompt("How many clues (leave empty for any)?", "");
+
+                let clues = match clues.trim() {
+                    "" => None,
+                    c => match c.parse::<u8>() {
+                        Ok(c) => Some(c),
+                        Err(e) => {
+                            println!("expected a number but found characters: {}", e);
+                            return;
+                        }
+                    },
+                };
+
+                let mut grade_options = vec!["Any".to_string()];
+                grade_options.extend(Grade::ALL.iter().map(|g| g.to_string()));
+
+                let grade = match prompt_select("Select the grade", &grade_options) {
+                    0 => None,
+                    i => Some(Grade::ALL[i - 1]),
+                };
+
+                let unplayed_only = confirm("Only puzzles you haven't played yet?", true);
+
+                let record = match db.pick(clues, grade, unplayed_only, &mut rand::rng()) {
+                    Some(r) => r,
+                    None => {
+                        println!("No stored puzzle matches, try generating or importing some");
+                        return;
                     }
+                };
+
+                let board = match record.to_board() {
+                    Ok(b) => b,
+                    Err(e) => {
+                        display_error!(format!("loading the stored puzzle: {e}"));
+                        return;
+                    }
+                };
+
+                println!(
+                    "{} puzzle with {} clues",
+                    record.get_grade(),
+                    record.get_clues()
+                );
+
+                let key = record.get_key().to_string();
+
+                self.hard_reset();
+                self.set_board(board);
+                self.db_key = Some(key);
+                self.game_loop();
+            }
+            DatabaseAction::Import => {
+                let dir = prompt("Directory holding the clues_N folders", ".");
+
+                println!("Grading and storing the puzzles, this may take a while");
+
+                match db.import_flat_files(&*dir) {
+                    Ok((added, skipped)) => println!(
+                        "{added} puzzles imported, {skipped} were already stored ({} in total)",
+                        db.len()
+                    ),
+                    Err(e) => display_error!(format!("importing the puzzle files: {e}")),
                 }
-                UserRequest::Undo => {
-                    if self.undo_buffer.is_empty() {
+            }
+        }
+    }
+
+    fn record_result(&self, time_taken: Duration) {
+        if let Some(key) = &self.db_key
+            && let Err(e) =
+                PuzzleDb::open().and_then(|mut db| db.record_play(key, !self.give_up, time_taken))
+        {
+            display_warn!(format!(
+                "unable to record the play in the puzzle database: {e}"
+            ));
+        }
+
+        let mut stats = match Stats::load() {
+            Ok(s) => s,
+            Err(e) => {
+                display_warn!(format!(
+                    "unable to load the stats, result not recorded: {e}"
+                ));
+                return;
+            }
+        };
+
+        let (kind, label) = match &self.daily {
+            Some(date) => (GameKind::Daily, date.as_str()),
+            None => (GameKind::Regular, "-"),
+        };
+
+        // only the first attempt at a daily puzzle counts
+        if kind == GameKind::Daily && stats.get_daily(label).is_some() {
+            println!("The puzzle of this seed was already played, this attempt isn't recorded");
+            println!("{}\n", stats.summary(kind));
+            return;
+        }
+
+        let outcome = if self.give_up {
+            Outcome::GaveUp
+        } else {
+            Outcome::Solved
+        };
+
+        if let Err(e) = stats.record(GameResult::new(
+            kind,
+            label,
+            outcome,
+            time_taken,
+            self.mistakes,
+            self.additional_clues,
+        )) {
+            display_warn!(format!("unable to record the result: {e}"));
+            return;
+        }
+
+        println!("{}\n", stats.summary(kind));
+    }
+
+    fn is_solved(&self) -> bool {
+        self.board
+            .as_ref()
+            .is_some_and(|b| b.is_board_solved_completely())
+    }
+
+    /// returns the end of game message, none if the player gave up
+    fn completion_message(&self, elapsed: Duration) -> Option<Message<'static>> {
+        if self.give_up {
+            return None;
+        }
+
+        let msg = if self.mistakes > 0 {
+            "Even though you made some mistake(s), you made it. Congragulations!"
+        } else {
+            "Congragulations!"
+        };
+
+        Some(Message::new(
+            format!(
+                "{}\nTime taken: {}\n\n{}",
+                msg,
+                format_duration(elapsed),
+                self.initital_board_layout
+            ),
+            MessageType::Success,
+        ))
+    }
+
+    fn apply_request(&mut self, req: &UserRequest, elapsed: Duration) -> Option<Message<'static>> {
+        let mut message = None;
+        let before = self.board.as_ref().unwrap().get_grid();
+
+        match req {
+            UserRequest::Guess(pos, val) => {
+                match self.board.as_mut().unwrap().insert_at(pos, Some(*val)) {
+                    InsertStatus::Wrong => {
+                        self.mistakes += 1;
                         message = Some(Message::new(
-                            "You can't use the undo option as there is no known previous move"
-                                .into(),
+                            "Value doesn't fit in this cell, please try again",
+                            MessageType::Error,
+                        ));
+                    }
+                    InsertStatus::ValuePresent => {
+                        message = Some(Message::new(
+                            "Value is already present in the cell/block/row/column, try clearing the cell before inserting a new value or force insert",
+                            MessageType::Warn,
+                        ))
+                    }
+                    _ => (),
+                };
+            }
+            UserRequest::RemoveGuess(pos) => {
+                if let InsertStatus::ValuePresent =
+                    self.board.as_mut().unwrap().insert_at(pos, None)
+                {
+                    message = Some(Message::new(
+                        "Please check the position that you are trying to remove at. Maybe it's not filled to begin with",
+                        MessageType::Warn,
+                    ));
+                }
+            }
+            UserRequest::Undo => {
+                let cmd = match self.undo_buffer.pop() {
+                    Some(cmd) => cmd,
+                    None => {
+                        return Some(Message::new(
+                            "You can't use the undo option as there is no known previous move",
                             MessageType::Warn,
                         ));
-                        continue;
                     }
+                };
 
-                    let pp = self.undo_buffer.pop().unwrap();
+                self.board
+                    .as_mut()
+                    .unwrap()
+                    .revert_changes(cmd.get_changes());
 
-                    self.board.as_mut().unwrap().insert_at(&pp.0, None);
-                    self.redo_buffer.push(pp);
-                }
-                UserRequest::Redo => {
-                    if self.redo_buffer.is_empty() {
-                        message = Some(Message::new(
-                            "You can't use the redo option as there is nothing undid yet".into(),
+                message = Some(Message::new(
+                    format!("Undid {}", cmd.get_request()),
+                    MessageType::Normal,
+                ));
+                self.redo_buffer.push(cmd);
+            }
+            UserRequest::Redo => {
+                let cmd = match self.redo_buffer.pop() {
+                    Some(cmd) => cmd,
+                    None => {
+                        return Some(Message::new(
+                            "You can't use the redo option as there is nothing undid yet",
                             MessageType::Warn,
                         ));
-                        continue;
                     }
+                };
 
-                    let pp = self.redo_buffer.pop().unwrap();
+                self.board
+                    .as_mut()
+                    .unwrap()
+                    .reapply_changes(cmd.get_changes());
 
-                    self.board.as_mut().unwrap().insert_at(&pp.0, pp.1);
-                    self.undo_buffer.push(pp);
-                }
-                UserRequest::Hint(pos) => {
-                    match self.board.as_mut().unwrap().hint(&pos) {
-                        HintStatus::ValuePresent => {
-                            message = Some(Message::new(
-                                "Hint requested on already filled cell".into(),
-                                MessageType::Warn,
-                            ))
-                        }
-                        HintStatus::Ok => self.additional_clues += 1,
-                    }
-                    continue;
-                }
-                UserRequest::Highlight(v) => {
-                    self.board.as_mut().unwrap().highlight(Some(v));
-                }
-                UserRequest::RemoveHighlight => {
-                    self.board.as_mut().unwrap().highlight(None);
-                }
-                UserRequest::ShareOriginal => {
-                    let v = &self.initital_board_layout;
-                    message = Some(Message::new(v, MessageType::Success));
-                }
-                UserRequest::ShareCurrentState => {
-                    msg = self.board.as_mut().unwrap().to_str();
-                    message = Some(Message::new(&msg, MessageType::Success));
-                }
-                UserRequest::ShareThonkyVersion => {
-                    msg = self.board.as_mut().unwrap().to_thonky_str();
-                    message = Some(Message::new(&msg, MessageType::Success));
-                }
-                UserRequest::TimeElapsed => {
-                    msg = format!("Time elapsed: {}", format_duration(start_time.elapsed()));
-                    message = Some(Message::new(&msg, MessageType::Normal));
-                }
-                UserRequest::Reset => {
-                    self.reset();
+                message = Some(Message::new(
+                    format!("Redid {}", cmd.get_request()),
+                    MessageType::Normal,
+                ));
+                self.undo_buffer.push(cmd);
+            }
+            UserRequest::Hint(pos) => match self.board.as_mut().unwrap().hint(pos) {
+                HintStatus::ValuePresent => {
+                    message = Some(Message::new(
+                        "Hint requested on already filled cell",
+                        MessageType::Warn,
+                    ))
                 }
-                UserRequest::HardReset => {
-                    self.hard_reset();
+                HintStatus::Conflict => {
+                    message = Some(Message::new(
+                        "The hinted value clashes with one of your guesses, clear the wrong guesses and try again",
+                        MessageType::Warn,
+                    ))
                 }
-                UserRequest::Giveup => {
-                    let b = self.board.as_mut().unwrap();
-                    b.reset();
-                    b.solve();
-                    give_up = true;
+                HintStatus::Ok => self.additional_clues += 1,
+            },
+            UserRequest::HintNextMove => {
+                let step = match self.board.as_ref().unwrap().find_logical_step() {
+                    Some(step) => step,
+                    None => {
+                        return Some(Message::new(NO_LOGICAL_STEP_FOUND, MessageType::Warn));
+                    }
+                };
 
-                    message = Some(Message::new(
-                        &self.initital_board_layout,
-                        MessageType::Highlight,
-                    ));
+                message = match self.board.as_mut().unwrap().hint(step.get_pos()) {
+                    HintStatus::Ok => {
+                        self.additional_clues += 1;
+                        Some(Message::new(step.explain(), MessageType::Highlight))
+                    }
+                    _ => Some(Message::new(
+                        format!("{}, clear your guess in that cell first", step.explain()),
+                        MessageType::Warn,
+                    )),
+                };
+            }
+            UserRequest::Explain => {
+                message = Some(self.explain());
+            }
+            UserRequest::Highlight(v) => {
+                self.board.as_mut().unwrap().highlight(Some(*v));
+            }
+            UserRequest::RemoveHighlight => {
+                self.board.as_mut().unwrap().highlight(None);
+            }
+            UserRequest::ShareOriginal => {
+                message = Some(Message::new(
+                    self.initital_board_layout.clone(),
+                    MessageType::Success,
+                ));
+            }
+            UserRequest::ShareCurrentState => {
+                message = Some(Message::new(
+                    self.board.as_ref().unwrap().to_str(),
+                    MessageType::Success,
+                ));
+            }
+            UserRequest::ShareThonkyVersion => {
+                message = Some(Message::new(
+                    self.board.as_ref().unwrap().to_thonky_str(),
+                    MessageType::Success,
+                ));
+            }
+            UserRequest::ShareFpuzzlesLink => {
+                message = Some(match self.board.as_ref().unwrap().to_fpuzzles_url() {
+                    Ok(url) => Message::new(url, MessageType::Success),
+                    Err(e) => Message::new(
+                        format!("Board can't be shared as a link: {}", e),
+                        MessageType::Error,
+                    ),
+                });
+            }
+            UserRequest::TimeElapsed => {
+                message = Some(Message::new(
+                    format!("Time elapsed: {}", format_duration(elapsed)),
+                    MessageType::Normal,
+                ));
+            }
+            // counters are left as is, the reset can be undone
+            UserRequest::Reset => {
+                self.board.as_mut().unwrap().reset();
+            }
+            UserRequest::HardReset => {
+                self.board.as_mut().unwrap().hard_reset();
+            }
+            UserRequest::Giveup => {
+                let b = self.board.as_mut().unwrap();
+                b.reset();
+                b.solve();
+                self.give_up = true;
+
+                message = Some(Message::new(
+                    self.initital_board_layout.clone(),
+                    MessageType::Highlight,
+                ));
+            }
+            UserRequest::Exit => (),
+        }
+
+        let changes = self.board.as_ref().unwrap().changes_since(&before);
+
+        if !changes.is_empty() {
+            // the board moved on, the explanation in progress might not hold anymore
+            self.explain_ladder = None;
+
+            if req.is_mutating() {
+                self.undo_buffer
+                    .push(MoveCommand::new(req.clone(), changes));
+                self.redo_buffer.clear();
+            }
+        }
+
+        message
+    }
+
+    /// walks the hint ladder one rung further: technique, region, cell and finally the value
+    fn explain(&mut self) -> Message<'static> {
+        let (step, level) = match self.explain_ladder.take() {
+            Some((step, level)) => (step, level + 1),
+            None => match self.board.as_ref().unwrap().find_logical_step() {
+                Some(step) => (step, 1),
+                None => {
+                    return Message::new(NO_LOGICAL_STEP_FOUND, MessageType::Warn);
                 }
-                UserRequest::Exit => {
-                    break;
+            },
+        };
+
+        let msg = match level {
+            1 => match step.get_assisted_by() {
+                None => format!("Hint 1/4: there is a {} on the board", step.get_technique()),
+                Some(t) => format!(
+                    "Hint 1/4: there is a {} on the board once the {} are spotted",
+                    step.get_technique(),
+                    t
+                ),
+            },
+            2 => format!(
+                "Hint 2/4: look for the {} in {}",
+                step.get_technique(),
+                step.get_region()
+            ),
+            3 => format!("Hint 3/4: focus on the cell at {}", step.get_pos()),
+            _ => match self.board.as_mut().unwrap().hint(step.get_pos()) {
+                HintStatus::Ok => {
+                    self.additional_clues += 1;
+                    format!("Hint 4/4: {}", step.explain())
                 }
+                _ => format!(
+                    "Hint 4/4: {}, clear your guess in that cell first",
+                    step.explain()
+                ),
+            },
+        };
+
+        if level < 4 {
+            self.explain_ladder = Some((step, level));
+        }
+
+        Message::new(msg, MessageType::Highlight)
+    }
+
+    /// steps through a recorded game, `speed` scales the recorded timings (0 waits for enter on every move)
+    fn replay(&mut self, log: &MoveLog, speed: f64) {
+        let board = match Sudoku::from_str(log.get_initial_layout()) {
+            Ok(b) => b,
+            Err(e) => {
+                display_error!(format!("invalid board found in the move history: {e}"));
+                return;
+            }
+        };
+
+        self.set_board(board);
+
+        let moves = log.get_moves();
+        let mut previous = Duration::ZERO;
+
+        self.draw(&Some(Message::new(
+            format!("Replaying {} move(s)", moves.len()),
+            MessageType::Highlight,
+        )));
+
+        for (idx, m) in moves.iter().enumerate() {
+            if speed > 0.0 {
+                thread::sleep(m.get_elapsed().saturating_sub(previous).div_f64(speed));
+            } else {
+                prompt("Press enter for the next move", "");
+            }
+
+            previous = m.get_elapsed();
+
+            let header = format!(
+                "Move {}/{} at {}: {}",
+                idx + 1,
+                moves.len(),
+                format_duration(Duration::from_secs(m.get_elapsed().as_secs())),
+                m.get_request()
+            );
+
+            let message = match self.apply_request(m.get_request(), m.get_elapsed()) {
+                Some(msg) => Message::new(format!("{header}\n{}", msg.get_msg()), msg.get_type()),
+                None => Message::new(header, MessageType::Highlight),
+            };
+
+            match self.completion_message(previous) {
+                Some(done) if self.is_solved() => self.draw(&Some(done)),
+                _ => self.draw(&Some(message)),
             }
         }
+
+        println!("End of the replay\n");
     }
 
     fn draw(&self, message: &Option<Message>) {
@@ -325,86 +1120,90 @@ impl Game {
             self.additional_clues.to_string().magenta().bold()
         );
 
-        let highlighted = board.get_highlighted();
+        let size = board.get_size();
+        let (box_rows, box_cols) = board.get_drawn_box();
+        let separator = size.separator_for(box_cols);
+        let region_colours = board.region_colours();
         let mut board_str = String::with_capacity(1500);
 
-        for i in &mut board.get_grid().iter().enumerate() {
-            if i.0 == 0 {
-                board_str.push_str(&format!(
-                    "{}",
-                    "    0  1  2   3  4  5   6  7  8 \n".italic()
-                ));
+        if board.get_variant().has_cages() {
+            let cell = |i: usize, j: usize| {
+                Game::format_cell(
+                    board,
+                    i,
+                    j,
+                    Game::cell_background(board, &region_colours, i, j),
+                )
+            };
+
+            for line in board.draw_cages(&cell) {
                 board_str.push_str(&format!(
-                    "{}         {}\n",
-                    "   -----------------------------".blue(),
+                    "{}        {}\n",
+                    line,
                     instructions.pop().unwrap_or_default()
                 ));
             }
+        } else {
+            for i in &mut board.get_grid().iter().enumerate() {
+                if i.0 == 0 {
+                    board_str.push_str(&format!("{}", size.column_header_for(box_cols).italic()));
+                    board_str.push_str(&format!(
+                        "{}         {}\n",
+                        separator.blue(),
+                        instructions.pop().unwrap_or_default()
+                    ));
+                }
 
-            board_str.push_str(&format!("{} {}", i.0.to_string().italic(), "|".blue()));
-
-            for j in i.1.iter().enumerate() {
-                match j.1.0 {
-                    Some(v) => {
-                        if board
-                            .get_prefilled_positions()
-                            .contains_key(&Position::new(i.0, j.0))
-                        {
-                            let mut val = v.to_string().bold();
-                            if highlighted.is_some() {
-                                if j.1.0.unwrap() == highlighted.unwrap() {
-                                    val = v.to_string().on_bright_yellow().green().bold();
-                                }
-                            }
-
-                            board_str.push_str(&format!(" {} ", val));
-                        } else {
-                            let mut val = match j.1.1 {
-                                CellState::Hinted => v.to_string().magenta().bold(),
-                                CellState::Wrong => v.to_string().red().bold(),
-                                CellState::UserMarkedDefault => v.to_string().yellow().bold(),
-                                _ => v.to_string().green(),
-                            };
+                board_str.push_str(&format!(
+                    "{} {}",
+                    coordinate_to_char(i.0).to_string().italic(),
+                    "|".blue()
+                ));
 
-                            if highlighted.is_some() {
-                                if j.1.0.unwrap() == highlighted.unwrap() {
-                                    if j.1.1 == CellState::Wrong {
-                                        val = val.on_bright_yellow().red().bold();
-                                    } else {
-                                        val = val.on_bright_yellow().green().bold();
-                                    }
-                                }
-                            }
+                for j in i.1.iter().enumerate() {
+                    board_str.push_str(&Game::format_cell(
+                        board,
+                        i.0,
+                        j.0,
+                        Game::cell_background(board, &region_colours, i.0, j.0),
+                    ));
 
-                            board_str.push_str(&format!(" {} ", val));
-                        }
-                    }
-                    None => {
-                        board_str.push_str("   ");
+                    if (j.0 + 1) % box_cols == 0 {
+                        board_str.push_str(&format!("{}", "|".blue()));
                     }
                 }
 
-                if (j.0 + 1) % 3 == 0 {
-                    board_str.push_str(&format!("{}", "|".blue()));
-                }
-            }
-
-            board_str.push_str(&format!(
-                "        {}\n",
-                instructions.pop().unwrap_or_default()
-            ));
-
-            if (i.0 + 1) % 3 == 0 {
                 board_str.push_str(&format!(
-                    "{}         {}\n",
-                    "   -----------------------------".blue(),
+                    "        {}\n",
                     instructions.pop().unwrap_or_default()
                 ));
+
+                if (i.0 + 1) % box_rows == 0 {
+                    board_str.push_str(&format!(
+                        "{}         {}\n",
+                        separator.blue(),
+                        instructions.pop().unwrap_or_default()
+                    ));
+                }
             }
         }
 
         println!("{board_str}");
 
+        if !board.get_constraints().is_empty() {
+            println!("{}", "Constraints".bold());
+
+            for c in board.get_constraints() {
+                println!("  {c}");
+            }
+
+            println!();
+        }
+
+        Game::print_message(message);
+    }
+
+    pub(super) fn print_message(message: &Option<Message>) {
         match message {
             None => println!("\n"),
             Some(m) => {
@@ -421,6 +1220,84 @@ impl Game {
         }
     }
 
+    /// region colour for jigsaw boards, diagonal and window cells are greyed out
+    fn cell_background(
+        board: &Sudoku,
+        region_colours: &[usize],
+        x: usize,
+        y: usize,
+    ) -> Option<Color> {
+        if board.get_variant().has_regions() {
+            let (r, g, b) = REGION_COLOURS[region_colours[board.get_regions()[x][y]]];
+            return Some(Color::TrueColor { r, g, b });
+        }
+
+        match board.is_on_diagonal(x, y) || board.is_in_window(x, y) {
+            true => Some(Color::BrightBlack),
+            false => None,
+        }
+    }
+
+    /// renders a cell 3 chars wide, with the value coloured by its state
+    pub(super) fn format_cell(
+        board: &Sudoku,
+        x: usize,
+        y: usize,
+        background: Option<Color>,
+    ) -> String {
+        let highlighted = board.get_highlighted();
+        let cell = board.get_grid()[x][y];
+
+        // the highlight takes precedence over the background
+        let background = match highlighted.is_some() && cell.0 == highlighted {
+            true => None,
+            false => background,
+        };
+
+        match cell.0 {
+            Some(v) => {
+                if board
+                    .get_prefilled_positions()
+                    .contains_key(&Position::new(x, y))
+                {
+                    let v_str = digit_to_char(v).to_string();
+                    let mut val = v_str.bold();
+                    if highlighted == Some(v) {
+                        val = v_str.on_bright_yellow().green().bold();
+                    }
+
+                    Game::tint_cell(val, background)
+                } else {
+                    let v_str = digit_to_char(v).to_string();
+                    let mut val = match cell.1 {
+                        CellState::Hinted => v_str.magenta().bold(),
+                        CellState::Wrong => v_str.red().bold(),
+                        CellState::UserMarkedDefault => v_str.yellow().bold(),
+                        _ => v_str.green(),
+                    };
+
+                    if highlighted == Some(v) {
+                        if cell.1 == CellState::Wrong {
+                            val = val.on_bright_yellow().red().bold();
+                        } else {
+                            val = val.on_bright_yellow().green().bold();
+                        }
+                    }
+
+                    Game::tint_cell(val, background)
+                }
+            }
+            None => Game::tint_cell(" ".normal(), background),
+        }
+    }
+
+    fn tint_cell(val: ColoredString, background: Option<Color>) -> String {
+        match background {
+            None => format!(" {} ", val),
+            Some(c) => format!("{}{}{}", " ".on_color(c), val.on_color(c), " ".on_color(c)),
+        }
+    }
+
     fn _r(&mut self) {
         self.additional_clues = 0;
         self.mistakes = 0;
@@ -428,15 +1305,6 @@ impl Game {
         self.redo_buffer.clear();
     }
 
-    fn reset(&mut self) {
-        match &mut self.board {
-            None => (),
-            Some(b) => b.reset(),
-        };
-
-        self._r();
-    }
-
     fn hard_reset(&mut self) {
         match &mut self.board {
             None => (),
@@ -452,10 +1320,15 @@ impl Game {
         Self::default()
     }
 
+    /// current date (UTC) in YYYY-MM-DD format, used as the seed of the daily puzzle
+    fn today() -> String {
+        humantime::format_rfc3339_seconds(SystemTime::now()).to_string()[..10].to_string()
+    }
+
     fn get_instructions() -> Vec<String> {
         let mut instructions = vec![];
 
-        instructions.push(format!(""));
+        instructions.push("".into());
         instructions.push("Following commands are the way to interact with the board,".into());
         instructions.push("".into());
         instructions.push(format!(
@@ -467,9 +1340,10 @@ impl Game {
             "RemoveGuess".bold()
         ));
         instructions.push(format!(
-            "{}: t | {}: h07 (0 and 7 indicate x and y coordinates)",
+            "{}: t | {}: h07 (0 and 7 indicate x and y coordinates) or h | {}: e",
             "Time elapsed".bold(),
-            "Hint".bold()
+            "Hint".bold(),
+            "Explain".bold()
         ));
         instructions.push(format!(
             "{}: k | {}: u | {}: r | {}: i<n> (i followed by a valid number)",
@@ -479,7 +1353,7 @@ impl Game {
             "Highlight".bold()
         ));
         instructions.push(format!(
-            "{}: s<n> (n could be 1 (Empty) or 2 (Filled) or 3 (Thonky Sudoku))",
+            "{}: s<n> (n could be 1 (Empty) or 2 (Filled) or 3 (Thonky Sudoku) or 4 (f-puzzles link))",
             "Share".bold()
         ));
         instructions.push(format!(
@@ -488,7 +1362,7 @@ impl Game {
             "Hard Reset".bold(),
             "Exit".bold()
         ));
-        instructions.push(format!(""));
+        instructions.push("".into());
         instructions.push(format!(
             "{}: {}",
             "Designed and developed by".italic(),
@@ -500,3 +1374,109 @@ impl Game {
         instructions
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIVENS: &str =
+        "..82...3...147..65.5..6.49.8.6..53.2.......7..2....8.6..9....43..431.....7.694..1";
+
+    fn game() -> Game {
+        let mut game = Game::new();
+        game.set_board(Sudoku::from_str(GIVENS).unwrap());
+        game
+    }
+
+    fn apply(game: &mut Game, req: UserRequest) -> Option<MessageType> {
+        game.apply_request(&req, Duration::ZERO)
+            .map(|m| m.get_type())
+    }
+
+    fn cell(game: &Game, x: usize, y: usize) -> (Option<u8>, CellState) {
+        game.board.as_ref().unwrap().get_grid()[x][y]
+    }
+
+    /// values on the board, a reset leaves the state of the cells it clears as it was
+    fn values(game: &Game) -> Vec<Vec<Option<u8>>> {
+        game.board
+            .as_ref()
+            .unwrap()
+            .get_grid()
+            .iter()
+            .map(|row| row.iter().map(|c| c.0).collect())
+            .collect()
+    }
+
+    #[test]
+    fn undo_and_redo_restore_overwritten_and_removed_guesses() {
+        let mut game = game();
+        let pos = Position::new(0, 0);
+        let solution = {
+            let mut solved = game.board.clone().unwrap();
+            solved.solve();
+            solved.get_grid()[0][0].0.unwrap()
+        };
+        // a value the row, column and block still allow but the solution doesn't
+        let wrong = (1..=9)
+            .find(|v| {
+                let mut board = game.board.clone().unwrap();
+                *v != solution && {
+                    board.insert_at(&pos, Some(*v));
+                    board.get_grid()[0][0].0 == Some(*v)
+                }
+            })
+            .unwrap();
+
+        apply(&mut game, UserRequest::Guess(pos.clone(), wrong));
+        apply(&mut game, UserRequest::RemoveGuess(pos.clone()));
+        apply(&mut game, UserRequest::Guess(pos.clone(), solution));
+        assert_eq!(cell(&game, 0, 0), (Some(solution), CellState::Normal));
+
+        apply(&mut game, UserRequest::Undo);
+        assert_eq!(cell(&game, 0, 0), (None, CellState::Normal));
+        apply(&mut game, UserRequest::Undo);
+        assert_eq!(cell(&game, 0, 0), (Some(wrong), CellState::Wrong));
+
+        apply(&mut game, UserRequest::Redo);
+        apply(&mut game, UserRequest::Redo);
+        assert_eq!(cell(&game, 0, 0), (Some(solution), CellState::Normal));
+        assert!(matches!(
+            apply(&mut game, UserRequest::Redo),
+            Some(MessageType::Warn)
+        ));
+    }
+
+    #[test]
+    fn undo_and_redo_cover_hints_and_resets() {
+        let mut game = game();
+        let start = values(&game);
+
+        apply(&mut game, UserRequest::Hint(Position::new(0, 0)));
+        apply(&mut game, UserRequest::Hint(Position::new(0, 1)));
+        assert_eq!(cell(&game, 0, 0).1, CellState::Hinted);
+
+        let hinted = game.board.as_ref().unwrap().get_grid();
+
+        // the reset is undone as one step
+        apply(&mut game, UserRequest::Reset);
+        assert_eq!(values(&game), start);
+        apply(&mut game, UserRequest::Undo);
+        assert_eq!(game.board.as_ref().unwrap().get_grid(), hinted);
+
+        apply(&mut game, UserRequest::Undo);
+        assert_eq!(cell(&game, 0, 1), (None, CellState::Normal));
+        assert_eq!(cell(&game, 0, 0).1, CellState::Hinted);
+
+        apply(&mut game, UserRequest::Redo);
+        assert_eq!(game.board.as_ref().unwrap().get_grid(), hinted);
+
+        // a new move drops what was left to redo
+        apply(&mut game, UserRequest::Undo);
+        apply(&mut game, UserRequest::Hint(Position::new(0, 4)));
+        assert!(matches!(
+            apply(&mut game, UserRequest::Redo),
+            Some(MessageType::Warn)
+        ));
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, BufRead},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{
+    game::types::UserRequest,
+    sudoku::{BoardSize, write_atomic},
+};
+
+#[derive(Debug, Clone)]
+pub struct MoveRecord {
+    elapsed: Duration,
+    request: UserRequest,
+}
+
+impl MoveRecord {
+    pub fn get_elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn get_request(&self) -> &UserRequest {
+        &self.request
+    }
+}
+
+/// Complete log of the requests applied to a board, in the order they were applied.
+///
+/// File format: the first line is the initial board layout (same as `Sudoku::to_str`),
+/// every following line is `<milliseconds since start> <command>`.
+#[derive(Debug, Clone, Default)]
+pub struct MoveLog {
+    initial_layout: String,
+    moves: Vec<MoveRecord>,
+}
+
+impl MoveLog {
+    pub fn new(initial_layout: &str) -> Self {
+        MoveLog {
+            initial_layout: initial_layout.to_string(),
+            moves: vec![],
+        }
+    }
+
+    pub fn get_initial_layout(&self) -> &str {
+        &self.initial_layout
+    }
+
+    pub fn get_moves(&self) -> &[MoveRecord] {
+        &self.moves
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    pub fn record(&mut self, elapsed: Duration, request: &UserRequest) {
+        self.moves.push(MoveRecord {
+            elapsed,
+            request: request.clone(),
+        });
+    }
+
+    pub fn export_to_file<P>(&self, filename: P) -> Result<(), Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+    {
+        let mut bytes = format!("{}\n", self.initial_layout);
+
+        for m in &self.moves {
+            bytes.push_str(&format!("{} {}\n", m.elapsed.as_millis(), m.request));
+        }
+
+        write_atomic(filename.as_ref(), bytes.as_bytes())
+    }
+
+    pub fn load_from_file<P>(filename: P) -> Result<Self, Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::open(filename)?;
+        let mut lines = io::BufReader::new(file).lines();
+
+        let initial_layout = match lines.next() {
+            Some(l) => l?,
+            None => return Err("move history file is empty".into()),
+        };
+
+        let mut log = MoveLog::new(initial_layout.trim());
+
+        // the layout holds one comma separated entry per cell
+        let cells = log.initial_layout.split(',').count();
+        let side = match BoardSize::from_total_positions(cells) {
+            Some(s) => s.side(),
+            None => return Err(format!("invalid board layout found with {cells} cells").into()),
+        };
+
+        for (idx, line) in lines.enumerate() {
+            let line = line?;
+            let line = line.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let (millis, command) = match line.split_once(' ') {
+                Some(v) => v,
+                None => {
+                    return Err(format!("invalid move found on line {}: {line}", idx + 2).into());
+                }
+            };
+
+            let millis = millis.parse::<u64>()?;
+            let request = UserRequest::parse(command.trim(), side)?;
+
+            log.record(Duration::from_millis(millis), &request);
+        }
+
+        Ok(log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::sudoku::{Position, Sudoku};
+
+    #[test]
+    fn saved_logs_load_back_the_same() {
+        let board = Sudoku::from_str(
+            "..82...3...147..65.5..6.49.8.6..53.2.......7..2....8.6..9....43..431.....7.694..1",
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join(format!("sudoku_moves_{}", std::process::id()));
+
+        let mut log = MoveLog::new(&board.to_str());
+        let requests = [
+            UserRequest::Guess(Position::new(0, 0), 6),
+            UserRequest::RemoveGuess(Position::new(0, 0)),
+            UserRequest::Undo,
+            UserRequest::Redo,
+            UserRequest::Hint(Position::new(8, 7)),
+            UserRequest::HintNextMove,
+            UserRequest::Highlight(9),
+            UserRequest::Reset,
+            UserRequest::ShareThonkyVersion,
+            UserRequest::Giveup,
+        ];
+
+        for (i, r) in requests.iter().enumerate() {
+            log.record(Duration::from_millis(1500 * i as u64 + 7), r);
+        }
+
+        log.export_to_file(&path).unwrap();
+        let loaded = MoveLog::load_from_file(&path).unwrap();
+
+        assert_eq!(loaded.get_initial_layout(), log.get_initial_layout());
+        assert_eq!(
+            loaded
+                .get_moves()
+                .iter()
+                .map(|m| (m.get_elapsed(), m.get_request().to_string()))
+                .collect::<Vec<_>>(),
+            log.get_moves()
+                .iter()
+                .map(|m| (m.get_elapsed(), m.get_request().to_string()))
+                .collect::<Vec<_>>()
+        );
+
+        fs::write(&path, format!("{}\n12 g00\n", board.to_str())).unwrap();
+        assert!(MoveLog::load_from_file(&path).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,2 +1,6 @@
+#[allow(clippy::module_inception)]
 pub mod game;
+mod history;
+mod samurai;
+mod stats;
 mod types;
@@ -0,0 +1,158 @@
+use std::time::Instant;
+
+use colored::Colorize;
+use humantime::format_duration;
+
+use crate::{
+    game::{
+        game::Game,
+        types::{Message, MessageType, SamuraiRequest},
+    },
+    sudoku::{HintStatus, InsertStatus, Samurai},
+    util::prompt,
+};
+
+impl Game {
+    /// plays a samurai board until it is solved, given up on or left
+    pub(super) fn samurai_loop(&mut self, mut board: Samurai) {
+        let initial_layout = board.to_str();
+        let starting_clues = board.number_of_initial_clues();
+        let start_time = Instant::now();
+        let mut give_up = false;
+        let mut mistakes = 0;
+        let mut hints = 0;
+        let mut message: Option<Message> = None;
+
+        loop {
+            if board.is_board_solved_completely() {
+                let msg = match (give_up, mistakes > 0) {
+                    (true, _) => "Here is the solution",
+                    (false, true) => {
+                        "Even though you made some mistake(s), you made it. Congragulations!"
+                    }
+                    (false, false) => "Congragulations!",
+                };
+
+                message = Some(Message::new(
+                    format!(
+                        "{}\nTime taken: {}\n\n{}",
+                        msg,
+                        format_duration(start_time.elapsed()),
+                        initial_layout
+                    ),
+                    MessageType::Success,
+                ));
+
+                Game::draw_samurai(&board, (starting_clues, mistakes, hints), &message);
+                break;
+            }
+
+            Game::draw_samurai(&board, (starting_clues, mistakes, hints), &message);
+
+            let ans = prompt(
+                "Enter your guess (ex: g3007 - means fill location 0 (x), 0 (y) of grid 3 with 7)",
+                "",
+            );
+
+            let req = match SamuraiRequest::parse(&ans) {
+                Ok(r) => r,
+                Err(e) => {
+                    message = Some(Message::new(
+                        format!("Error parsing your request: {}", e),
+                        MessageType::Error,
+                    ));
+                    continue;
+                }
+            };
+
+            message = None;
+
+            match req {
+                SamuraiRequest::Guess(grid, pos, val) => {
+                    match board.insert_at(grid, &pos, Some(val)) {
+                        InsertStatus::Wrong => {
+                            mistakes += 1;
+                            message = Some(Message::new(
+                                "Value doesn't fit in this cell, please try again",
+                                MessageType::Error,
+                            ));
+                        }
+                        InsertStatus::ValuePresent => {
+                            message = Some(Message::new(
+                                "Value is already present in the cell, try clearing the cell before inserting a new value",
+                                MessageType::Warn,
+                            ))
+                        }
+                        InsertStatus::Right => (),
+                    }
+                }
+                SamuraiRequest::RemoveGuess(grid, pos) => {
+                    board.insert_at(grid, &pos, None);
+                }
+                SamuraiRequest::Hint(grid, pos) => match board.hint(grid, &pos) {
+                    HintStatus::Ok => hints += 1,
+                    HintStatus::ValuePresent => {
+                        message = Some(Message::new(
+                            "The cell is already filled, clear it before asking for a hint",
+                            MessageType::Warn,
+                        ))
+                    }
+                    HintStatus::Conflict => {
+                        message = Some(Message::new(
+                            "The hinted value clashes with a wrong guess, clear that one first",
+                            MessageType::Warn,
+                        ))
+                    }
+                },
+                SamuraiRequest::Share => {
+                    message = Some(Message::new(board.to_str(), MessageType::Normal));
+                }
+                SamuraiRequest::Giveup => {
+                    give_up = true;
+                    board.reveal_solution();
+                }
+                SamuraiRequest::Exit => break,
+            }
+        }
+    }
+
+    /// `counts` are the initial clues, the mistakes and the hints taken
+    fn draw_samurai(board: &Samurai, counts: (usize, u32, u32), message: &Option<Message>) {
+        let (starting_clues, mistakes, hints) = counts;
+
+        // clears the screen without a scrollbar
+        print!("{esc}c", esc = 27 as char);
+
+        println!(
+            "Initial clues: {} {} # mistakes: {} {} # hints: {}\n",
+            starting_clues.to_string().bold(),
+            "|".white().bold(),
+            mistakes.to_string().red().bold(),
+            "|".white().bold(),
+            hints.to_string().magenta().bold()
+        );
+
+        let grids = board.get_grids();
+        let cell = |g: usize, i: usize, j: usize| Game::format_cell(&grids[g], i, j, None);
+
+        for line in board.draw(&cell) {
+            println!("{line}");
+        }
+
+        println!(
+            "\nGrids are numbered 1 to 5 in reading order, 3 is the centre one. Coordinates are within the grid,"
+        );
+        println!("rows and columns are labelled with those of the outermost grid holding them\n");
+        println!(
+            "{}: g<grid><x><y><n> | {}: o<grid><x><y> | {}: h<grid><x><y> | {}: s | {}: k | {}: x\n",
+            "Guess".bold(),
+            "RemoveGuess".bold(),
+            "Hint".bold(),
+            "Share".bold(),
+            "Give up".bold(),
+            "Exit".bold()
+        );
+
+        Game::print_message(message);
+    }
+}
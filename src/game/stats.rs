@@ -0,0 +1,266 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, ErrorKind, Write},
+    time::Duration,
+};
+
+use humantime::format_duration;
+
+const STATS_FILE_NAME: &str = "sudoku_stats";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameKind {
+    Regular,
+    Daily,
+}
+
+impl Display for GameKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            GameKind::Regular => write!(f, "regular"),
+            GameKind::Daily => write!(f, "daily"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Solved,
+    GaveUp,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Outcome::Solved => write!(f, "solved"),
+            Outcome::GaveUp => write!(f, "gave_up"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GameResult {
+    kind: GameKind,
+    /// seed of the daily puzzle (its date unless another seed was entered), `-` for regular games
+    label: String,
+    outcome: Outcome,
+    time_taken: Duration,
+    mistakes: u8,
+    hints: u8,
+}
+
+impl GameResult {
+    pub fn new(
+        kind: GameKind,
+        label: &str,
+        outcome: Outcome,
+        time_taken: Duration,
+        mistakes: u8,
+        hints: u8,
+    ) -> Self {
+        GameResult {
+            kind,
+            label: label.to_string(),
+            outcome,
+            time_taken,
+            mistakes,
+            hints,
+        }
+    }
+
+    /// tab separated fields, tabs, newlines and backslashes of the label are escaped so any
+    /// seed fits in its field
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            self.kind,
+            escape(&self.label),
+            self.outcome,
+            self.time_taken.as_millis(),
+            self.mistakes,
+            self.hints
+        )
+    }
+
+    /// reads a line written by `to_line`, records written before the seeds could hold spaces
+    /// are separated by spaces
+    fn parse(line: &str) -> Result<Self, Box<dyn Error>> {
+        let parts = match line.contains('\t') {
+            true => line.split('\t').collect::<Vec<&str>>(),
+            false => line.split_whitespace().collect::<Vec<&str>>(),
+        };
+
+        if parts.len() != 6 {
+            return Err(format!("invalid stats record found: {line}").into());
+        }
+
+        let kind = match parts[0] {
+            "regular" => GameKind::Regular,
+            "daily" => GameKind::Daily,
+            k => return Err(format!("unknown game kind in stats: {k}").into()),
+        };
+
+        let outcome = match parts[2] {
+            "solved" => Outcome::Solved,
+            "gave_up" => Outcome::GaveUp,
+            o => return Err(format!("unknown outcome in stats: {o}").into()),
+        };
+
+        Ok(GameResult {
+            kind,
+            label: unescape(parts[1]),
+            outcome,
+            time_taken: Duration::from_millis(parts[3].parse::<u64>()?),
+            mistakes: parts[4].parse::<u8>()?,
+            hints: parts[5].parse::<u8>()?,
+        })
+    }
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(label: &str) -> String {
+    let mut resp = String::new();
+    let mut chars = label.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('t') => resp.push('\t'),
+                Some('n') => resp.push('\n'),
+                Some(c) => resp.push(c),
+                None => resp.push('\\'),
+            },
+            c => resp.push(c),
+        }
+    }
+
+    resp
+}
+
+/// results of every finished game, kept in a flat file next to the puzzle files
+#[derive(Debug, Default)]
+pub struct Stats {
+    results: Vec<GameResult>,
+}
+
+impl Stats {
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        let file = match File::open(STATS_FILE_NAME) {
+            Ok(f) => f,
+            Err(e) => {
+                if e.kind() == ErrorKind::NotFound {
+                    return Ok(Stats::default());
+                }
+
+                return Err(e.into());
+            }
+        };
+
+        let mut results = vec![];
+
+        for line in io::BufReader::new(file).lines() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            results.push(GameResult::parse(&line)?);
+        }
+
+        Ok(Stats { results })
+    }
+
+    pub fn record(&mut self, result: GameResult) -> Result<(), Box<dyn Error>> {
+        let mut file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(STATS_FILE_NAME)?;
+
+        file.write_all(result.to_line().as_bytes())?;
+        file.write_all(b"\n")?;
+
+        self.results.push(result);
+
+        Ok(())
+    }
+
+    /// result of the daily puzzle for the given date, if it was already played
+    pub fn get_daily(&self, date: &str) -> Option<&GameResult> {
+        self.results
+            .iter()
+            .find(|r| r.kind == GameKind::Daily && r.label == date)
+    }
+
+    pub fn summary(&self, kind: GameKind) -> String {
+        let results = self
+            .results
+            .iter()
+            .filter(|r| r.kind == kind)
+            .collect::<Vec<_>>();
+
+        let solved = results
+            .iter()
+            .filter(|r| r.outcome == Outcome::Solved)
+            .collect::<Vec<_>>();
+
+        let best = solved
+            .iter()
+            .filter(|r| r.mistakes == 0 && r.hints == 0)
+            .map(|r| r.time_taken)
+            .min();
+
+        format!(
+            "{} games played: {}, solved: {}, best clean time: {}",
+            kind,
+            results.len(),
+            solved.len(),
+            match best {
+                Some(b) => format_duration(Duration::from_secs(b.as_secs())).to_string(),
+                None => "-".into(),
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_with_spaces_and_tabs_round_trip() {
+        for label in ["2026-10-18", "team puzzle night", "a\tb\\c\nd", "-"] {
+            let result = GameResult::new(
+                GameKind::Daily,
+                label,
+                Outcome::Solved,
+                Duration::from_millis(61_500),
+                1,
+                2,
+            );
+            let parsed = GameResult::parse(&result.to_line()).unwrap();
+
+            assert_eq!(parsed.label, label);
+            assert_eq!(parsed.outcome, Outcome::Solved);
+            assert_eq!(parsed.time_taken, Duration::from_millis(61_500));
+            assert_eq!((parsed.mistakes, parsed.hints), (1, 2));
+        }
+    }
+
+    #[test]
+    fn reads_space_separated_records() {
+        let parsed = GameResult::parse("daily 2026-10-17 gave_up 1200 3 0").unwrap();
+
+        assert_eq!(parsed.kind, GameKind::Daily);
+        assert_eq!(parsed.label, "2026-10-17");
+        assert_eq!(parsed.outcome, Outcome::GaveUp);
+    }
+}
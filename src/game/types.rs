@@ -1,12 +1,16 @@
-use std::{error::Error, fmt::Display};
+use std::{borrow::Cow, error::Error, fmt::Display};
 
-use crate::sudoku::Position;
+use crate::sudoku::{CellChange, Position, char_to_digit, coordinate_to_char, digit_to_char};
 
 #[derive(Debug, Clone, Default)]
 pub enum MainSelection {
     New,
+    Daily,
+    Samurai,
     Load,
+    Replay,
     Generate,
+    Database,
     #[default]
     Exit,
 }
@@ -16,12 +20,31 @@ impl Display for MainSelection {
         match &self {
             MainSelection::Load => write!(f, "Load"),
             MainSelection::New => write!(f, "New"),
+            MainSelection::Daily => write!(f, "Daily puzzle"),
+            MainSelection::Samurai => write!(f, "Samurai (five overlapping grids)"),
+            MainSelection::Replay => write!(f, "Replay a game"),
             MainSelection::Generate => write!(f, "Generate boards"),
+            MainSelection::Database => write!(f, "Puzzle database"),
             MainSelection::Exit => write!(f, "Exit"),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub enum DatabaseAction {
+    Play,
+    Import,
+}
+
+impl Display for DatabaseAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            DatabaseAction::Play => write!(f, "Play a stored puzzle"),
+            DatabaseAction::Import => write!(f, "Import the clues_N puzzle files"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum MessageType {
     Success,
@@ -33,13 +56,16 @@ pub enum MessageType {
 
 #[derive(Debug)]
 pub struct Message<'a> {
-    msg: &'a str,
+    msg: Cow<'a, str>,
     msg_type: MessageType,
 }
 
 impl<'a> Message<'a> {
-    pub fn new(msg: &'a str, msg_type: MessageType) -> Self {
-        Message { msg, msg_type }
+    pub fn new(msg: impl Into<Cow<'a, str>>, msg_type: MessageType) -> Self {
+        Message {
+            msg: msg.into(),
+            msg_type,
+        }
     }
 
     pub fn get_type(&self) -> MessageType {
@@ -47,11 +73,11 @@ impl<'a> Message<'a> {
     }
 
     pub fn get_msg(&self) -> &str {
-        self.msg
+        &self.msg
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum UserRequest {
     Guess(Position, u8),
     RemoveGuess(Position),
@@ -61,18 +87,98 @@ pub enum UserRequest {
     HardReset,
     Giveup,
     Hint(Position),
+    HintNextMove,
+    Explain,
     Highlight(u8),
     RemoveHighlight,
     ShareOriginal,
     ShareCurrentState,
     ShareThonkyVersion,
+    ShareFpuzzlesLink,
     TimeElapsed,
     Exit,
 }
 
+/// a mutating request along with every cell it touched, so it can be undone/redone as one step
+#[derive(Debug, Clone)]
+pub struct MoveCommand {
+    request: UserRequest,
+    changes: Vec<CellChange>,
+}
+
+impl MoveCommand {
+    pub fn new(request: UserRequest, changes: Vec<CellChange>) -> Self {
+        MoveCommand { request, changes }
+    }
+
+    pub fn get_request(&self) -> &UserRequest {
+        &self.request
+    }
+
+    pub fn get_changes(&self) -> &[CellChange] {
+        &self.changes
+    }
+}
+
+/// renders the request back into the command that parses to it, used for the move history
+impl Display for UserRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            UserRequest::Guess(pos, val) => write!(
+                f,
+                "g{}{}{}",
+                coordinate_to_char(pos.get_x()),
+                coordinate_to_char(pos.get_y()),
+                digit_to_char(*val)
+            ),
+            UserRequest::RemoveGuess(pos) => write!(
+                f,
+                "o{}{}",
+                coordinate_to_char(pos.get_x()),
+                coordinate_to_char(pos.get_y())
+            ),
+            UserRequest::Undo => write!(f, "u"),
+            UserRequest::Redo => write!(f, "r"),
+            UserRequest::Reset => write!(f, "y"),
+            UserRequest::HardReset => write!(f, "z"),
+            UserRequest::Giveup => write!(f, "k"),
+            UserRequest::Hint(pos) => write!(
+                f,
+                "h{}{}",
+                coordinate_to_char(pos.get_x()),
+                coordinate_to_char(pos.get_y())
+            ),
+            UserRequest::HintNextMove => write!(f, "h"),
+            UserRequest::Explain => write!(f, "e"),
+            UserRequest::Highlight(v) => write!(f, "i{}", digit_to_char(*v)),
+            UserRequest::RemoveHighlight => write!(f, "i"),
+            UserRequest::ShareOriginal => write!(f, "s1"),
+            UserRequest::ShareCurrentState => write!(f, "s2"),
+            UserRequest::ShareThonkyVersion => write!(f, "s3"),
+            UserRequest::ShareFpuzzlesLink => write!(f, "s4"),
+            UserRequest::TimeElapsed => write!(f, "t"),
+            UserRequest::Exit => write!(f, "x"),
+        }
+    }
+}
+
 impl UserRequest {
+    /// requests that change the cells of the board and hence end up on the undo buffer
+    pub fn is_mutating(&self) -> bool {
+        matches!(
+            self,
+            UserRequest::Guess(..)
+                | UserRequest::RemoveGuess(_)
+                | UserRequest::Hint(_)
+                | UserRequest::HintNextMove
+                | UserRequest::Explain
+                | UserRequest::Reset
+                | UserRequest::HardReset
+        )
+    }
+
     #[inline]
-    fn validate_len(c: &Vec<char>, min_len: usize) -> bool {
+    fn validate_len(c: &[char], min_len: usize) -> bool {
         if c.len() - 1 != min_len {
             return false;
         }
@@ -80,12 +186,14 @@ impl UserRequest {
         true
     }
 
-    pub fn parse(ui: &str) -> Result<Self, Box<dyn Error>> {
+    /// `side` is the number of cells per row of the board the request is meant for, values and
+    /// coordinates above 9 are written as letters
+    pub fn parse(ui: &str, side: usize) -> Result<Self, Box<dyn Error>> {
         let ui = ui.to_lowercase();
 
         let chars = ui.chars().collect::<Vec<char>>();
 
-        if chars.len() == 0 {
+        if chars.is_empty() {
             return Err("expected userRequest to be of atleast 1 char long".into());
         }
 
@@ -95,46 +203,51 @@ impl UserRequest {
                     return Err("invalid guess made, please try again".into());
                 }
 
-                let val = match chars[3].to_digit(10) {
-                    Some(v) => v as u8,
+                let val = match char_to_digit(chars[3]) {
+                    Some(v) => v,
                     None => {
-                        return Err("expected a digit between 1 and 9 inclusive but found something else (value digit)".into());
+                        return Err(format!("expected a digit between 1 and {} inclusive but found something else (value digit)", digit_to_char(side as u8)).into());
                     }
                 };
 
-                if val < 1 || val > 9 {
-                    return Err(
-                        "values are not in range, make sure it is in between 1 and 9 inclusive"
-                            .into(),
-                    );
+                if !(1..=side as u8).contains(&val) {
+                    return Err(format!(
+                        "values are not in range, make sure it is in between 1 and {} inclusive",
+                        digit_to_char(side as u8)
+                    )
+                    .into());
                 }
 
-                return Ok(Self::Guess(UserRequest::parse_position(&chars)?, val));
+                return Ok(Self::Guess(UserRequest::parse_position(&chars, side)?, val));
             }
             'h' => {
+                if chars.len() == 1 {
+                    return Ok(Self::HintNextMove);
+                }
+
                 if !UserRequest::validate_len(&chars, 2) {
                     return Err("invalid hint requested, please try again".into());
                 }
 
-                return Ok(Self::Hint(UserRequest::parse_position(&chars)?));
+                return Ok(Self::Hint(UserRequest::parse_position(&chars, side)?));
             }
             'i' => {
                 if !UserRequest::validate_len(&chars, 1) {
                     return Ok(Self::RemoveHighlight);
                 }
 
-                let val = match chars[1].to_digit(10) {
-                    Some(v) => v as u8,
+                let val = match char_to_digit(chars[1]) {
+                    Some(v) => v,
                     None => {
                         return Ok(Self::RemoveHighlight);
                     }
                 };
 
-                if val > 9 {
+                if val as usize > side {
                     return Ok(Self::RemoveHighlight);
                 }
 
-                return Ok(Self::Highlight(val));
+                Ok(Self::Highlight(val))
             }
             's' => {
                 if !UserRequest::validate_len(&chars, 1) {
@@ -145,6 +258,7 @@ impl UserRequest {
                     '1' => UserRequest::ShareOriginal,
                     '2' => UserRequest::ShareCurrentState,
                     '3' => UserRequest::ShareThonkyVersion,
+                    '4' => UserRequest::ShareFpuzzlesLink,
                     _ => {
                         return Err(
                             "invalid share type requested, please fix it and try again".into()
@@ -157,43 +271,119 @@ impl UserRequest {
                     return Err("expected position but found none, please try again".into());
                 }
 
-                return Ok(Self::RemoveGuess(UserRequest::parse_position(&chars)?));
-            }
-            't' => return Ok(Self::TimeElapsed),
-            'u' => return Ok(Self::Undo),
-            'r' => return Ok(Self::Redo),
-            'y' => return Ok(Self::Reset),
-            'z' => return Ok(Self::HardReset),
-            'k' => return Ok(Self::Giveup),
-            'x' => return Ok(Self::Exit),
-            _ => {
-                return Err("Unknown option, please try again".into());
+                return Ok(Self::RemoveGuess(UserRequest::parse_position(
+                    &chars, side,
+                )?));
             }
+            'e' => Ok(Self::Explain),
+            't' => Ok(Self::TimeElapsed),
+            'u' => Ok(Self::Undo),
+            'r' => Ok(Self::Redo),
+            'y' => Ok(Self::Reset),
+            'z' => Ok(Self::HardReset),
+            'k' => Ok(Self::Giveup),
+            'x' => Ok(Self::Exit),
+            _ => Err("Unknown option, please try again".into()),
         }
     }
 
-    fn parse_position(c: &[char]) -> Result<Position, Box<dyn Error>> {
-        let x = match c[1].to_digit(10) {
+    fn parse_position(c: &[char], side: usize) -> Result<Position, Box<dyn Error>> {
+        let last = coordinate_to_char(side - 1);
+
+        let x = match char_to_digit(c[1]) {
             Some(v) => v as usize,
             None => {
-                return Err("expected a digit between 1 and 9 inclusive but found something else (first digit)".into());
+                return Err(format!("expected a digit between 0 and {last} inclusive but found something else (first digit)").into());
             }
         };
 
-        let y = match c[2].to_digit(10) {
+        let y = match char_to_digit(c[2]) {
             Some(v) => v as usize,
             None => {
-                return Err("expected a digit between 1 and 9 inclusive but found something else (second digit)".into());
+                return Err(format!("expected a digit between 0 and {last} inclusive but found something else (second digit)").into());
             }
         };
 
-        if x > 8 || y > 8 {
-            return Err(
-                "co-ordinates are not in range, make sure it is in between 0 and 8 inclusive"
-                    .into(),
-            );
+        if x >= side || y >= side {
+            return Err(format!(
+                "co-ordinates are not in range, make sure it is in between 0 and {last} inclusive"
+            )
+            .into());
         }
 
         Ok(Position::new(x, y))
     }
 }
+
+/// request on a samurai board, cells are addressed by the grid (1 to 5 in reading order) and the
+/// x and y coordinates within that grid
+#[derive(Debug, Clone)]
+pub enum SamuraiRequest {
+    Guess(usize, Position, u8),
+    RemoveGuess(usize, Position),
+    Hint(usize, Position),
+    Share,
+    Giveup,
+    Exit,
+}
+
+impl SamuraiRequest {
+    pub fn parse(ui: &str) -> Result<Self, Box<dyn Error>> {
+        let ui = ui.to_lowercase();
+        let chars = ui.chars().collect::<Vec<char>>();
+
+        if chars.is_empty() {
+            return Err("expected userRequest to be of atleast 1 char long".into());
+        }
+
+        match chars[0] {
+            'g' => {
+                if !UserRequest::validate_len(&chars, 4) {
+                    return Err("invalid guess made, please try again".into());
+                }
+
+                let (grid, pos) = SamuraiRequest::parse_cell(&chars)?;
+
+                match char_to_digit(chars[4]) {
+                    Some(v) if (1..=9).contains(&v) => Ok(Self::Guess(grid, pos, v)),
+                    _ => Err(
+                        "values are not in range, make sure it is in between 1 and 9 inclusive"
+                            .into(),
+                    ),
+                }
+            }
+            'o' => {
+                if !UserRequest::validate_len(&chars, 3) {
+                    return Err(
+                        "expected grid and position but found none, please try again".into(),
+                    );
+                }
+
+                let (grid, pos) = SamuraiRequest::parse_cell(&chars)?;
+                Ok(Self::RemoveGuess(grid, pos))
+            }
+            'h' => {
+                if !UserRequest::validate_len(&chars, 3) {
+                    return Err("invalid hint requested, please try again".into());
+                }
+
+                let (grid, pos) = SamuraiRequest::parse_cell(&chars)?;
+                Ok(Self::Hint(grid, pos))
+            }
+            's' => Ok(Self::Share),
+            'k' => Ok(Self::Giveup),
+            'x' => Ok(Self::Exit),
+            _ => Err("Unknown option, please try again".into()),
+        }
+    }
+
+    /// grid (returned 0 based) followed by the x and y coordinates within it
+    fn parse_cell(c: &[char]) -> Result<(usize, Position), Box<dyn Error>> {
+        let grid = match char_to_digit(c[1]) {
+            Some(g) if (1..=5).contains(&g) => g as usize - 1,
+            _ => return Err("expected a grid between 1 and 5 inclusive".into()),
+        };
+
+        Ok((grid, UserRequest::parse_position(&c[1..], 9)?))
+    }
+}
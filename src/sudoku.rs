@@ -1,22 +1,69 @@
 use colored::Colorize;
 use dashmap::DashSet;
-use rand::RngExt;
+use rand::Rng;
 use std::{
     collections::HashMap,
     error::Error,
     fmt::Display,
-    fs::{File, OpenOptions},
-    io::{self, BufRead, ErrorKind, Write},
+    fs::File,
+    io::{self, BufRead, ErrorKind},
     path::Path,
     sync::{
         Arc,
         atomic::{AtomicUsize, Ordering},
-        mpsc::{self, Sender},
+        mpsc::{self, RecvTimeoutError, Sender},
     },
     thread,
+    time::Instant,
 };
 
-const MAX_NUMBER_OF_RECORDS_IN_A_FILE: usize = 100_000;
+mod cage;
+mod cancel;
+mod canonical;
+mod constraint;
+mod db;
+mod fpuzzles;
+mod invalid_cache;
+mod jigsaw;
+mod mask;
+mod minimal;
+mod progress;
+mod samurai;
+mod seeded_rng;
+mod size;
+mod storage;
+mod symmetry;
+mod techniques;
+mod transform;
+mod variant;
+
+use invalid_cache::{InvalidFilter, PackedBoard};
+use progress::{PROGRESS_INTERVAL, ProgressTracker, ThreadCounters};
+use seeded_rng::SeededRng;
+
+pub use cage::Cage;
+pub use cancel::CancellationToken;
+pub use constraint::{
+    AntiKing, AntiKnight, Arrow, Consecutive, Constraint, ExtraRule, Kropki, KropkiDot, OddEven,
+    Parity, Sandwich, SandwichLine, Thermometer, Values, Whisper, Xv, parse_constraint,
+};
+pub use db::{Play, PuzzleDb, PuzzleRecord, Source};
+pub use invalid_cache::DEFAULT_FALSE_POSITIVE_RATE;
+pub use jigsaw::REGION_COLOURS;
+pub use mask::ClueMask;
+pub use progress::{NoProgress, Progress, ProgressObserver, ThreadStats};
+pub use samurai::Samurai;
+pub use size::{BoardSize, char_to_digit, coordinate_to_char, digit_to_char};
+pub use storage::write_atomic;
+pub use symmetry::Symmetry;
+pub use techniques::{Grade, LogicalStep, Region, Technique};
+pub use variant::Variant;
+
+/// invalid boards held back before they are appended to the cache file in one go
+const INVALID_RECORDS_PER_WRITE: usize = 100_000;
+
+/// directory the puzzle files are kept in
+const PUZZLE_DIR: &str = ".";
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
 pub enum CellState {
@@ -35,6 +82,7 @@ pub enum InsertStatus {
 pub enum HintStatus {
     Ok,
     ValuePresent,
+    Conflict,
 }
 
 enum UpdateMapsType {
@@ -42,8 +90,9 @@ enum UpdateMapsType {
     Remove,
 }
 
-type Board = [[(Option<u8>, CellState); 9]; 9];
-type DietBoard = [u8; 81];
+type Cell = (Option<u8>, CellState);
+type Board = Vec<Vec<Cell>>;
+type DietBoard = Vec<u8>;
 
 #[derive(PartialEq, Eq, Debug, Hash, Clone)]
 pub struct Position {
@@ -53,13 +102,26 @@ pub struct Position {
 
 impl Display for Position {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "x: {}, y: {}", self.x, self.y)
+        write!(
+            f,
+            "x: {}, y: {}",
+            coordinate_to_char(self.x),
+            coordinate_to_char(self.y)
+        )
     }
 }
 
 impl Position {
     pub fn new(x: usize, y: usize) -> Self {
-        return Self { x, y };
+        Self { x, y }
+    }
+
+    pub fn get_x(&self) -> usize {
+        self.x
+    }
+
+    pub fn get_y(&self) -> usize {
+        self.y
     }
 
     pub fn parse(pos: &str) -> Result<Self, Box<dyn Error>> {
@@ -99,43 +161,112 @@ impl Position {
     }
 }
 
+/// state of a single cell before and after a move, used to undo/redo it
+#[derive(Debug, Clone)]
+pub struct CellChange {
+    pos: Position,
+    before: Cell,
+    after: Cell,
+}
+
 #[derive(Debug, Clone)]
 struct RandomBoardsRequestArgs {
     number_of_puzzles: usize,
+    puzzle_index: usize,
     number_of_found_counter: Arc<AtomicUsize>,
-    total_number_of_puzzles_searched: Arc<AtomicUsize>,
-    completed_set: Arc<DashSet<DietBoard>>,
+    /// stats of the worker thread looking for the board
+    counters: Arc<ThreadCounters>,
+    /// boards an earlier run found to have no unique solution, it stays the same during the run.
+    /// Skipping one draws the same next candidate as checking it again would, so only the boards
+    /// the filter wrongly reports change what a seed generates
+    invalid_filter: Arc<InvalidFilter>,
+    /// canonical forms of the boards the run keeps at lower indices, an equivalent copy is drawn
+    /// again. Empty for the workers, equivalent boards are only sorted out once they are done
+    taken: Arc<DashSet<PackedBoard>>,
     tx: Sender<DataTxPacket>,
+    /// unique boards that still have a redundant clue are dropped too
+    minimal_only: bool,
+    cancel: CancellationToken,
+}
+
+/// cells of a solved grid that are kept as givens
+#[derive(Debug, Clone)]
+enum Layout {
+    /// this many givens, removed a whole orbit of the symmetry at a time
+    Random(u8, Symmetry),
+    /// exactly the cells of the mask
+    Mask(ClueMask),
 }
 
 enum DataTxPacket {
-    Valid(Sudoku),
-    Invalid(DietBoard),
+    /// board along with the index of the puzzle in the run, which its seed is derived from
+    Valid(Box<Sudoku>, usize),
+    Invalid(PackedBoard),
 }
 
 #[derive(Debug, Clone)]
 pub struct Sudoku {
+    size: BoardSize,
+    variant: Variant,
     grid: Board,
     prefilled_positions: HashMap<Position, u8>,
     solved_grid: Board,
     highlighted: Option<u8>,
-    rows: [u16; 9],
-    columns: [u16; 9],
-    blocks: [u16; 9],
+    rows: Vec<u32>,
+    columns: Vec<u32>,
+    blocks: Vec<u32>,
+    /// block (region) every cell belongs to, the regular boxes unless it is a jigsaw board
+    regions: Vec<Vec<usize>>,
+    /// main diagonal first, anti diagonal second. Only filled in for `Variant::Diagonal`
+    diagonals: [u32; 2],
+    /// hyper windows, only filled in for `Variant::Windoku`
+    windows: Vec<u32>,
+    /// only set for `Variant::Killer`
+    cages: Vec<Cage>,
+    /// cage each cell belongs to
+    cage_ids: Vec<Vec<Option<usize>>>,
+    /// values placed in every cage, as a bitmap
+    cage_values: Vec<u32>,
+    /// sets of values every cage can still be filled with, as bitmaps
+    cage_combinations: Vec<Vec<u32>>,
+    /// extra rules on top of the variant, ex: thermometers or kropki dots
+    constraints: Vec<Arc<dyn Constraint>>,
+    /// constraints looking at each cell
+    constraint_ids: Vec<Vec<Vec<usize>>>,
 }
 
 impl Display for Sudoku {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.variant.has_cages() {
+            let cell = |i: usize, j: usize| match self.grid[i][j].0 {
+                Some(v) => format!(" {} ", digit_to_char(v).to_string().bold()),
+                None => "   ".to_string(),
+            };
+
+            for line in self.draw_cages(&cell) {
+                writeln!(f, "{line}")?;
+            }
+
+            return Ok(());
+        }
+
+        let (box_rows, box_cols) = self.get_drawn_box();
+        let separator = self.size.separator_for(box_cols);
+
         for i in &mut self.grid.iter().enumerate() {
             if i.0 == 0 {
-                write!(f, "{}", "    0  1  2   3  4  5   6  7  8 \n".italic())
+                write!(f, "{}", self.size.column_header_for(box_cols).italic())
                     .expect("error displaying board 1");
-                write!(f, "   {}\n", "-----------------------------".blue())
-                    .expect("error displaying board 2");
+                writeln!(f, "{}", separator.blue()).expect("error displaying board 2");
             }
 
-            write!(f, "{} {}", i.0.to_string().italic(), "|".blue())
-                .expect("error displaying board 3");
+            write!(
+                f,
+                "{} {}",
+                coordinate_to_char(i.0).to_string().italic(),
+                "|".blue()
+            )
+            .expect("error displaying board 3");
 
             for j in i.1.iter().enumerate() {
                 match j.1.0 {
@@ -144,29 +275,31 @@ impl Display for Sudoku {
                             .prefilled_positions
                             .contains_key(&Position::new(i.0, j.0))
                         {
-                            let mut val = v.to_string().bold();
-                            if self.highlighted.is_some() {
-                                if j.1.0.unwrap() == self.highlighted.unwrap() {
-                                    val = v.to_string().yellow().bold();
-                                }
+                            let v = digit_to_char(v).to_string();
+                            let mut val = v.bold();
+                            if self.highlighted.is_some()
+                                && j.1.0.unwrap() == self.highlighted.unwrap()
+                            {
+                                val = v.yellow().bold();
                             }
 
                             write!(f, " {} ", val).expect("error displaying board 4");
                         } else {
+                            let v = digit_to_char(v).to_string();
                             let mut val = match j.1.1 {
-                                CellState::Hinted => v.to_string().magenta().bold(),
-                                CellState::Wrong => v.to_string().red().bold(),
-                                CellState::UserMarkedDefault => v.to_string().yellow().bold(),
-                                _ => v.to_string().green(),
+                                CellState::Hinted => v.magenta().bold(),
+                                CellState::Wrong => v.red().bold(),
+                                CellState::UserMarkedDefault => v.yellow().bold(),
+                                _ => v.green(),
                             };
 
-                            if self.highlighted.is_some() {
-                                if j.1.0.unwrap() == self.highlighted.unwrap() {
-                                    if j.1.1 == CellState::Wrong {
-                                        val = val.red().bold();
-                                    } else {
-                                        val = val.yellow().bold();
-                                    }
+                            if self.highlighted.is_some()
+                                && j.1.0.unwrap() == self.highlighted.unwrap()
+                            {
+                                if j.1.1 == CellState::Wrong {
+                                    val = val.red().bold();
+                                } else {
+                                    val = val.yellow().bold();
                                 }
                             }
 
@@ -178,16 +311,15 @@ impl Display for Sudoku {
                     }
                 }
 
-                if (j.0 + 1) % 3 == 0 {
+                if (j.0 + 1) % box_cols == 0 {
                     write!(f, "{}", "|".blue()).expect("error displaying board 7");
                 }
             }
 
-            write!(f, "\n").expect("error displaying board 8");
+            writeln!(f).expect("error displaying board 8");
 
-            if (i.0 + 1) % 3 == 0 {
-                write!(f, "   {}\n", "-----------------------------".blue())
-                    .expect("error displaying board 9");
+            if (i.0 + 1) % box_rows == 0 {
+                writeln!(f, "{}", separator.blue()).expect("error displaying board 9");
             }
         }
 
@@ -196,10 +328,85 @@ impl Display for Sudoku {
 }
 
 impl Sudoku {
-    const TOTAL_POSITIONS: usize = 81;
+    /// empty board of the given size and rule set, nothing prefilled
+    pub fn empty(size: BoardSize, variant: Variant) -> Self {
+        let grid = vec![vec![(None, CellState::Normal); size.side()]; size.side()];
+
+        Sudoku {
+            size,
+            variant,
+            grid: grid.clone(),
+            prefilled_positions: HashMap::new(),
+            solved_grid: grid,
+            highlighted: None,
+            rows: vec![0; size.side()],
+            columns: vec![0; size.side()],
+            blocks: vec![0; size.side()],
+            regions: (0..size.side())
+                .map(|i| (0..size.side()).map(|j| size.block_id(i, j)).collect())
+                .collect(),
+            diagonals: [0; 2],
+            windows: vec![0; size.window_count()],
+            cages: vec![],
+            cage_ids: vec![vec![None; size.side()]; size.side()],
+            cage_values: vec![],
+            cage_combinations: vec![],
+            constraints: vec![],
+            constraint_ids: vec![vec![vec![]; size.side()]; size.side()],
+        }
+    }
+
+    /// empty board with the same size, rule set, regions, cages and constraints
+    fn blank_like(&self) -> Self {
+        let mut blank = Sudoku::empty(self.size, self.variant);
+        blank.regions = self.regions.clone();
+        blank
+            .set_cages(self.cages.clone())
+            .expect("cages of a valid board");
+        blank.constraints = self.constraints.clone();
+        blank.constraint_ids = self.constraint_ids.clone();
+        blank
+    }
+
+    /// fills an empty board with the given cells, every `Normal` value is considered a clue
+    fn fill_grid(&mut self, grid: Board) -> Result<(), Box<dyn Error>> {
+        let sudoku = self;
+
+        for i in grid.iter().enumerate() {
+            for j in i.1.iter().enumerate() {
+                if let Some(val) = j.1.0 {
+                    let pos = Position::new(i.0, j.0);
+
+                    if sudoku.update_maps(&pos, val, UpdateMapsType::Add).is_err() {
+                        return Err(
+                            "duplicate value found in row block column diagonal window or cage"
+                                .into(),
+                        );
+                    }
+
+                    if j.1.1 == CellState::Normal {
+                        sudoku.prefilled_positions.insert(pos, val);
+                    }
+                }
+            }
+        }
+
+        sudoku.solved_grid = grid.clone();
+        sudoku.grid = grid;
+
+        sudoku.check_constraints()
+    }
+
+    pub fn get_size(&self) -> BoardSize {
+        self.size
+    }
+
+    pub fn get_variant(&self) -> Variant {
+        self.variant
+    }
 
     pub fn get_grid(&self) -> Board {
-        self.grid
+        self.grid.clone()
     }
 
     pub fn get_prefilled_positions(&self) -> HashMap<Position, u8> {
@@ -211,25 +418,33 @@ impl Sudoku {
     }
 
     pub fn to_thonky_str(&self) -> String {
-        let mut resp = String::with_capacity(81);
+        let mut resp = String::with_capacity(self.size.total_positions());
 
         for i in &mut self.grid.iter().enumerate() {
             for j in i.1.iter().enumerate() {
                 match j.1.0 {
-                    Some(k) => resp.push_str(&k.to_string()),
-                    None => resp.push_str("."),
+                    Some(k) => resp.push(digit_to_char(k)),
+                    None => resp.push('.'),
                 }
             }
         }
 
-        assert_eq!(resp.len(), 81);
+        assert_eq!(resp.len(), self.size.total_positions());
 
         resp
     }
 
+    /// comma separated cells, prefixed with `<tag>:` for boards that aren't classic. Cages
+    /// follow the cells, each one prefixed with a `;`, the region map of jigsaw boards is
+    /// prefixed with a `|`. Constraints come last, each one prefixed with a `#`
     pub fn to_str(&self) -> String {
         let mut resp = String::new();
 
+        if let Some(tag) = self.variant.tag() {
+            resp.push_str(tag);
+            resp.push(':');
+        }
+
         for i in &mut self.grid.iter().enumerate() {
             for j in i.1.iter().enumerate() {
                 if let Some(k) = j.1.0 {
@@ -240,21 +455,33 @@ impl Sudoku {
                         resp.push('u');
                     };
 
-                    resp.push_str(&k.to_string());
+                    resp.push(digit_to_char(k));
                 }
 
                 if !(i.0 + 1 >= self.grid.len() && j.0 + 1 >= self.grid[0].len()) {
-                    resp.push_str(",");
+                    resp.push(',');
                 }
             }
         }
 
+        for cage in &self.cages {
+            resp.push(';');
+            resp.push_str(&cage.to_string());
+        }
+
+        if self.variant.has_regions() {
+            resp.push('|');
+            resp.push_str(&self.regions_to_str());
+        }
+
+        resp.push_str(&self.constraints_to_str());
+
         resp
     }
 
     pub fn is_board_solved_completely(&self) -> bool {
-        for b in self.blocks {
-            if b.count_ones() != 9 {
+        for b in &self.blocks {
+            if b.count_ones() as usize != self.size.side() {
                 return false;
             }
         }
@@ -263,12 +490,59 @@ impl Sudoku {
     }
 
     pub fn number_of_initial_clues(&self) -> u8 {
-        self.prefilled_positions.len() as u8
+        self.prefilled_positions.len().min(u8::MAX as usize) as u8
+    }
+
+    #[inline]
+    fn get_block_id(&self, row: usize, col: usize) -> usize {
+        self.regions[row][col]
+    }
+
+    /// diagonals going through the cell, none unless the variant has diagonals
+    #[inline]
+    fn get_diagonal_ids(&self, row: usize, col: usize) -> impl Iterator<Item = usize> {
+        let has_diagonals = self.variant.has_diagonals();
+        let side = self.size.side();
+
+        [row == col, row + col == side - 1]
+            .into_iter()
+            .enumerate()
+            .filter(move |(_, on)| has_diagonals && *on)
+            .map(|(d, _)| d)
+    }
+
+    pub fn is_on_diagonal(&self, row: usize, col: usize) -> bool {
+        self.get_diagonal_ids(row, col).next().is_some()
+    }
+
+    /// hyper window the cell lies in, none unless the variant has windows
+    #[inline]
+    fn get_window_id(&self, row: usize, col: usize) -> Option<usize> {
+        match self.variant.has_windows() {
+            true => self.size.window_id(row, col),
+            false => None,
+        }
+    }
+
+    pub fn is_in_window(&self, row: usize, col: usize) -> bool {
+        self.get_window_id(row, col).is_some()
     }
 
+    /// values that can still go into the cell, as a bitmap
     #[inline]
-    fn get_block_id(row: usize, col: usize) -> usize {
-        (row / 3) * 3 + (col / 3)
+    fn candidates_at(&self, row: usize, col: usize) -> u32 {
+        let diagonals = self
+            .get_diagonal_ids(row, col)
+            .fold(0, |acc, d| acc | self.diagonals[d]);
+        let window = self.get_window_id(row, col).map_or(0, |w| self.windows[w]);
+
+        self.get_cage_candidates(row, col)
+            & self.get_constraint_candidates(row, col)
+            & !(self.rows[row]
+                | self.columns[col]
+                | self.blocks[self.get_block_id(row, col)]
+                | diagonals
+                | window)
     }
 
     #[inline(always)]
@@ -278,28 +552,59 @@ impl Sudoku {
         v: u8,
         op_type: UpdateMapsType,
     ) -> Result<(), Box<dyn Error>> {
-        let bid = Sudoku::get_block_id(pos.x, pos.y);
+        let bid = self.get_block_id(pos.x, pos.y);
+        let diagonal_ids = self.get_diagonal_ids(pos.x, pos.y).collect::<Vec<usize>>();
+        let window_id = self.get_window_id(pos.x, pos.y);
+        let cage_id = self.cage_ids[pos.x][pos.y];
+
         match op_type {
             UpdateMapsType::Remove => {
                 self.blocks[bid] &= !(1 << v);
                 self.rows[pos.x] &= !(1 << v);
                 self.columns[pos.y] &= !(1 << v);
+
+                for d in diagonal_ids {
+                    self.diagonals[d] &= !(1 << v);
+                }
+
+                if let Some(w) = window_id {
+                    self.windows[w] &= !(1 << v);
+                }
+
+                if let Some(c) = cage_id {
+                    self.cage_values[c] &= !(1 << v);
+                }
             }
             UpdateMapsType::Add => {
-                if Sudoku::check_for_conflict(
-                    &[
-                        (&self.blocks, bid),
-                        (&self.rows, pos.x),
-                        (&self.columns, pos.y),
-                    ],
-                    v,
-                ) {
+                let mut maps = vec![
+                    (&self.blocks[..], bid),
+                    (&self.rows[..], pos.x),
+                    (&self.columns[..], pos.y),
+                ];
+
+                maps.extend(diagonal_ids.iter().map(|d| (&self.diagonals[..], *d)));
+                maps.extend(window_id.map(|w| (&self.windows[..], w)));
+                maps.extend(cage_id.map(|c| (&self.cage_values[..], c)));
+
+                if Sudoku::check_for_conflict(&maps, v) {
                     return Err("given value is already present".into());
                 }
 
                 Sudoku::insert_into_bitmap(&mut self.blocks, bid, v);
                 Sudoku::insert_into_bitmap(&mut self.rows, pos.x, v);
                 Sudoku::insert_into_bitmap(&mut self.columns, pos.y, v);
+
+                for d in diagonal_ids {
+                    Sudoku::insert_into_bitmap(&mut self.diagonals, d, v);
+                }
+
+                if let Some(w) = window_id {
+                    Sudoku::insert_into_bitmap(&mut self.windows, w, v);
+                }
+
+                if let Some(c) = cage_id {
+                    Sudoku::insert_into_bitmap(&mut self.cage_values, c, v);
+                }
             }
         }
 
@@ -315,22 +620,16 @@ impl Sudoku {
     ) -> Result<(), Box<dyn Error>> {
         let exisiting_val = self.grid[pos.x][pos.y];
 
-        match exisiting_val.0 {
-            Some(v) => {
-                self.update_maps(pos, v, UpdateMapsType::Remove)
-                    .expect("removal shouldn't trigger an error");
-            }
-            None => (),
-        };
+        if let Some(v) = exisiting_val.0 {
+            self.update_maps(pos, v, UpdateMapsType::Remove)
+                .expect("removal shouldn't trigger an error");
+        }
 
-        match val {
-            None => (),
-            Some(v) => {
-                if self.update_maps(pos, v, UpdateMapsType::Add).is_err() {
-                    return Err("value is already present".into());
-                }
-            }
-        };
+        if let Some(v) = val
+            && self.update_maps(pos, v, UpdateMapsType::Add).is_err()
+        {
+            return Err("value is already present".into());
+        }
 
         self.grid[pos.x][pos.y] = (val, cell_state);
 
@@ -352,13 +651,18 @@ impl Sudoku {
                 resp = InsertStatus::Wrong;
             }
 
-            if self.highlighted.is_some() {
-                if self.highlighted != val {
-                    self.highlight(val);
-                }
+            if self.highlighted.is_some() && self.highlighted != val {
+                self.highlight(val);
             }
         }
 
+        // values breaking a constraint are turned away like duplicates in a row or column
+        if let Some(v) = val
+            && self.get_constraint_candidates(pos.x, pos.y) & (1 << v) == 0
+        {
+            return InsertStatus::Wrong;
+        }
+
         if self.insert(pos, val, cell_state).is_err() {
             return InsertStatus::Wrong;
         }
@@ -371,11 +675,60 @@ impl Sudoku {
             return HintStatus::ValuePresent;
         }
 
-        self.grid[pos.x][pos.y] = (self.solved_grid[pos.x][pos.y].0, CellState::Hinted);
+        // a wrong guess elsewhere in the row/column/block can hold the hinted value
+        if self
+            .insert(pos, self.solved_grid[pos.x][pos.y].0, CellState::Hinted)
+            .is_err()
+        {
+            return HintStatus::Conflict;
+        }
 
         HintStatus::Ok
     }
 
+    /// lists every cell that differs from the given snapshot of the grid
+    pub fn changes_since(&self, before: &Board) -> Vec<CellChange> {
+        let mut changes = vec![];
+
+        for (i, row) in before.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                if *cell != self.grid[i][j] {
+                    changes.push(CellChange {
+                        pos: Position::new(i, j),
+                        before: *cell,
+                        after: self.grid[i][j],
+                    });
+                }
+            }
+        }
+
+        changes
+    }
+
+    pub fn revert_changes(&mut self, changes: &[CellChange]) {
+        self.restore_cells(changes.iter().map(|c| (&c.pos, c.before)));
+    }
+
+    pub fn reapply_changes(&mut self, changes: &[CellChange]) {
+        self.restore_cells(changes.iter().map(|c| (&c.pos, c.after)));
+    }
+
+    fn restore_cells<'a, I>(&mut self, cells: I)
+    where
+        I: Iterator<Item = (&'a Position, Cell)> + Clone,
+    {
+        // clear everything first so the values being restored never collide with the old ones
+        for (pos, _) in cells.clone() {
+            self.insert(pos, None, CellState::Normal)
+                .expect("removal shouldn't trigger an error");
+        }
+
+        for (pos, cell) in cells {
+            self.insert(pos, cell.0, cell.1)
+                .expect("restored cells were valid when they were recorded");
+        }
+    }
+
     pub fn highlight(&mut self, val: Option<u8>) {
         if val.is_none() {
             self.highlighted = None;
@@ -393,12 +746,12 @@ impl Sudoku {
         let mut max_filled = 0;
         let mut pos = None;
 
-        for i in 0..9 {
-            for j in 0..9 {
+        for i in 0..self.size.side() {
+            for j in 0..self.size.side() {
                 if self.grid[i][j].0.is_none() {
                     let sum = self.rows[i].count_ones()
                         + self.columns[j].count_ones()
-                        + self.blocks[Sudoku::get_block_id(i, j)].count_ones();
+                        + self.blocks[self.get_block_id(i, j)].count_ones();
                     if sum > max_filled {
                         max_filled = sum;
 
@@ -411,107 +764,146 @@ impl Sudoku {
         pos
     }
 
+    /// solves the board in place, returns false (leaving the board untouched) when there is no
+    /// solution or more than one
     pub fn solve(&mut self) -> bool {
-        let mut filled_stack = vec![];
-        // let mut empty_cells_stack = self.fetch_empty_cells();
-        let mut reached_dead_end = false;
+        self.solve_within(usize::MAX).unwrap_or(false)
+    }
 
+    /// same as `solve` but gives up after visiting `budget` cells, None when it gave up
+    fn solve_within(&mut self, budget: usize) -> Option<bool> {
         let mut solutions = 0;
-        let mut initial_solution = String::new();
+        let mut budget = budget;
+        self.search_solutions(2, &mut solutions, &mut budget);
 
-        loop {
-            let next_empty_cell = self.fetch_next_empty_cell();
+        if budget == 0 {
+            return None;
+        }
 
-            // if no other way to go
-            if next_empty_cell.is_none() && filled_stack.is_empty() {
-                return false;
+        if solutions != 1 {
+            return Some(false);
+        }
+
+        for i in 0..self.size.side() {
+            for j in 0..self.size.side() {
+                if self.grid[i][j].0.is_none() {
+                    self.insert(
+                        &Position::new(i, j),
+                        self.solved_grid[i][j].0,
+                        CellState::Normal,
+                    )
+                    .expect("solution fits the board");
+                }
             }
+        }
 
-            if next_empty_cell.is_none() && !reached_dead_end {
-                solutions += 1;
+        Some(self.is_board_solved_completely())
+    }
 
-                self.solved_grid = self.grid;
+    /// backtracking over the cell with the fewest candidates, stops once `limit` solutions are
+    /// found or the budget runs out. The first solution found is kept in `solved_grid`, the grid
+    /// is restored on return
+    fn search_solutions(&mut self, limit: usize, solutions: &mut usize, budget: &mut usize) {
+        if *budget == 0 {
+            return;
+        }
 
-                if solutions >= 2 {
-                    if !initial_solution.is_empty() {
-                        if initial_solution == self.to_str() {
-                            return true;
-                        }
-                    }
+        *budget -= 1;
 
-                    self.solved_grid = [[(None, CellState::Normal); 9]; 9];
-                    return false;
+        let mut next: Option<(usize, usize, u32)> = None;
+
+        'scan: for i in 0..self.size.side() {
+            for j in 0..self.size.side() {
+                if self.grid[i][j].0.is_some() {
+                    continue;
                 }
 
-                initial_solution = self.to_str();
-                reached_dead_end = true;
+                let candidates = self.candidates_at(i, j);
+
+                // dead end, some cell can't take any value
+                if candidates == 0 {
+                    return;
+                }
+
+                if next.is_none_or(|n| candidates.count_ones() < n.2.count_ones()) {
+                    next = Some((i, j, candidates));
+
+                    if candidates.count_ones() == 1 {
+                        break 'scan;
+                    }
+                }
             }
+        }
 
-            // println!("{self}");
-            // println!("{}", self.to_str());
+        let (x, y, mut candidates) = match next {
+            Some(n) => n,
+            None => {
+                *solutions += 1;
 
-            if reached_dead_end {
-                if filled_stack.is_empty() {
-                    reached_dead_end = false;
-                    continue;
+                if *solutions == 1 {
+                    self.solved_grid = self.grid.clone();
                 }
 
-                let filled_pos = filled_stack.pop().unwrap();
+                return;
+            }
+        };
 
-                let val = self.get(&filled_pos);
+        let pos = Position::new(x, y);
 
-                match val {
-                    Some(v) => {
-                        if v == 9 {
-                            self.insert(&filled_pos, None, CellState::Normal)
-                                .expect("this is removal");
-                            // empty_cells_stack.push(filled_pos.clone());
-                        }
+        while candidates != 0 && *solutions < limit {
+            let v = candidates.trailing_zeros() as u8;
+            candidates &= candidates - 1;
 
-                        for i in v + 1..=9 {
-                            if self.insert(&filled_pos, Some(i), CellState::Normal).is_ok() {
-                                filled_stack.push(filled_pos.clone());
-                                reached_dead_end = false;
-                                break;
-                            }
+            self.insert(&pos, Some(v), CellState::Normal)
+                .expect("candidate fits the cell");
+            self.search_solutions(limit, solutions, budget);
+        }
 
-                            if i == 9 {
-                                self.insert(&filled_pos, None, CellState::Normal)
-                                    .expect("this is removal");
-                                // empty_cells_stack.push(filled_pos.clone());
-                            }
-                        }
+        self.insert(&pos, None, CellState::Normal)
+            .expect("this is removal");
+    }
+
+    /// fills every empty cell with random values that respect the rules, false if impossible
+    fn fill_randomly<R: Rng>(&mut self, rng: &mut R) -> bool {
+        let mut next: Option<(usize, usize, u32)> = None;
+
+        for i in 0..self.size.side() {
+            for j in 0..self.size.side() {
+                if self.grid[i][j].0.is_none() {
+                    let candidates = self.candidates_at(i, j);
+
+                    if next.is_none_or(|n| candidates.count_ones() < n.2.count_ones()) {
+                        next = Some((i, j, candidates));
                     }
-                    None => return false,
-                }
-            } else {
-                if next_empty_cell.is_none() {
-                    break;
                 }
+            }
+        }
 
-                let empty_pos = next_empty_cell.unwrap();
+        let (x, y, candidates) = match next {
+            Some(n) => n,
+            None => return true,
+        };
 
-                for i in 1..=9 {
-                    if self.insert(&empty_pos, Some(i), CellState::Normal).is_ok() {
-                        filled_stack.push(empty_pos.clone());
-                        break;
-                    }
+        let pos = Position::new(x, y);
+        let mut values = (1..=self.size.side() as u8)
+            .filter(|v| candidates & (1 << v) != 0)
+            .collect::<Vec<u8>>();
 
-                    if i == 9 {
-                        if filled_stack.is_empty() {
-                            return solutions == 1;
-                        }
+        seeded_rng::shuffle(rng, &mut values);
 
-                        self.insert(&empty_pos, None, CellState::Normal)
-                            .expect("this is removal");
-                        // empty_cells_stack.push(empty_pos.clone());
-                        reached_dead_end = true;
-                    }
-                }
+        for v in values {
+            self.insert(&pos, Some(v), CellState::Normal)
+                .expect("candidate fits the cell");
+
+            if self.fill_randomly(rng) {
+                return true;
             }
         }
 
-        self.is_board_solved_completely()
+        self.insert(&pos, None, CellState::Normal)
+            .expect("this is removal");
+
+        false
     }
 
     pub fn reset(&mut self) {
@@ -521,12 +913,9 @@ impl Sudoku {
                 if !(self.prefilled_positions.contains_key(&pos)
                     || self.grid[i.0][j.0].1 == CellState::UserMarkedDefault)
                 {
-                    match self.grid[i.0][j.0].0 {
-                        Some(v) => {
-                            self.update_maps(&pos, v, UpdateMapsType::Remove)
-                                .expect("removal doesn't trigger error");
-                        }
-                        None => (),
+                    if let Some(v) = self.grid[i.0][j.0].0 {
+                        self.update_maps(&pos, v, UpdateMapsType::Remove)
+                            .expect("removal doesn't trigger error");
                     }
 
                     self.grid[i.0][j.0].0 = None;
@@ -540,12 +929,9 @@ impl Sudoku {
             for j in i.1.iter().enumerate() {
                 let pos = Position::new(i.0, j.0);
                 if !self.prefilled_positions.contains_key(&pos) {
-                    match self.grid[i.0][j.0].0 {
-                        Some(v) => {
-                            self.update_maps(&pos, v, UpdateMapsType::Remove)
-                                .expect("removal doesn't trigger error");
-                        }
-                        None => (),
+                    if let Some(v) = self.grid[i.0][j.0].0 {
+                        self.update_maps(&pos, v, UpdateMapsType::Remove)
+                            .expect("removal doesn't trigger error");
                     }
 
                     self.grid[i.0][j.0].0 = None;
@@ -556,174 +942,581 @@ impl Sudoku {
 }
 
 impl Sudoku {
-    pub fn generate_random_board(number_of_clues: u8, callback: fn(usize)) -> Option<Self> {
+    /// random board with a unique solution and givens laid out with the symmetry, none when the
+    /// rule set or one of the extra rules doesn't support the size, when the symmetry can't lay
+    /// out that many givens or when no killer board with that few givens was found. The callback
+    /// is told how many boards were thrown away so far
+    pub fn generate_random_board(
+        size: BoardSize,
+        variant: Variant,
+        rules: &[ExtraRule],
+        number_of_clues: u8,
+        symmetry: Symmetry,
+        mut callback: impl FnMut(usize),
+    ) -> Option<Self> {
+        if !variant.supports(size) || rules.iter().any(|r| !r.supports(size, variant)) {
+            return None;
+        }
+
+        // killer boards need few givens, if any
+        if variant.has_cages() {
+            return Sudoku::random_killer_board(
+                size,
+                rules,
+                number_of_clues.min(size.max_clues()),
+                symmetry,
+                Some(&mut callback),
+                &mut rand::rng(),
+            );
+        }
+
+        let number_of_clues = number_of_clues.clamp(size.min_clues(), size.max_clues());
+
+        if !symmetry.reaches(size.side(), number_of_clues as usize) {
+            return None;
+        }
+
+        Sudoku::random_board(
+            size,
+            variant,
+            rules,
+            &Layout::Random(number_of_clues, symmetry),
+            None,
+            Some(&mut callback),
+            &mut rand::rng(),
+        )
+    }
+
+    /// same seed (ex: a date) and number of clues always yield the same board
+    pub fn generate_seeded_board(
+        number_of_clues: u8,
+        seed: &str,
+        mut callback: impl FnMut(usize),
+    ) -> Option<Self> {
         let number_of_clues = number_of_clues.clamp(10, 80);
-        Sudoku::random_board(&number_of_clues, None, Some(callback))
+        let mut rng = SeededRng::new(Sudoku::hash_seed(seed));
+        Sudoku::random_board(
+            BoardSize::CLASSIC,
+            Variant::Classic,
+            &[],
+            &Layout::Random(number_of_clues, Symmetry::None),
+            None,
+            Some(&mut callback),
+            &mut rng,
+        )
     }
 
+    /// FNV-1a, unlike `DefaultHasher` it is guaranteed to stay the same across releases
+    fn hash_seed(seed: &str) -> u64 {
+        seed.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+
+    /// generates classic boards on all the physical cores. Puzzle `i` of a run is always drawn from
+    /// a stream derived from `(seed, i)`, so the same seed and number of clues regenerate the same
+    /// set of boards regardless of the number of threads. Returns the boards, the number of threads
+    /// used and the seed of the run (a random one when none is given). With `minimal_only` every
+    /// board is minimal, none of its clues can be removed. With `resume` the boards an earlier
+    /// run with the seed (and the same settings) stored are kept and only the rest are generated,
+    /// without a seed any stored boards generated with the same settings are kept instead.
+    /// A cancelled run returns the boards found so far, a run asking for a number of clues the
+    /// symmetry can't lay out returns none. Boards an earlier run found invalid are skipped,
+    /// along with about `false_positive_rate` of the others as the cache is only held as a
+    /// filter. The observer hears how the run is going whenever a board is found and a few
+    /// times a second otherwise
+    #[allow(clippy::too_many_arguments)]
     pub fn generate_random_boards(
         number_of_clues: u8,
+        symmetry: Symmetry,
+        minimal_only: bool,
         number_of_puzzles: usize,
         just_print: bool,
-    ) -> (Vec<Self>, usize) {
+        false_positive_rate: f64,
+        seed: Option<u64>,
+        resume: bool,
+        cancel: &CancellationToken,
+        observer: &mut dyn ProgressObserver,
+    ) -> (Vec<Self>, usize, u64) {
         let number_of_clues = number_of_clues.clamp(10, 80);
 
-        // let num_threads = std::cmp::max(1, num_cpus::get().saturating_sub(1));
-        // let num_threads = 1;
-        let num_threads = num_cpus::get_physical();
+        Sudoku::generate_boards(
+            Layout::Random(number_of_clues, symmetry),
+            minimal_only,
+            number_of_puzzles,
+            num_cpus::get_physical(),
+            just_print,
+            false_positive_rate,
+            seed,
+            resume,
+            cancel,
+            observer,
+        )
+    }
 
-        let mut file_number = 0;
+    /// stored boards a resumed run starts from, along with their index. A seeded run gets back
+    /// the boards an earlier run with the seed stored, an unseeded one the generated boards with
+    /// the same number of givens. Either way only the boards that fit the layout count
+    fn resumed_boards(
+        db: &PuzzleDb,
+        layout: &Layout,
+        minimal_only: bool,
+        number_of_puzzles: usize,
+        seeded: bool,
+        seed: u64,
+    ) -> Result<Vec<(usize, Sudoku)>, Box<dyn Error>> {
+        let number_of_clues = match layout {
+            Layout::Random(n, _) => *n,
+            Layout::Mask(mask) => mask.number_of_clues(),
+        };
+
+        let mut boards: Vec<(usize, Sudoku)> = vec![];
+
+        for record in db.query(Some(number_of_clues), None, false) {
+            if boards.len() >= number_of_puzzles {
+                break;
+            }
+
+            let puzzle_index = match record.get_source() {
+                // the index in the earlier run, when the seed matches
+                Source::Generated {
+                    seed: s,
+                    puzzle_index,
+                } if seeded => {
+                    if s != seed
+                        || puzzle_index >= number_of_puzzles
+                        || boards.iter().any(|(i, _)| *i == puzzle_index)
+                    {
+                        continue;
+                    }
+
+                    puzzle_index
+                }
+                // without a seed there is no earlier run to match, the boards are numbered
+                Source::Generated { .. } => boards.len(),
+                _ => continue,
+            };
+
+            let board = record.to_board()?;
+
+            if board.fits_layout(layout, minimal_only) {
+                boards.push((puzzle_index, board));
+            }
+        }
+
+        Ok(boards)
+    }
+
+    /// worker setup shared by the batch runs, see `generate_random_boards`. The public runs use
+    /// a thread per physical core
+    #[allow(clippy::too_many_arguments)]
+    fn generate_boards(
+        layout: Layout,
+        minimal_only: bool,
+        number_of_puzzles: usize,
+        num_threads: usize,
+        just_print: bool,
+        false_positive_rate: f64,
+        seed: Option<u64>,
+        resume: bool,
+        cancel: &CancellationToken,
+        observer: &mut dyn ProgressObserver,
+    ) -> (Vec<Self>, usize, u64) {
+        let seeded = seed.is_some();
+        let seed = seed.unwrap_or_else(|| rand::rng().next_u64());
+
+        // invalid records are kept per number of clues, whatever layout they came from
+        let number_of_clues = match &layout {
+            Layout::Random(n, _) => *n,
+            Layout::Mask(mask) => mask.number_of_clues(),
+        };
+
+        if let Layout::Random(n, symmetry) = &layout
+            && !symmetry.reaches(BoardSize::CLASSIC.side(), *n as usize)
+        {
+            eprintln!("{n} givens can't be laid out with the symmetry: {symmetry}");
+            return (vec![], 0, seed);
+        }
 
         let mut boards = vec![];
         let mut handlers = vec![];
         let found_counter = Arc::new(AtomicUsize::new(0));
-        let total_seen_counter = Arc::new(AtomicUsize::new(0));
-
-        let dashset: Arc<DashSet<DietBoard>> = Arc::new(DashSet::new());
+        let thread_counters = (0..num_threads)
+            .map(|_| Arc::new(ThreadCounters::default()))
+            .collect::<Vec<Arc<ThreadCounters>>>();
 
         let mut invalid_inps = vec![];
 
-        if !just_print {
-            // fetch data from files and feed the dashset with invalid records
-            loop {
-                match Sudoku::read_lines(
-                    Sudoku::invalid_file_name(number_of_clues, file_number),
-                    |v| {
-                        dashset.insert(v);
-                    },
-                ) {
-                    Ok(v) => {
-                        if !v {
-                            break;
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("error reading lines: {e}");
-                        return (vec![], 0);
-                    }
+        // a failed write stops the caching, the boards found are still returned
+        let mut cache_invalid = !just_print;
+
+        // boards the cache knows are invalid are skipped by the workers
+        let invalid_filter = match just_print {
+            true => InvalidFilter::new(0, false_positive_rate),
+            false => match Sudoku::load_invalid_filter(
+                Path::new(PUZZLE_DIR),
+                number_of_clues,
+                false_positive_rate,
+            ) {
+                Ok(f) => f,
+                Err(e) => {
+                    eprintln!("error reading the invalid board cache: {e}");
+                    return (vec![], 0, seed);
                 }
+            },
+        };
+        let invalid_filter = Arc::new(invalid_filter);
 
-                file_number += 1;
-            }
-        }
+        // valid puzzles already stored, a seed regenerates them
+        let mut db = match just_print {
+            true => None,
+            false => match PuzzleDb::open() {
+                Ok(d) => Some(d),
+                Err(e) => {
+                    eprintln!("error opening the puzzle database: {e}");
+                    return (vec![], 0, seed);
+                }
+            },
+        };
 
-        if !just_print {
-            // also add all the valid puzzles to the set
-            match Sudoku::read_lines(Sudoku::valid_file_name(number_of_clues), |v| {
-                dashset.insert(v);
-            }) {
-                Ok(_) => (),
+        if resume && let Some(d) = db.as_ref() {
+            match Sudoku::resumed_boards(d, &layout, minimal_only, number_of_puzzles, seeded, seed)
+            {
+                Ok(b) => boards = b,
                 Err(e) => {
-                    eprintln!("error reading lines (valid puzzles): {e}");
-                    return (vec![], 0);
+                    eprintln!("error reading a stored puzzle of the run: {e}");
+                    return (vec![], 0, seed);
                 }
             }
         }
 
-        total_seen_counter.store(dashset.len(), Ordering::Relaxed);
+        found_counter.store(boards.len(), Ordering::Relaxed);
+
+        let missing = (0..number_of_puzzles)
+            .filter(|i| !boards.iter().any(|(idx, _)| idx == i))
+            .collect::<Vec<usize>>();
 
+        let tracker =
+            ProgressTracker::new(boards.len(), number_of_puzzles, thread_counters.clone());
         let (tx, rx) = mpsc::channel::<DataTxPacket>();
 
-        for _ in 0..num_threads {
+        for (thread_id, counters) in thread_counters.into_iter().enumerate() {
             let tx_clone = tx.clone();
             let found_counter_clone = found_counter.clone();
-            let total_seen_counter_clone = total_seen_counter.clone();
-            let dashset_clone = dashset.clone();
+            let invalid_filter = invalid_filter.clone();
+            let layout = layout.clone();
+            let missing = missing.clone();
+            let cancel = cancel.clone();
 
             handlers.push(thread::spawn(move || {
-                loop {
+                // every thread takes every `num_threads`th missing puzzle of the run
+                for puzzle_index in missing.into_iter().skip(thread_id).step_by(num_threads) {
+                    if cancel.is_cancelled() {
+                        break;
+                    }
+
                     Sudoku::random_board(
-                        &number_of_clues,
+                        BoardSize::CLASSIC,
+                        Variant::Classic,
+                        &[],
+                        &layout,
                         Some(RandomBoardsRequestArgs {
                             number_of_puzzles,
+                            puzzle_index,
                             number_of_found_counter: found_counter_clone.clone(),
-                            total_number_of_puzzles_searched: total_seen_counter_clone.clone(),
-                            completed_set: dashset_clone.clone(),
+                            counters: counters.clone(),
+                            invalid_filter: invalid_filter.clone(),
+                            taken: Arc::new(DashSet::new()),
                             tx: tx_clone.clone(),
+                            minimal_only,
+                            cancel: cancel.clone(),
                         }),
                         None,
+                        &mut Sudoku::puzzle_rng(seed, puzzle_index),
                     );
-
-                    if found_counter_clone.load(Ordering::Relaxed) >= number_of_puzzles as usize {
-                        drop(tx_clone);
-                        break;
-                    }
                 }
+
+                drop(tx_clone);
             }));
         }
 
         drop(tx);
 
-        for m in rx {
-            match m {
-                DataTxPacket::Invalid(v) => {
-                    invalid_inps.push(v);
-                    if !just_print {
-                        if invalid_inps.len() >= MAX_NUMBER_OF_RECORDS_IN_A_FILE {
-                            match Sudoku::export_to_file(
-                                Sudoku::invalid_file_name(number_of_clues, file_number),
-                                &invalid_inps,
-                            ) {
-                                Ok(_) => file_number += 1,
-                                Err(e) => {
-                                    eprintln!("Error dumping to file. Error: {e}");
-                                    return (vec![], 0);
-                                }
-                            };
-                            invalid_inps = vec![];
-                        }
-                    }
+        // invalid boards go to the cache and valid ones to the database, the valid ones are
+        // handed back with their index
+        let mut store = |m: DataTxPacket| match m {
+            DataTxPacket::Invalid(v) => {
+                if !cache_invalid {
+                    return None;
                 }
-                DataTxPacket::Valid(b) => {
-                    if !just_print {
-                        Sudoku::append_to_file(Sudoku::valid_file_name(number_of_clues), &b)
-                            .expect("error writting a valid puzzle to file");
+
+                invalid_inps.push(v);
+
+                if invalid_inps.len() >= INVALID_RECORDS_PER_WRITE {
+                    if let Err(e) = Sudoku::append_to_invalid_cache(
+                        Path::new(PUZZLE_DIR),
+                        number_of_clues,
+                        &invalid_inps,
+                    ) {
+                        eprintln!(
+                            "\nError dumping to file, invalid boards aren't cached any more. Error: {e}"
+                        );
+                        cache_invalid = false;
                     }
 
-                    boards.push(b);
+                    invalid_inps.clear();
+                }
 
-                    found_counter.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            DataTxPacket::Valid(b, puzzle_index) => {
+                if let Some(d) = db.as_mut()
+                    && let Err(e) = d.insert(&b, Source::Generated { seed, puzzle_index })
+                {
+                    eprintln!(
+                        "\nError writing a valid puzzle to the database, the rest of the run isn't stored. Error: {e}"
+                    );
+                    db = None;
                 }
+
+                Some((puzzle_index, *b))
             }
+        };
 
-            print!(
-                "\rProgress: {}/{}                   ",
-                found_counter.load(Ordering::Relaxed),
-                total_seen_counter.load(Ordering::Relaxed)
-            );
-            io::stdout().flush().unwrap();
+        let mut last_report = Instant::now();
+        tracker.report(found_counter.load(Ordering::Relaxed), observer);
+
+        loop {
+            // waking up without a packet still reports, so the rates move while nothing is found
+            let m = match rx.recv_timeout(PROGRESS_INTERVAL) {
+                Ok(m) => Some(m),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+
+            let found = match m.and_then(&mut store) {
+                Some(b) => {
+                    boards.push(b);
+                    found_counter.fetch_add(1, Ordering::Relaxed);
+                    true
+                }
+                None => false,
+            };
+
+            if found || last_report.elapsed() >= PROGRESS_INTERVAL {
+                tracker.report(found_counter.load(Ordering::Relaxed), observer);
+                last_report = Instant::now();
+            }
         }
 
         for handler in handlers {
             handler.join().expect("error join the thread handler");
         }
 
-        if !just_print {
-            if invalid_inps.len() > 0 {
-                match Sudoku::export_to_file(
-                    Sudoku::invalid_file_name(number_of_clues, file_number),
-                    &invalid_inps,
-                ) {
-                    Ok(_) => (),
-                    Err(e) => {
-                        eprintln!("Error dumping to file. Error: {e}");
-                        return (vec![], 0);
-                    }
-                };
+        // relabelled, transposed or shuffled copies are only sorted out once every worker is done,
+        // so which thread drew first doesn't matter: the lower index keeps the board and the
+        // higher one draws again from its own stream, skipping the boards of the lower ones. The
+        // canonical form takes 220-360µs on a board with 24 to 36 givens, three to four times the
+        // uniqueness check of a candidate, so only the boards found pay for it
+        boards.sort_by_key(|(idx, _)| *idx);
+
+        let taken = Arc::new(DashSet::new());
+        let mut kept = Vec::with_capacity(boards.len());
+
+        for (puzzle_index, mut board) in boards {
+            while !taken.insert(board.canonical_pack()) {
+                let (tx, rx) = mpsc::channel::<DataTxPacket>();
+
+                let redrawn = Sudoku::random_board(
+                    BoardSize::CLASSIC,
+                    Variant::Classic,
+                    &[],
+                    &layout,
+                    Some(RandomBoardsRequestArgs {
+                        number_of_puzzles: 1,
+                        puzzle_index,
+                        number_of_found_counter: Arc::new(AtomicUsize::new(0)),
+                        counters: Arc::new(ThreadCounters::default()),
+                        invalid_filter: invalid_filter.clone(),
+                        taken: taken.clone(),
+                        tx,
+                        minimal_only,
+                        cancel: cancel.clone(),
+                    }),
+                    None,
+                    &mut Sudoku::puzzle_rng(seed, puzzle_index),
+                );
+
+                rx.into_iter().for_each(|m| {
+                    store(m);
+                });
+
+                match redrawn {
+                    Some(b) => board = b,
+                    // cancelled, the index stays missing
+                    None => break,
+                }
+            }
+
+            if taken.len() > kept.len() {
+                kept.push(board);
             }
         }
 
-        (boards, num_threads)
+        tracker.report(kept.len(), observer);
+
+        if cache_invalid
+            && !invalid_inps.is_empty()
+            && let Err(e) = Sudoku::append_to_invalid_cache(
+                Path::new(PUZZLE_DIR),
+                number_of_clues,
+                &invalid_inps,
+            )
+        {
+            eprintln!("\nError dumping to file. Error: {e}");
+        }
+
+        (kept, num_threads, seed)
     }
 
+    /// whether the givens of a stored board follow the layout of a batch run
+    fn fits_layout(&self, layout: &Layout, minimal_only: bool) -> bool {
+        if self.size != BoardSize::CLASSIC || self.variant != Variant::Classic {
+            return false;
+        }
+
+        let givens = &self.prefilled_positions;
+
+        let fits = match layout {
+            Layout::Random(n, symmetry) => {
+                givens.len() == *n as usize
+                    && givens.keys().all(|p| {
+                        symmetry
+                            .orbit(self.size.side(), p.x, p.y)
+                            .into_iter()
+                            .all(|(x, y)| givens.contains_key(&Position::new(x, y)))
+                    })
+            }
+            Layout::Mask(mask) => {
+                givens.len() == mask.number_of_clues() as usize
+                    && givens.keys().all(|p| mask.contains(p.x, p.y))
+            }
+        };
+
+        fits && (!minimal_only || self.is_minimal())
+    }
+
+    /// rng for the `puzzle_index`th puzzle of a seeded run
+    fn puzzle_rng(seed: u64, puzzle_index: usize) -> SeededRng {
+        SeededRng::new(Sudoku::hash_seed(&format!("{seed}/{puzzle_index}")))
+    }
+
+    /// reads a board layout written by `to_str`, a thonky or 7sudoku string, or an f-puzzles or
+    /// sudokupad link
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(inp: &str) -> Result<Self, Box<dyn Error>> {
+        if fpuzzles::is_fpuzzles(inp) {
+            return Sudoku::from_fpuzzles(inp);
+        }
+
         let mut inp = inp.trim().to_string();
+        let mut variant = Variant::Classic;
+        let mut cages = vec![];
+        let mut regions = None;
+        let mut constraints = vec![];
+
+        if let Some((tag, cells)) = inp.split_once(':') {
+            variant = match Variant::from_tag(tag.trim()) {
+                Some(v) => v,
+                None => return Err(format!("unknown variant found: {tag}").into()),
+            };
+
+            inp = cells.trim().to_string();
+        }
+
+        if let Some((cells, constraint_list)) = inp.clone().split_once('#') {
+            for c in constraint_list.split('#') {
+                constraints.push(parse_constraint(c)?);
+            }
+
+            inp = cells.to_string();
+        }
+
+        if let Some((cells, region_map)) = inp.clone().split_once('|') {
+            if !variant.has_regions() {
+                return Err("region map found on a board without irregular regions".into());
+            }
+
+            regions = Some(region_map.to_string());
+            inp = cells.to_string();
+        }
+
+        if let Some((cells, cage_list)) = inp.split_once(';') {
+            if !variant.has_cages() {
+                return Err("cages found on a board without cages".into());
+            }
+
+            for c in cage_list.split(';') {
+                cages.push(Cage::parse(c)?);
+            }
+
+            inp = cells.to_string();
+        }
+
+        let (size, res) = Sudoku::grid_from_str(&inp)?;
+
+        let regions = match regions {
+            Some(r) => Some(Sudoku::regions_from_str(size, &r)?),
+            None if variant.has_regions() => {
+                return Err("jigsaw board found without a region map".into());
+            }
+            None => None,
+        };
+
+        Sudoku::build(size, variant, cages, regions, constraints, res)
+    }
+
+    /// board with the given rules and cells, fails when the cells break a rule or can't be
+    /// completed
+    fn build(
+        size: BoardSize,
+        variant: Variant,
+        cages: Vec<Cage>,
+        regions: Option<Vec<Vec<usize>>>,
+        constraints: Vec<Arc<dyn Constraint>>,
+        grid: Board,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut sudoku = Sudoku::empty(size, variant);
+        sudoku.set_cages(cages)?;
+
+        if let Some(r) = regions {
+            sudoku.set_regions(r)?;
+        }
+
+        for c in constraints {
+            sudoku.add_constraint(c)?;
+        }
+
+        sudoku.fill_grid(grid)?;
+
+        if sudoku.solve() {
+            sudoku.reset();
+            return Ok(sudoku);
+        }
+
+        Err("invalid board given".into())
+    }
+
+    /// cells of a board layout without tag, cages or regions, the size follows from the number
+    /// of cells
+    fn grid_from_str(inp: &str) -> Result<(BoardSize, Board), Box<dyn Error>> {
+        let mut inp = inp.to_string();
 
         if inp.contains(".") {
             inp = Sudoku::from_thonky_str(&inp);
         } else if inp.contains("0") {
-	    inp = Sudoku::from_7sudoku_com(&inp);
-	}
+            inp = Sudoku::from_7sudoku_com(&inp);
+        }
 
         let split = inp.split(",");
 
@@ -731,21 +1524,22 @@ impl Sudoku {
 
         let cell_count = split_cells.len();
 
-        if cell_count != Sudoku::TOTAL_POSITIONS {
-            return Err(format!(
-                "invalid input found, expected {} cells, found {}",
-                Sudoku::TOTAL_POSITIONS,
-                cell_count
-            )
-            .into());
-        }
-
-        let mut prefilled_positions = HashMap::new();
+        let size = match BoardSize::from_total_positions(cell_count) {
+            Some(s) => s,
+            None => {
+                return Err(format!(
+                    "invalid input found, expected {} cells (or the cells of another supported size), found {}",
+                    BoardSize::CLASSIC.total_positions(),
+                    cell_count
+                )
+                .into());
+            }
+        };
 
         let mut list: Vec<(Option<u8>, CellState)> = vec![];
 
-        for sc in split_cells.iter().enumerate() {
-            let mut v = sc.1.trim().to_string();
+        for sc in split_cells.iter() {
+            let mut v = sc.trim().to_lowercase();
 
             if v.is_empty() {
                 list.push((None, CellState::Normal));
@@ -756,7 +1550,7 @@ impl Sudoku {
 
             // user input number, would be in the form of u7, basically prefixed with a u
             if v.len() == 2 {
-                let c = v.to_lowercase().chars().collect::<Vec<char>>();
+                let c = v.chars().collect::<Vec<char>>();
 
                 if c[0] != 'u' {
                     return Err(
@@ -769,23 +1563,20 @@ impl Sudoku {
                 v = c[1].to_string();
             }
 
-            let val = v.parse::<u8>();
-
-            if val.is_err() {
-                list.push((None, CellState::Normal));
-                continue;
-            }
-
-            let val = val.unwrap();
-
-            if val < 1 || val > 9 {
-                return Err(
-                    "input values cannot contain values less than 1 or greater than 9".into(),
-                );
-            }
+            let val = match v.chars().next().and_then(char_to_digit) {
+                Some(val) if v.len() == 1 => val,
+                _ => {
+                    list.push((None, CellState::Normal));
+                    continue;
+                }
+            };
 
-            if !is_user_defined {
-                prefilled_positions.insert(Position::new(sc.0 / 9, sc.0 % 9), val);
+            if !(1..=size.side() as u8).contains(&val) {
+                return Err(format!(
+                    "input values cannot contain values less than 1 or greater than {}",
+                    digit_to_char(size.side() as u8)
+                )
+                .into());
             }
 
             if is_user_defined {
@@ -795,184 +1586,145 @@ impl Sudoku {
             }
         }
 
-        let mut blocks = [0; 9];
-        let mut columns = [0; 9];
-        let mut rows = [0; 9];
-
-        let res: Board = list
-            .chunks(9)
-            .map(|chunk| chunk.try_into().expect("error chunking the input given"))
-            .collect::<Vec<_>>()
-            .try_into()
-            .expect("given board is not of valid lengths");
-
-        for i in res.iter().enumerate() {
-            for j in i.1.iter().enumerate() {
-                if j.1.0.is_some() {
-                    let val = j.1.0.unwrap();
-
-                    if Sudoku::check_for_conflict(
-                        &[
-                            (&blocks, Sudoku::get_block_id(i.0, j.0)),
-                            (&rows, i.0),
-                            (&columns, j.0),
-                        ],
-                        val,
-                    ) {
-                        return Err("duplicate value found in row block or column".into());
-                    }
-
-                    Sudoku::insert_into_bitmap(&mut rows, i.0, val);
-                    Sudoku::insert_into_bitmap(&mut columns, j.0, val);
-                    Sudoku::insert_into_bitmap(&mut blocks, Sudoku::get_block_id(i.0, j.0), val);
-                }
-            }
-        }
-
-        let mut sudoku = Sudoku {
-            grid: res.clone(),
-            prefilled_positions,
-            solved_grid: res,
-            highlighted: None,
-            blocks,
-            rows,
-            columns,
-        };
-
-        if sudoku.solve() {
-            sudoku.reset();
-            return Ok(sudoku);
-        }
-
-        Err("invalid board given".into())
+        Ok((size, list.chunks(size.side()).map(|c| c.to_vec()).collect()))
     }
 
-    fn random_board(
-        number_of_clues: &u8,
+    fn random_board<R: Rng>(
+        size: BoardSize,
+        variant: Variant,
+        rules: &[ExtraRule],
+        layout: &Layout,
         conditonal_run_info: Option<RandomBoardsRequestArgs>,
-        callback: Option<fn(usize)>,
+        mut callback: Option<&mut dyn FnMut(usize)>,
+        rng: &mut R,
     ) -> Option<Self> {
-        let mut rng = rand::rng();
         let mut counter = 0;
 
-        'outer: loop {
-            let mut grid: Board = [[(None, CellState::Normal); 9]; 9];
+        loop {
+            if let Some(cri) = conditonal_run_info.clone()
+                && (cri.cancel.is_cancelled()
+                    || cri.number_of_found_counter.load(Ordering::Relaxed) >= cri.number_of_puzzles)
+            {
+                return None;
+            };
 
-            let mut blocks: [u16; 9] = [0; 9];
-            let mut rows: [u16; 9] = [0; 9];
-            let mut columns: [u16; 9] = [0; 9];
+            let mut solution = Sudoku::empty(size, variant);
+            solution.add_rules_before_fill(rules);
 
-            if let Some(cri) = conditonal_run_info.clone() {
-                if cri.number_of_found_counter.load(Ordering::Relaxed) >= cri.number_of_puzzles {
-                    return None;
-                }
-            };
+            if !solution.fill_randomly(rng) {
+                continue;
+            }
 
-            for i in 0..9 {
-                for j in 0..9 {
-                    let bid = Sudoku::get_block_id(i, j);
-                    let mut counter = 0;
+            if variant.has_regions() {
+                solution = solution.with_random_regions(rng);
+            }
 
-                    loop {
-                        let val = rng.random_range(1..=9) as u8;
+            solution.add_rules_after_fill(rules, rng);
 
-                        if (blocks[bid] & (1 << val)) != 0
-                            || (rows[i] & (1 << val)) != 0
-                            || (columns[j] & (1 << val)) != 0
-                        {
-                            counter += 1;
-                            if counter >= 20 {
-                                continue 'outer;
-                            }
+            let mut board = solution.blank_like();
+            let mut full_grid = solution.grid;
 
-                            continue;
+            let (number_of_clues, symmetry) = match layout {
+                Layout::Random(n, s) => (*n, *s),
+                Layout::Mask(mask) => {
+                    for (x, row) in full_grid.iter_mut().enumerate() {
+                        for (y, cell) in row.iter_mut().enumerate() {
+                            if !mask.contains(x, y) {
+                                cell.0 = None;
+                            }
                         }
+                    }
 
-                        grid[i][j].0 = Some(val);
-                        blocks[bid] |= 1 << val;
-                        rows[i] |= 1 << val;
-                        columns[j] |= 1 << val;
+                    (mask.number_of_clues(), Symmetry::None)
+                }
+            };
 
-                        break;
+            // cells are removed a whole orbit at a time, when no orbit left fits the removals the
+            // removal starts over with other orbits, the symmetry is known to reach the count
+            let grid = loop {
+                let mut grid = full_grid.clone();
+                let mut number_of_removals =
+                    grid.iter().flatten().filter(|c| c.0.is_some()).count()
+                        - number_of_clues as usize;
+
+                while number_of_removals > 0 {
+                    let x = seeded_rng::below(rng, size.side());
+                    let y = seeded_rng::below(rng, size.side());
+
+                    if grid[x][y].0.is_none() {
+                        continue;
                     }
-                }
-            }
 
-            let mut number_of_removals = 81 - number_of_clues;
+                    let orbit = symmetry.orbit(size.side(), x, y);
 
-            while number_of_removals > 0 {
-                let x = rng.random_range(0..9);
-                let y = rng.random_range(0..9);
+                    if orbit.len() <= number_of_removals {
+                        for (i, j) in &orbit {
+                            grid[*i][*j].0 = None;
+                        }
 
-                if grid[x][y].0 != None {
-                    grid[x][y].0 = None;
-                    number_of_removals -= 1;
+                        number_of_removals -= orbit.len();
+                    } else if (0..size.total_positions()).all(|k| {
+                        let (i, j) = (k / size.side(), k % size.side());
+                        grid[i][j].0.is_none()
+                            || symmetry.orbit(size.side(), i, j).len() > number_of_removals
+                    }) {
+                        break;
+                    }
                 }
-            }
 
-            let diet_grid = Sudoku::get_diet_board(&grid);
-
-            if let Some(cri) = conditonal_run_info.clone() {
-                if !cri.completed_set.insert(diet_grid) {
-                    continue;
+                if number_of_removals == 0 {
+                    break grid;
                 }
             };
 
-            let mut prefilled_positions = HashMap::new();
-
-            let mut blocks = [0; 9];
-            let mut columns = [0; 9];
-            let mut rows = [0; 9];
-
-            for i in grid.iter().enumerate() {
-                for j in i.1.iter().enumerate() {
-                    if j.1.0.is_some() {
-                        let val = j.1.0.unwrap();
-                        prefilled_positions.insert(Position::new(i.0, j.0), val);
-
-                        if Sudoku::check_for_conflict(
-                            &[
-                                (&blocks, Sudoku::get_block_id(i.0, j.0)),
-                                (&rows, i.0),
-                                (&columns, j.0),
-                            ],
-                            val,
-                        ) {
-                            continue 'outer;
-                        }
+            // batch runs are classic so the board can be packed, boards known to be invalid are
+            // skipped
+            let tried = match conditonal_run_info.as_ref() {
+                Some(cri) => {
+                    let tried = invalid_cache::pack(&Sudoku::get_diet_board(&grid));
 
-                        Sudoku::insert_into_bitmap(&mut rows, i.0, val);
-                        Sudoku::insert_into_bitmap(&mut columns, j.0, val);
-                        Sudoku::insert_into_bitmap(
-                            &mut blocks,
-                            Sudoku::get_block_id(i.0, j.0),
-                            val,
-                        );
+                    if cri.invalid_filter.contains(&tried) {
+                        continue;
                     }
-                }
-            }
 
-            let mut board = Self {
-                grid: grid.clone(),
-                prefilled_positions,
-                solved_grid: grid,
-                highlighted: None,
-                rows,
-                columns,
-                blocks,
+                    Some(tried)
+                }
+                None => None,
             };
 
-            if let Some(cri) = conditonal_run_info.clone() {
-                cri.total_number_of_puzzles_searched
-                    .fetch_add(1, Ordering::Relaxed);
+            board
+                .fill_grid(grid)
+                .expect("values come from a solved grid");
+
+            if let Some(cri) = conditonal_run_info.as_ref() {
+                cri.counters.add_searched();
             };
 
             if board.solve() {
                 board.reset();
 
+                // unique but not what the run is after, it stays out of the invalid records
+                if conditonal_run_info
+                    .as_ref()
+                    .is_some_and(|cri| cri.minimal_only && !board.is_minimal())
+                {
+                    continue;
+                }
+
+                // copies of a board a lower index keeps are drawn again
+                if conditonal_run_info.as_ref().is_some_and(|cri| {
+                    !cri.taken.is_empty() && cri.taken.contains(&board.canonical_pack())
+                }) {
+                    continue;
+                }
+
                 if let Some(cri) = conditonal_run_info.clone() {
+                    cri.counters.add_found();
                     cri.tx
-                        .send(DataTxPacket::Valid(board.clone()))
+                        .send(DataTxPacket::Valid(
+                            Box::new(board.clone()),
+                            cri.puzzle_index,
+                        ))
                         .expect("error sending on channel");
                 };
 
@@ -981,13 +1733,14 @@ impl Sudoku {
 
             counter += 1;
 
-            if let Some(cri) = conditonal_run_info.clone() {
+            if let (Some(cri), Some(tried)) = (conditonal_run_info.clone(), tried) {
+                cri.counters.add_invalid();
                 cri.tx
-                    .send(DataTxPacket::Invalid(diet_grid))
+                    .send(DataTxPacket::Invalid(tried))
                     .expect("error send data on thread");
             };
 
-            if let Some(cb) = callback {
+            if let Some(cb) = callback.as_mut() {
                 cb(counter);
             }
         }
@@ -995,50 +1748,25 @@ impl Sudoku {
 
     #[inline]
     fn get_diet_board(board: &Board) -> DietBoard {
-        let mut v: DietBoard = [0; 81];
-        let mut counter = 0;
-
-        for i in board {
-            for j in i {
-                match j.0 {
-                    None => (),
-                    Some(k) => v[counter] = k,
-                }
-
-                counter += 1;
-            }
-        }
-
-        v
+        board
+            .iter()
+            .flat_map(|row| row.iter().map(|c| c.0.unwrap_or(0)))
+            .collect()
     }
 
     /// returns true if there is a conflict
-    fn check_for_conflict(maps: &[(&[u16; 9], usize); 3], v: u8) -> bool {
-        maps.iter().any(|(m, i)| (m[*i] & (1u16 << v)) != 0)
+    fn check_for_conflict(maps: &[(&[u32], usize)], v: u8) -> bool {
+        maps.iter().any(|(m, i)| (m[*i] & (1u32 << v)) != 0)
     }
 
-    fn insert_into_bitmap(map: &mut [u16; 9], idx: usize, v: u8) {
+    fn insert_into_bitmap(map: &mut [u32], idx: usize, v: u8) {
         map[idx] |= 1 << v;
     }
 
-    fn get(&self, pos: &Position) -> Option<u8> {
-        self.grid[pos.x][pos.y].0
-    }
-
-    #[inline]
-    fn invalid_file_name(number_of_clues: u8, file_number: i32) -> String {
-        format!("clues_{number_of_clues}/invalid_{number_of_clues}_{file_number}")
-    }
-
-    #[inline]
-    fn valid_file_name(number_of_clues: u8) -> String {
-        format!("clues_{number_of_clues}/valid_puzzles_{number_of_clues}")
-    }
-
-    fn read_lines<P, F>(filename: P, process_line: F) -> Result<bool, Box<dyn Error>>
+    fn read_lines<P, F>(filename: P, mut process_line: F) -> Result<bool, Box<dyn Error>>
     where
         P: AsRef<Path>,
-        F: Fn(DietBoard),
+        F: FnMut(DietBoard),
     {
         let file = match File::open(filename) {
             Ok(r) => r,
@@ -1054,44 +1782,12 @@ impl Sudoku {
 
         for line_result in reader.lines() {
             let line = line_result?;
-            process_line(Sudoku::thonky_to_diet_board(&line)?);
+            process_line(Sudoku::thonky_to_diet_board(line.trim())?);
         }
 
         Ok(true)
     }
 
-    fn export_to_file<P>(filename: P, lines: &Vec<DietBoard>) -> Result<bool, Box<dyn Error>>
-    where
-        P: AsRef<Path>,
-    {
-        let mut file = File::create(filename)?;
-
-        for line in lines {
-            file.write_all(Sudoku::diet_board_to_thonky(&line).unwrap().as_bytes())?;
-            file.write_all(b"\n")?;
-        }
-
-        Ok(true)
-    }
-
-    fn append_to_file<P>(filename: P, board: &Sudoku) -> Result<bool, Box<dyn Error>>
-    where
-        P: AsRef<Path>,
-    {
-        let mut file = OpenOptions::new()
-            .append(true)
-            .create(true)
-            .open(filename)?;
-
-        let str_to_append = board.to_thonky_str();
-
-        // Write the data to the end of the file.
-        file.write_all(str_to_append.as_bytes())?;
-        file.write_all(b"\n")?;
-
-        Ok(true)
-    }
-
     fn thonky_to_diet_board(s: &str) -> Result<DietBoard, String> {
         if s.len() != 81 {
             return Err(format!(
@@ -1111,28 +1807,7 @@ impl Sudoku {
             }
         }
 
-        vec.try_into()
-            .map_err(|v: Vec<u8>| format!("Expected a Vec of length 81, but got {}", v.len()))
-    }
-
-    fn diet_board_to_thonky(board: &DietBoard) -> Result<String, String> {
-        let mut result = String::with_capacity(81);
-
-        for &value in board.iter() {
-            match value {
-                0 => result.push('.'),
-                1..=9 => {
-                    let digit_char = std::char::from_digit(value as u32, 10)
-                        .ok_or_else(|| format!("Invalid digit value: {}", value))?;
-                    result.push(digit_char);
-                }
-                _ => {
-                    return Err(format!("Invalid value in array: {}", value));
-                }
-            }
-        }
-
-        Ok(result)
+        Ok(vec)
     }
 
     fn from_thonky_str(s: &str) -> String {
@@ -1145,8 +1820,12 @@ impl Sudoku {
     }
 
     fn from_7sudoku_com(s: &str) -> String {
-	let s = s.split("").filter(|v| !v.is_empty()).collect::<Vec<&str>>().join(",");
+        let s = s
+            .split("")
+            .filter(|v| !v.is_empty())
+            .collect::<Vec<&str>>()
+            .join(",");
 
-	s.replace("0", "")
+        s.replace("0", "")
     }
 }
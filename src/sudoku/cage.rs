@@ -0,0 +1,513 @@
+use std::{error::Error, fmt::Display};
+
+use colored::Colorize;
+use rand::Rng;
+
+use super::{
+    BoardSize, CellState, ExtraRule, Position, Sudoku, Symmetry, Variant,
+    constraint::{cells_from_str, cells_to_str},
+    coordinate_to_char, seeded_rng,
+};
+
+/// most cells a generated cage is grown to
+const MAX_CAGE_LEN: usize = 4;
+
+/// partitions tried per solved grid before a new grid is filled
+const PARTITION_ATTEMPTS: usize = 20;
+
+/// solved grids tried before giving up, too few givens on a big board may never be enough
+const GRID_ATTEMPTS: usize = 10;
+
+/// cells the uniqueness check may visit before the board counts as ambiguous, bigger boards
+/// with few givens can otherwise take minutes to prove unique
+const UNIQUENESS_BUDGET: usize = 50_000;
+
+/// group of cells whose values add up to `sum`, no value repeats within a cage
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Cage {
+    sum: u32,
+    cells: Vec<Position>,
+}
+
+/// written as `<sum>=<xy>+<xy>+...`, ex: 12=00+01+10
+impl Display for Cage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.sum, cells_to_str(&self.cells))
+    }
+}
+
+impl Cage {
+    pub fn new(sum: u32, cells: Vec<Position>) -> Self {
+        Cage { sum, cells }
+    }
+
+    pub fn get_sum(&self) -> u32 {
+        self.sum
+    }
+
+    pub fn get_cells(&self) -> &[Position] {
+        &self.cells
+    }
+
+    pub fn parse(inp: &str) -> Result<Self, Box<dyn Error>> {
+        let (sum, cells) = match inp.trim().split_once('=') {
+            Some(v) => v,
+            None => return Err(format!("invalid cage found, expected sum=xy+xy: {inp}").into()),
+        };
+
+        let sum = sum.trim().parse::<u32>()?;
+
+        Ok(Cage::new(sum, cells_from_str(cells)?))
+    }
+}
+
+/// values still allowed in the empty cells of a cage, given what is already placed in it
+#[inline]
+fn cage_candidates(combinations: &[u32], used: u32) -> u32 {
+    combinations
+        .iter()
+        .filter(|c| *c & used == used)
+        .fold(0, |acc, c| acc | (c & !used))
+}
+
+/// every set (as a bitmap) of `len` distinct values out of `all_values` that adds up to `sum`
+fn combinations(sum: u32, len: usize, all_values: u32) -> Vec<u32> {
+    let mut found = vec![];
+    collect_combinations(1, sum, len, all_values, 0, &mut found);
+    found
+}
+
+fn collect_combinations(
+    from: u32,
+    sum_left: u32,
+    len_left: usize,
+    all_values: u32,
+    picked: u32,
+    found: &mut Vec<u32>,
+) {
+    if len_left == 0 {
+        if sum_left == 0 {
+            found.push(picked);
+        }
+
+        return;
+    }
+
+    for v in from..=sum_left.min(31) {
+        if all_values & (1 << v) != 0 {
+            collect_combinations(
+                v + 1,
+                sum_left - v,
+                len_left - 1,
+                all_values,
+                picked | (1 << v),
+                found,
+            );
+        }
+    }
+}
+
+impl Sudoku {
+    pub fn get_cages(&self) -> &[Cage] {
+        &self.cages
+    }
+
+    /// sets the cages of an empty board, cages can't overlap but don't need to cover every cell
+    pub fn set_cages(&mut self, cages: Vec<Cage>) -> Result<(), Box<dyn Error>> {
+        let side = self.size.side();
+        let mut cage_ids = vec![vec![None; side]; side];
+        let mut cage_combinations = vec![];
+
+        for (id, cage) in cages.iter().enumerate() {
+            if cage.cells.is_empty() || cage.cells.len() > side {
+                return Err(format!("cage {cage} has an invalid number of cells").into());
+            }
+
+            for p in &cage.cells {
+                if p.x >= side || p.y >= side {
+                    return Err(format!("cage {cage} has a cell outside the board").into());
+                }
+
+                if cage_ids[p.x][p.y].replace(id).is_some() {
+                    return Err(format!("cage {cage} overlaps another cage").into());
+                }
+            }
+
+            let found = combinations(cage.sum, cage.cells.len(), self.size.all_values());
+
+            if found.is_empty() {
+                return Err(format!("cage {cage} has a sum that can't be reached").into());
+            }
+
+            cage_combinations.push(found);
+        }
+
+        self.cage_values = vec![0; cages.len()];
+        self.cage_combinations = cage_combinations;
+        self.cage_ids = cage_ids;
+        self.cages = cages;
+
+        Ok(())
+    }
+
+    /// values the cage of the cell still allows, every value for cells outside a cage
+    #[inline]
+    pub(super) fn get_cage_candidates(&self, row: usize, col: usize) -> u32 {
+        match self.cage_ids[row][col] {
+            None => self.size.all_values(),
+            Some(c) => cage_candidates(&self.cage_combinations[c], self.cage_values[c]),
+        }
+    }
+
+    /// cage restrictions for every empty cell, wrong guesses are ignored like in `candidates`
+    pub(super) fn cage_restrictions(&self) -> Vec<Vec<u32>> {
+        let side = self.size.side();
+        let mut used = vec![0u32; self.cages.len()];
+
+        for (i, row) in self.grid.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                if let (Some(v), state) = cell
+                    && *state != CellState::Wrong
+                    && let Some(c) = self.cage_ids[i][j]
+                {
+                    used[c] |= 1 << v;
+                }
+            }
+        }
+
+        let mut restrictions = vec![vec![self.size.all_values(); side]; side];
+
+        for (c, cage) in self.cages.iter().enumerate() {
+            let allowed = cage_candidates(&self.cage_combinations[c], used[c]);
+
+            for p in &cage.cells {
+                restrictions[p.x][p.y] = allowed;
+            }
+        }
+
+        restrictions
+    }
+
+    /// killer board with as few givens as needed (at most `max_givens`) for a unique solution,
+    /// givens are added a whole orbit of the symmetry at a time. None when no board was found
+    /// within the attempts
+    pub(super) fn random_killer_board<R: Rng>(
+        size: BoardSize,
+        rules: &[ExtraRule],
+        max_givens: u8,
+        symmetry: Symmetry,
+        mut callback: Option<&mut dyn FnMut(usize)>,
+        rng: &mut R,
+    ) -> Option<Self> {
+        let mut counter = 0;
+
+        for _ in 0..GRID_ATTEMPTS {
+            let mut solution = Sudoku::empty(size, Variant::Killer);
+            solution.add_rules_before_fill(rules);
+
+            if !solution.fill_randomly(rng) {
+                continue;
+            }
+
+            solution.add_rules_after_fill(rules, rng);
+
+            for _ in 0..PARTITION_ATTEMPTS {
+                let cages = Sudoku::partition_into_cages(&solution, rng);
+
+                let mut board = solution.blank_like();
+                board
+                    .set_cages(cages)
+                    .expect("cages are built from a solved grid");
+
+                let mut cells = (0..size.total_positions())
+                    .map(|k| Position::new(k / size.side(), k % size.side()))
+                    .collect::<Vec<Position>>();
+                seeded_rng::shuffle(rng, &mut cells);
+
+                let mut givens = cells.into_iter();
+
+                loop {
+                    let mut solved = board.clone();
+
+                    if solved.solve_within(UNIQUENESS_BUDGET) == Some(true) {
+                        solved.reset();
+                        return Some(solved);
+                    }
+
+                    let pos = givens
+                        .find(|p| !board.prefilled_positions.contains_key(p))
+                        .expect("a full grid is always unique");
+                    let orbit = symmetry.orbit(size.side(), pos.x, pos.y);
+
+                    if board.number_of_initial_clues() as usize + orbit.len() > max_givens as usize
+                    {
+                        break;
+                    }
+
+                    for (x, y) in orbit {
+                        let pos = Position::new(x, y);
+                        let val = solution.grid[x][y].0;
+
+                        board
+                            .insert(&pos, val, CellState::Normal)
+                            .expect("value comes from a solved grid");
+                        board.prefilled_positions.insert(pos, val.unwrap());
+                    }
+                }
+
+                counter += 1;
+
+                if let Some(cb) = callback.as_mut() {
+                    cb(counter);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// grows cages out of random cells, a cage never holds the same value twice
+    fn partition_into_cages<R: Rng>(solution: &Sudoku, rng: &mut R) -> Vec<Cage> {
+        let side = solution.size.side();
+        let mut taken = vec![vec![false; side]; side];
+        let mut cages = vec![];
+
+        let mut starts = (0..side * side)
+            .map(|k| (k / side, k % side))
+            .collect::<Vec<(usize, usize)>>();
+        seeded_rng::shuffle(rng, &mut starts);
+
+        for (x, y) in starts {
+            if taken[x][y] {
+                continue;
+            }
+
+            let target = 2 + seeded_rng::below(rng, MAX_CAGE_LEN - 1);
+            let mut cells = vec![(x, y)];
+            let mut values = 1u32 << solution.grid[x][y].0.unwrap();
+            taken[x][y] = true;
+
+            while cells.len() < target {
+                let mut neighbours = cells
+                    .iter()
+                    .flat_map(|&(i, j)| {
+                        [
+                            (i.wrapping_sub(1), j),
+                            (i + 1, j),
+                            (i, j.wrapping_sub(1)),
+                            (i, j + 1),
+                        ]
+                    })
+                    .filter(|&(i, j)| {
+                        i < side
+                            && j < side
+                            && !taken[i][j]
+                            && values & (1 << solution.grid[i][j].0.unwrap()) == 0
+                    })
+                    .collect::<Vec<(usize, usize)>>();
+
+                neighbours.sort();
+                neighbours.dedup();
+
+                let (i, j) = match seeded_rng::choose(rng, &neighbours) {
+                    Some(n) => *n,
+                    None => break,
+                };
+
+                taken[i][j] = true;
+                values |= 1 << solution.grid[i][j].0.unwrap();
+                cells.push((i, j));
+            }
+
+            let sum = cells
+                .iter()
+                .map(|&(i, j)| solution.grid[i][j].0.unwrap() as u32)
+                .sum();
+
+            cells.sort();
+
+            cages.push(Cage::new(
+                sum,
+                cells
+                    .into_iter()
+                    .map(|(i, j)| Position::new(i, j))
+                    .collect(),
+            ));
+        }
+
+        cages.sort_by_key(|c| (c.cells[0].x, c.cells[0].y));
+
+        cages
+    }
+
+    /// renders the board with cage borders, the sum of a cage sits on the border above its first
+    /// cell. Box borders are blue. `cell` renders the 3 chars wide content of a cell
+    pub fn draw_cages(&self, cell: &dyn Fn(usize, usize) -> String) -> Vec<String> {
+        let side = self.size.side();
+        let (box_rows, box_cols) = (self.size.get_box_rows(), self.size.get_box_cols());
+
+        let cage_of = |i: usize, j: usize| -> Option<usize> {
+            if i < side && j < side {
+                self.cage_ids[i][j]
+            } else {
+                None
+            }
+        };
+
+        // border between (i - 1, j) and (i, j)
+        let horizontal = |i: usize, j: usize| -> bool {
+            i == 0 || i == side || cage_of(i - 1, j).is_none() || cage_of(i - 1, j) != cage_of(i, j)
+        };
+
+        // border between (i, j - 1) and (i, j)
+        let vertical = |i: usize, j: usize| -> bool {
+            j == 0 || j == side || cage_of(i, j - 1).is_none() || cage_of(i, j - 1) != cage_of(i, j)
+        };
+
+        let mut lines = vec![];
+
+        let mut header = String::from("   ");
+        for j in 0..side {
+            header.push_str(&format!("  {} ", coordinate_to_char(j)));
+        }
+        lines.push(format!("{}", header.italic()));
+
+        for i in 0..=side {
+            let mut line = String::from("  ");
+
+            for j in 0..=side {
+                let corner = (j < side && horizontal(i, j))
+                    || (j > 0 && horizontal(i, j - 1))
+                    || (i < side && vertical(i, j))
+                    || (i > 0 && vertical(i - 1, j));
+
+                line.push_str(&match (corner, i % box_rows == 0 && j % box_cols == 0) {
+                    (_, true) => format!("{}", "+".blue()),
+                    (true, false) => "+".to_string(),
+                    (false, false) => " ".to_string(),
+                });
+
+                if j == side {
+                    break;
+                }
+
+                let on_box_edge = i % box_rows == 0;
+
+                let segment = match self
+                    .cages
+                    .iter()
+                    .find(|c| i < side && c.cells[0] == Position::new(i, j))
+                {
+                    Some(c) => format!("{:-<3}", c.sum),
+                    None if horizontal(i, j) => "---".to_string(),
+                    None if on_box_edge => "...".to_string(),
+                    None => "   ".to_string(),
+                };
+
+                line.push_str(&match on_box_edge {
+                    true => format!("{}", segment.blue()),
+                    false => segment,
+                });
+            }
+
+            lines.push(line);
+
+            if i == side {
+                break;
+            }
+
+            let mut line = format!("{} ", coordinate_to_char(i).to_string().italic());
+
+            for j in 0..=side {
+                let on_box_edge = j % box_cols == 0;
+
+                line.push_str(&match (vertical(i, j), on_box_edge) {
+                    (true, true) => format!("{}", "|".blue()),
+                    (true, false) => "|".to_string(),
+                    (false, true) => format!("{}", ":".blue()),
+                    (false, false) => " ".to_string(),
+                });
+
+                if j < side {
+                    line.push_str(&cell(i, j));
+                }
+            }
+
+            lines.push(line);
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cages() {
+        let cage = Cage::parse(" 12=00+01+10 ").unwrap();
+
+        assert_eq!(cage.get_sum(), 12);
+        assert_eq!(
+            cage.get_cells(),
+            [
+                Position::new(0, 0),
+                Position::new(0, 1),
+                Position::new(1, 0)
+            ]
+        );
+        assert_eq!(Cage::parse(&cage.to_string()).unwrap(), cage);
+
+        for inp in ["12", "x=00+01", "12=00+1", "12=00+01+"] {
+            assert!(Cage::parse(inp).is_err(), "{inp}");
+        }
+    }
+
+    #[test]
+    fn turns_away_cages_that_cant_fit() {
+        let size = BoardSize::new(2, 2);
+        let cage = |inp: &str| Cage::parse(inp).unwrap();
+
+        for cages in [
+            vec![cage("3=00+01"), cage("4=01+11")],
+            vec![cage("2=00+01")],
+            vec![cage("3=00+04")],
+            vec![cage("10=00+01+02+03+10")],
+        ] {
+            let mut board = Sudoku::empty(size, Variant::Killer);
+            assert!(board.set_cages(cages).is_err());
+        }
+    }
+
+    #[test]
+    fn generated_killer_boards_are_unique() {
+        let size = BoardSize::new(2, 3);
+        let board =
+            Sudoku::generate_random_board(size, Variant::Killer, &[], 6, Symmetry::None, |_| {})
+                .unwrap();
+
+        assert!(board.number_of_initial_clues() <= 6);
+
+        let mut solved = board.clone();
+        assert!(solved.solve());
+
+        for cage in board.get_cages() {
+            let values = cage
+                .get_cells()
+                .iter()
+                .map(|p| solved.grid[p.x][p.y].0.unwrap() as u32)
+                .collect::<Vec<u32>>();
+
+            assert_eq!(values.iter().sum::<u32>(), cage.get_sum());
+            assert!(
+                values
+                    .iter()
+                    .all(|v| values.iter().filter(|w| *w == v).count() == 1)
+            );
+        }
+
+        // without cages or givens there is nothing to pin the solution down
+        assert!(!Sudoku::empty(size, Variant::Killer).solve());
+    }
+}
@@ -0,0 +1,374 @@
+use std::{cmp::Ordering, collections::HashMap, sync::OnceLock};
+
+use super::{
+    BoardSize, DietBoard, Sudoku, Variant, digit_to_char,
+    invalid_cache::{self, PackedBoard},
+};
+
+/// boards with more column arrangements than this are too slow to canonicalise and are compared
+/// as they are, only the classic, 4x4 and 6x6 boards stay under it
+const MAX_COLUMN_ARRANGEMENTS: usize = 1296;
+
+/// sides of the supported boards fit in the fixed size buffers below
+const MAX_SIDE: usize = 16;
+
+/// partly built copy of the board, rows are picked one at a time
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    transposed: bool,
+    /// index of the column arrangement, the source column of every column of the copy
+    columns: usize,
+    /// source rows picked so far, as a bitmap
+    used_rows: u32,
+    /// band of the source rows being picked
+    band: usize,
+    /// new digit of every source digit, 0 while it hasn't shown up yet
+    labels: [u8; MAX_SIDE + 1],
+    next_label: u8,
+}
+
+impl Candidate {
+    /// writes the row as it reads in the copy when `row` is picked next and compares it to the
+    /// best one so far, stops as soon as it reads bigger. Digits are relabelled in the order they
+    /// show up
+    fn read_row(
+        &mut self,
+        cells: &[u8],
+        side: usize,
+        columns: &[usize],
+        row: usize,
+        best: &[u8],
+        out: &mut [u8],
+    ) -> Ordering {
+        let mut resp = Ordering::Equal;
+
+        for (i, col) in columns.iter().enumerate() {
+            let v = match self.transposed {
+                true => cells[col * side + row],
+                false => cells[row * side + col],
+            } as usize;
+
+            if v != 0 && self.labels[v] == 0 {
+                self.next_label += 1;
+                self.labels[v] = self.next_label;
+            }
+
+            out[i] = self.labels[v];
+
+            if resp == Ordering::Equal {
+                resp = out[i].cmp(&best[i]);
+
+                if resp == Ordering::Greater {
+                    return resp;
+                }
+            }
+        }
+
+        resp
+    }
+}
+
+/// every order of `0..n`
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![vec![]];
+    }
+
+    let mut resp = vec![];
+
+    for p in permutations(n - 1) {
+        for i in 0..=p.len() {
+            let mut q = p.clone();
+            q.insert(i, n - 1);
+            resp.push(q);
+        }
+    }
+
+    resp
+}
+
+/// every order of the lines that keeps the groups of `group_len` lines together, the groups are
+/// swapped as a whole and the lines are swapped within their group
+fn arrangements(groups: usize, group_len: usize) -> Vec<Vec<usize>> {
+    let line_orders = permutations(group_len);
+    let mut resp = vec![];
+
+    for order in permutations(groups) {
+        let mut partial = vec![vec![]];
+
+        for g in order {
+            partial = partial
+                .iter()
+                .flat_map(|p: &Vec<usize>| {
+                    line_orders.iter().map(move |l| {
+                        let mut q = p.clone();
+                        q.extend(l.iter().map(|i| g * group_len + i));
+                        q
+                    })
+                })
+                .collect();
+        }
+
+        resp.extend(partial);
+    }
+
+    resp
+}
+
+/// column arrangements of every supported size that isn't too big to search, a stack holds
+/// `box_cols` columns
+fn column_arrangements(size: BoardSize) -> Option<&'static Vec<Vec<usize>>> {
+    static ARRANGEMENTS: OnceLock<HashMap<BoardSize, Vec<Vec<usize>>>> = OnceLock::new();
+
+    ARRANGEMENTS
+        .get_or_init(|| {
+            BoardSize::SUPPORTED
+                .into_iter()
+                .filter(|s| {
+                    let factorial = |n: usize| (1..=n).product::<usize>();
+                    factorial(s.get_box_rows())
+                        * factorial(s.get_box_cols()).pow(s.get_box_rows() as u32)
+                        <= MAX_COLUMN_ARRANGEMENTS
+                })
+                .map(|s| (s, arrangements(s.get_box_rows(), s.get_box_cols())))
+                .collect()
+        })
+        .get(&size)
+}
+
+/// filled cells in every stack of the row, fewest first. A row reads smallest with the filled
+/// cells pushed to the end, so only the rows with the smallest counts can come first
+fn stack_fill(cells: &[u8], size: BoardSize, transposed: bool, row: usize) -> Vec<usize> {
+    let side = size.side();
+
+    let mut resp = (0..size.get_box_rows())
+        .map(|stack| {
+            (0..size.get_box_cols())
+                .map(|i| stack * size.get_box_cols() + i)
+                .filter(|col| match transposed {
+                    true => cells[col * side + row] != 0,
+                    false => cells[row * side + col] != 0,
+                })
+                .count()
+        })
+        .collect::<Vec<usize>>();
+
+    resp.sort_unstable();
+    resp
+}
+
+/// smallest copy of the cells, row by row with empty cells as 0, among every copy that keeps the
+/// solutions of a classic board the same: digits relabelled, rows swapped within their band,
+/// columns within their stack, bands and stacks swapped as a whole and, with square boxes, the
+/// board transposed. Equivalent boards share it. None when the size has too many arrangements
+pub(super) fn canonical_form(size: BoardSize, cells: &[u8]) -> Option<DietBoard> {
+    let side = size.side();
+    let (box_rows, box_cols) = (size.get_box_rows(), size.get_box_cols());
+
+    let columns = column_arrangements(size)?;
+
+    let transposes = match box_rows == box_cols {
+        true => vec![false, true],
+        false => vec![false],
+    };
+
+    let mut candidates = transposes
+        .iter()
+        .flat_map(|t| {
+            (0..columns.len()).map(|c| Candidate {
+                transposed: *t,
+                columns: c,
+                used_rows: 0,
+                band: 0,
+                labels: [0; MAX_SIDE + 1],
+                next_label: 0,
+            })
+        })
+        .collect::<Vec<Candidate>>();
+
+    let fills = transposes
+        .iter()
+        .flat_map(|t| (0..side).map(|row| ((*t, row), stack_fill(cells, size, *t, row))))
+        .collect::<Vec<_>>();
+    let fewest = fills
+        .iter()
+        .map(|(_, f)| f)
+        .min()
+        .expect("boards have rows");
+    let first_rows = fills
+        .iter()
+        .filter(|(_, f)| f == fewest)
+        .map(|(r, _)| *r)
+        .collect::<Vec<(bool, usize)>>();
+
+    let mut resp = Vec::with_capacity(side * side);
+    let mut line = [0u8; MAX_SIDE];
+
+    for r in 0..side {
+        let mut best = [u8::MAX; MAX_SIDE];
+        let mut next = vec![];
+
+        for candidate in candidates {
+            // the first row of a band comes from a band that wasn't used yet, the others from the
+            // band of that first row
+            let options = (0..side).filter(|row| {
+                candidate.used_rows & (1 << row) == 0
+                    && (r > 0 || first_rows.contains(&(candidate.transposed, *row)))
+                    && match r % box_rows {
+                        0 => (0..box_rows).all(|i| {
+                            candidate.used_rows & (1 << (row / box_rows * box_rows + i)) == 0
+                        }),
+                        _ => row / box_rows == candidate.band,
+                    }
+            });
+
+            for row in options {
+                let mut c = candidate;
+
+                match c.read_row(cells, side, &columns[c.columns], row, &best, &mut line) {
+                    Ordering::Greater => continue,
+                    Ordering::Equal => (),
+                    Ordering::Less => {
+                        best = line;
+                        next.clear();
+                    }
+                }
+
+                c.used_rows |= 1 << row;
+                c.band = row / box_rows;
+                next.push(c);
+            }
+        }
+
+        resp.extend_from_slice(&best[..side]);
+        candidates = next;
+    }
+
+    Some(resp)
+}
+
+impl Sudoku {
+    /// the givens in canonical form, one char per cell, shared by every puzzle that is the same
+    /// up to relabelling digits and swapping rows, columns, bands and stacks. Only classic boards
+    /// with a size that is quick enough to search have one
+    pub fn get_canonical_form(&self) -> Option<String> {
+        if self.variant != Variant::Classic {
+            return None;
+        }
+
+        let mut puzzle = self.clone();
+        puzzle.hard_reset();
+
+        canonical_form(self.size, &Sudoku::get_diet_board(&puzzle.grid)).map(|c| {
+            c.into_iter()
+                .map(|v| match v {
+                    0 => '.',
+                    v => digit_to_char(v),
+                })
+                .collect()
+        })
+    }
+
+    /// the givens of a classic batch board packed in canonical form, or as they are when the size
+    /// has none, so equivalent boards of a run pack the same
+    pub(super) fn canonical_pack(&self) -> PackedBoard {
+        let givens = Sudoku::get_diet_board(&self.grid);
+
+        invalid_cache::pack(&canonical_form(self.size, &givens).unwrap_or(givens))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the classic board from wikipedia, row by row
+    const GIVENS: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    fn cells(board: &str) -> DietBoard {
+        board.bytes().map(|b| b - b'0').collect()
+    }
+
+    /// copy with the cell at `(x, y)` taken from `at(x, y)` of the board
+    fn moved(cells: &[u8], at: impl Fn(usize, usize) -> (usize, usize)) -> DietBoard {
+        (0..81)
+            .map(|k| {
+                let (x, y) = at(k / 9, k % 9);
+                cells[x * 9 + y]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn equivalent_boards_share_it() {
+        let board = cells(GIVENS);
+        let expected = canonical_form(BoardSize::CLASSIC, &board).unwrap();
+
+        let relabelled = board
+            .iter()
+            .map(|v| if *v == 0 { 0 } else { v % 9 + 1 })
+            .collect::<DietBoard>();
+        let transposed = moved(&board, |x, y| (y, x));
+        let bands_swapped = moved(&board, |x, y| ((x + 3) % 9, y));
+        let stacks_swapped = moved(&board, |x, y| (x, [6, 7, 8, 0, 1, 2, 3, 4, 5][y]));
+        let rows_swapped = moved(&board, |x, y| ([1, 0, 2, 3, 4, 5, 8, 7, 6][x], y));
+        let columns_swapped = moved(&board, |x, y| (x, [0, 2, 1, 5, 4, 3, 6, 7, 8][y]));
+
+        for copy in [
+            relabelled,
+            transposed,
+            bands_swapped,
+            stacks_swapped,
+            rows_swapped,
+            columns_swapped,
+        ] {
+            assert_ne!(copy, board);
+            assert_eq!(canonical_form(BoardSize::CLASSIC, &copy).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn every_transformation_at_once_keeps_it() {
+        let board = cells(GIVENS);
+        let copy = moved(&board, |x, y| {
+            (
+                [7, 6, 8, 2, 0, 1, 4, 5, 3][y],
+                [3, 5, 4, 0, 2, 1, 8, 6, 7][x],
+            )
+        })
+        .into_iter()
+        .map(|v| [0, 4, 7, 1, 9, 2, 5, 8, 3, 6][v as usize])
+        .collect::<DietBoard>();
+
+        assert_eq!(
+            canonical_form(BoardSize::CLASSIC, &copy),
+            canonical_form(BoardSize::CLASSIC, &board)
+        );
+    }
+
+    #[test]
+    fn different_boards_dont_share_it() {
+        let board = cells(GIVENS);
+
+        // two givens swapped, the same cells stay given
+        let mut swapped_givens = board.clone();
+        swapped_givens.swap(0, 8 * 9 + 4);
+
+        // a row swapped with a row of another band isn't an equivalent copy
+        let rows_across_bands = moved(&board, |x, y| ([3, 1, 2, 0, 4, 5, 6, 7, 8][x], y));
+
+        for other in [swapped_givens, rows_across_bands] {
+            assert_ne!(
+                canonical_form(BoardSize::CLASSIC, &other),
+                canonical_form(BoardSize::CLASSIC, &board)
+            );
+        }
+    }
+
+    #[test]
+    fn sizes_with_too_many_arrangements_have_none() {
+        let size = BoardSize::new(4, 4);
+        assert_eq!(canonical_form(size, &vec![0; size.total_positions()]), None);
+    }
+}
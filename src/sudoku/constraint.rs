@@ -0,0 +1,381 @@
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    sync::Arc,
+};
+
+use rand::Rng;
+
+use super::{
+    BoardSize, CellState, Position, Sudoku, Variant, char_to_digit, coordinate_to_char, seeded_rng,
+};
+
+mod consecutive;
+mod dots;
+mod lines;
+mod moves;
+mod parity;
+mod sandwich;
+
+pub use consecutive::Consecutive;
+pub use dots::{Kropki, KropkiDot, Xv};
+pub use lines::{Arrow, Thermometer, Whisper};
+pub use moves::{AntiKing, AntiKnight};
+pub use parity::{OddEven, Parity};
+pub use sandwich::{Sandwich, SandwichLine};
+
+/// share of the cells that are shaded on generated odd/even boards, one in `ODD_EVEN_SHARE`
+const ODD_EVEN_SHARE: usize = 4;
+
+/// value placed at a cell, none for empty cells and wrong guesses
+pub type Values<'a> = &'a dyn Fn(usize, usize) -> Option<u8>;
+
+/// rule the solver checks on top of the rows, columns and blocks. Constraints only look at the
+/// values placed so far, so they have to tell whether those can still be completed
+pub trait Constraint: Debug + Display + Send + Sync {
+    /// cells whose values the constraint restricts
+    fn cells(&self, size: BoardSize) -> Vec<Position>;
+
+    /// whether the placed values can still be completed without breaking the constraint
+    fn is_possible(&self, values: Values, size: BoardSize) -> bool;
+
+    /// values the empty cell can take, every value that keeps the constraint possible by default
+    fn candidates(&self, pos: &Position, values: Values, size: BoardSize) -> u32 {
+        (1..=size.side() as u8)
+            .filter(|&v| {
+                let with_v = |x: usize, y: usize| match x == pos.x && y == pos.y {
+                    true => Some(v),
+                    false => values(x, y),
+                };
+
+                self.is_possible(&with_v, size)
+            })
+            .fold(0, |acc, v| acc | (1 << v))
+    }
+
+    /// written into the board layout, `parse_constraint` reads it back
+    fn to_str(&self) -> String;
+}
+
+/// reads a constraint written by `Constraint::to_str`, `<name>=<arguments>` or just the name
+pub fn parse_constraint(inp: &str) -> Result<Arc<dyn Constraint>, Box<dyn Error>> {
+    let (name, args) = inp.trim().split_once('=').unwrap_or((inp.trim(), ""));
+
+    Ok(match name {
+        "thermo" => Arc::new(Thermometer::new(cells_from_str(args)?)),
+        "arrow" => match args.split_once('>') {
+            Some((circle, arrow)) => {
+                Arc::new(Arrow::new(cell_from_str(circle)?, cells_from_str(arrow)?))
+            }
+            None => return Err(format!("invalid arrow found, expected xy>xy+xy: {args}").into()),
+        },
+        "whisper" => Arc::new(Whisper::new(cells_from_str(args)?)),
+        "white" | "black" => {
+            let dot = match name {
+                "white" => KropkiDot::White,
+                _ => KropkiDot::Black,
+            };
+
+            match cells_from_str(args)?[..] {
+                [ref a, ref b] => Arc::new(Kropki::new(dot, a.clone(), b.clone())),
+                _ => return Err(format!("a kropki dot sits between two cells: {args}").into()),
+            }
+        }
+        "x" | "v" => {
+            let sum = if name == "x" { 10 } else { 5 };
+
+            match cells_from_str(args)?[..] {
+                [ref a, ref b] => Arc::new(Xv::new(sum, a.clone(), b.clone())),
+                _ => return Err(format!("an {name} sits between two cells: {args}").into()),
+            }
+        }
+        "sandwich" => Arc::new(Sandwich::parse(args)?),
+        "odd" => Arc::new(OddEven::new(Parity::Odd, cells_from_str(args)?)),
+        "even" => Arc::new(OddEven::new(Parity::Even, cells_from_str(args)?)),
+        "consecutive" => Arc::new(Consecutive::parse(args)?),
+        "nonconsecutive" => Arc::new(Consecutive::new(vec![])),
+        "antiknight" => Arc::new(AntiKnight),
+        "antiking" => Arc::new(AntiKing),
+        _ => return Err(format!("unknown constraint found: {name}").into()),
+    })
+}
+
+/// rule a board can be generated with, on top of its rule set
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum ExtraRule {
+    /// some cells are shaded as odd or even
+    OddEven,
+    /// bars mark every pair of neighbours that differ by 1
+    Consecutive,
+    /// no neighbours differ by 1
+    NonConsecutive,
+}
+
+impl Display for ExtraRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtraRule::OddEven => write!(f, "Odd/even shaded cells"),
+            ExtraRule::Consecutive => write!(f, "Consecutive bars"),
+            ExtraRule::NonConsecutive => write!(f, "Non-consecutive neighbours"),
+        }
+    }
+}
+
+impl ExtraRule {
+    pub const ALL: [ExtraRule; 3] = [
+        ExtraRule::OddEven,
+        ExtraRule::Consecutive,
+        ExtraRule::NonConsecutive,
+    ];
+
+    /// whether boards of the size and variant can be generated with the rule, no 4x4 grid is
+    /// non-consecutive and the extra regions of some variants leave no room for it either
+    pub fn supports(&self, size: BoardSize, variant: Variant) -> bool {
+        let side = size.side();
+
+        match self {
+            ExtraRule::NonConsecutive => {
+                side >= 6
+                    && (!variant.has_diagonals() || side == 9)
+                    && (!variant.has_windows() || side >= 12)
+            }
+            _ => true,
+        }
+    }
+}
+
+/// cell written as its x and y coordinate chars, ex: 07
+pub(super) fn cell_to_str(pos: &Position) -> String {
+    format!("{}{}", coordinate_to_char(pos.x), coordinate_to_char(pos.y))
+}
+
+/// cells separated by a `+`, ex: 00+01+11
+pub(super) fn cells_to_str(cells: &[Position]) -> String {
+    cells
+        .iter()
+        .map(cell_to_str)
+        .collect::<Vec<String>>()
+        .join("+")
+}
+
+pub(super) fn cell_from_str(inp: &str) -> Result<Position, Box<dyn Error>> {
+    let chars = inp.trim().chars().collect::<Vec<char>>();
+
+    let pos = match chars[..] {
+        [x, y] => char_to_digit(x).zip(char_to_digit(y)),
+        _ => None,
+    };
+
+    match pos {
+        Some((x, y)) => Ok(Position::new(x as usize, y as usize)),
+        None => Err(format!("invalid cell found, expected xy: {inp}").into()),
+    }
+}
+
+pub(super) fn cells_from_str(inp: &str) -> Result<Vec<Position>, Box<dyn Error>> {
+    inp.split('+').map(cell_from_str).collect()
+}
+
+impl Sudoku {
+    pub fn get_constraints(&self) -> &[Arc<dyn Constraint>] {
+        &self.constraints
+    }
+
+    /// adds a constraint on top of the variant's rules, every cell it looks at has to be on the
+    /// board
+    pub fn add_constraint(
+        &mut self,
+        constraint: Arc<dyn Constraint>,
+    ) -> Result<(), Box<dyn Error>> {
+        let side = self.size.side();
+        let cells = constraint.cells(self.size);
+
+        if let Some(p) = cells.iter().find(|p| p.x >= side || p.y >= side) {
+            return Err(
+                format!("constraint `{constraint}` has a cell outside the board: {p}").into(),
+            );
+        }
+
+        for p in cells {
+            self.constraint_ids[p.x][p.y].push(self.constraints.len());
+        }
+
+        self.constraints.push(constraint);
+
+        Ok(())
+    }
+
+    /// constraints of the rules the solution has to be filled with
+    pub(super) fn add_rules_before_fill(&mut self, rules: &[ExtraRule]) {
+        if rules.contains(&ExtraRule::NonConsecutive) {
+            self.add_constraint(Arc::new(Consecutive::new(vec![])))
+                .expect("covers the whole board");
+        }
+    }
+
+    /// constraints of the rules that are read off the filled solution, the odd/even shading and
+    /// the consecutive bars
+    pub(super) fn add_rules_after_fill<R: Rng>(&mut self, rules: &[ExtraRule], rng: &mut R) {
+        let side = self.size.side();
+        let values = |x: usize, y: usize| self.placed_value(x, y);
+        let mut constraints: Vec<Arc<dyn Constraint>> = vec![];
+
+        if rules.contains(&ExtraRule::OddEven) {
+            let mut cells = (0..side * side)
+                .map(|k| Position::new(k / side, k % side))
+                .collect::<Vec<Position>>();
+            seeded_rng::shuffle(rng, &mut cells);
+            cells.truncate(side * side / ODD_EVEN_SHARE);
+            cells.sort_by_key(|p| (p.x, p.y));
+
+            for parity in [Parity::Odd, Parity::Even] {
+                let shaded = cells
+                    .iter()
+                    .filter(|p| values(p.x, p.y).map(Parity::of) == Some(parity))
+                    .cloned()
+                    .collect::<Vec<Position>>();
+
+                if !shaded.is_empty() {
+                    constraints.push(Arc::new(OddEven::new(parity, shaded)));
+                }
+            }
+        }
+
+        // a non-consecutive solution has no bars to draw, the rule is in place already
+        if rules.contains(&ExtraRule::Consecutive) && !rules.contains(&ExtraRule::NonConsecutive) {
+            constraints.push(Arc::new(Consecutive::from_solution(self.size, &values)));
+        }
+
+        for c in constraints {
+            self.add_constraint(c).expect("cells come from the board");
+        }
+    }
+
+    /// value placed at the cell, wrong guesses are left out
+    #[inline]
+    pub(super) fn placed_value(&self, row: usize, col: usize) -> Option<u8> {
+        match self.grid[row][col] {
+            (Some(v), state) if state != CellState::Wrong => Some(v),
+            _ => None,
+        }
+    }
+
+    /// values the constraints on the cell still allow, as a bitmap
+    #[inline]
+    pub(super) fn get_constraint_candidates(&self, row: usize, col: usize) -> u32 {
+        let values = |x: usize, y: usize| self.placed_value(x, y);
+        let pos = Position::new(row, col);
+
+        self.constraint_ids[row][col]
+            .iter()
+            .fold(self.size.all_values(), |acc, id| {
+                acc & self.constraints[*id].candidates(&pos, &values, self.size)
+            })
+    }
+
+    /// fails on the first constraint the placed values can't satisfy anymore
+    pub(super) fn check_constraints(&self) -> Result<(), Box<dyn Error>> {
+        let values = |x: usize, y: usize| self.placed_value(x, y);
+
+        match self
+            .constraints
+            .iter()
+            .find(|c| !c.is_possible(&values, self.size))
+        {
+            Some(c) => Err(format!("the given values break the constraint `{c}`").into()),
+            None => Ok(()),
+        }
+    }
+
+    /// every constraint prefixed with a `#`
+    pub(super) fn constraints_to_str(&self) -> String {
+        self.constraints
+            .iter()
+            .map(|c| format!("#{}", c.to_str()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Symmetry;
+
+    /// value the classic board with the constraint keeps at the cell after `insert_at`, the
+    /// values before the last one are placed as they are
+    fn placed(constraint: Arc<dyn Constraint>, moves: &[(usize, usize, u8)]) -> Option<u8> {
+        let mut board = Sudoku::empty(BoardSize::CLASSIC, Variant::Classic);
+        board.add_constraint(constraint).unwrap();
+
+        let ((x, y, v), before) = moves.split_last().unwrap();
+
+        for (i, j, w) in before {
+            board
+                .insert(&Position::new(*i, *j), Some(*w), CellState::Normal)
+                .unwrap();
+        }
+
+        board.insert_at(&Position::new(*x, *y), Some(*v));
+        board.grid[*x][*y].0
+    }
+
+    #[test]
+    fn insert_at_turns_away_values_breaking_the_rules() {
+        let odd = || Arc::new(OddEven::new(Parity::Odd, vec![Position::new(0, 0)]));
+        assert_eq!(placed(odd(), &[(0, 0, 2)]), None);
+        assert_eq!(placed(odd(), &[(0, 0, 3)]), Some(3));
+
+        let bar = || Arc::new(Consecutive::parse("00+01").unwrap());
+        assert_eq!(placed(bar(), &[(0, 0, 5), (0, 1, 7)]), None);
+        assert_eq!(placed(bar(), &[(0, 0, 5), (0, 1, 6)]), Some(6));
+        // no bar between them, so they can't be consecutive
+        assert_eq!(placed(bar(), &[(0, 0, 5), (1, 0, 6)]), None);
+
+        let nonconsecutive = || Arc::new(Consecutive::new(vec![]));
+        assert_eq!(placed(nonconsecutive(), &[(0, 0, 5), (0, 1, 4)]), None);
+        assert_eq!(placed(nonconsecutive(), &[(0, 0, 5), (0, 1, 7)]), Some(7));
+    }
+
+    #[test]
+    fn generated_boards_follow_the_rules() {
+        let size = BoardSize::new(2, 3);
+
+        for rules in [
+            [ExtraRule::OddEven, ExtraRule::Consecutive],
+            [ExtraRule::OddEven, ExtraRule::NonConsecutive],
+        ] {
+            let board = Sudoku::generate_random_board(
+                size,
+                Variant::Classic,
+                &rules,
+                14,
+                Symmetry::None,
+                |_| {},
+            )
+            .unwrap();
+            let solution = |x: usize, y: usize| board.solved_grid[x][y].0;
+            let names = board
+                .get_constraints()
+                .iter()
+                .map(|c| c.to_str())
+                .collect::<Vec<String>>();
+
+            assert!(
+                names
+                    .iter()
+                    .any(|n| n.starts_with("odd") || n.starts_with("even"))
+            );
+            assert!(
+                names
+                    .iter()
+                    .any(|n| n.starts_with("consecutive") || n == "nonconsecutive")
+            );
+            assert!(
+                board
+                    .get_constraints()
+                    .iter()
+                    .all(|c| c.is_possible(&solution, size))
+            );
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::{collections::HashSet, error::Error, fmt::Display};
+
+use super::{Constraint, Values, cell_to_str, cells_from_str, cells_to_str};
+use crate::sudoku::{BoardSize, Position};
+
+/// orthogonal neighbours differ by 1 exactly when a bar is drawn between them. Without any bars
+/// no neighbours are consecutive at all
+#[derive(Debug, Clone)]
+pub struct Consecutive {
+    /// pairs with a bar, the cell closer to the top left comes first
+    marked: Vec<(Position, Position)>,
+    lookup: HashSet<(Position, Position)>,
+}
+
+/// the pair with the cell closer to the top left first
+fn ordered(a: &Position, b: &Position) -> (Position, Position) {
+    match (a.x, a.y) <= (b.x, b.y) {
+        true => (a.clone(), b.clone()),
+        false => (b.clone(), a.clone()),
+    }
+}
+
+/// bitmap of the values one above and one below
+#[inline]
+fn adjacent_values(v: u8) -> u32 {
+    (1 << (v + 1)) | (1 << (v - 1))
+}
+
+impl Consecutive {
+    pub fn new(marked: Vec<(Position, Position)>) -> Self {
+        let mut marked = marked
+            .iter()
+            .map(|(a, b)| ordered(a, b))
+            .collect::<Vec<(Position, Position)>>();
+
+        marked.sort_by_key(|(a, b)| (a.x, a.y, b.x, b.y));
+        marked.dedup();
+
+        Consecutive {
+            lookup: marked.iter().cloned().collect(),
+            marked,
+        }
+    }
+
+    /// bars from every pair of neighbours of a solved grid that differ by 1
+    pub fn from_solution(size: BoardSize, values: Values) -> Self {
+        let side = size.side();
+        let mut marked = vec![];
+
+        for x in 0..side {
+            for y in 0..side {
+                let v = values(x, y);
+
+                for (i, j) in [(x + 1, y), (x, y + 1)] {
+                    if i < side
+                        && j < side
+                        && v.zip(values(i, j)).is_some_and(|(a, b)| a.abs_diff(b) == 1)
+                    {
+                        marked.push((Position::new(x, y), Position::new(i, j)));
+                    }
+                }
+            }
+        }
+
+        Consecutive::new(marked)
+    }
+
+    /// reads the pairs written by `to_str`, separated by a `/`, ex: 00+01/33+43
+    pub fn parse(inp: &str) -> Result<Self, Box<dyn Error>> {
+        let mut marked = vec![];
+
+        for pair in inp.split('/').filter(|p| !p.trim().is_empty()) {
+            match &cells_from_str(pair)?[..] {
+                [a, b] if a.x.abs_diff(b.x) + a.y.abs_diff(b.y) == 1 => {
+                    marked.push((a.clone(), b.clone()))
+                }
+                _ => {
+                    return Err(
+                        format!("a consecutive bar sits between two neighbours: {pair}").into(),
+                    );
+                }
+            }
+        }
+
+        Ok(Consecutive::new(marked))
+    }
+
+    fn is_marked(&self, a: &Position, b: &Position) -> bool {
+        self.lookup.contains(&ordered(a, b))
+    }
+}
+
+impl Display for Consecutive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.marked.is_empty() {
+            return write!(
+                f,
+                "non-consecutive, orthogonal neighbours never differ by 1"
+            );
+        }
+
+        let bars = self
+            .marked
+            .iter()
+            .map(|(a, b)| format!("{}-{}", cell_to_str(a), cell_to_str(b)))
+            .collect::<Vec<String>>();
+
+        write!(
+            f,
+            "consecutive, only the neighbours with a bar differ by 1: {}",
+            bars.join(", ")
+        )
+    }
+}
+
+impl Constraint for Consecutive {
+    fn cells(&self, size: BoardSize) -> Vec<Position> {
+        let side = size.side();
+        (0..side * side)
+            .map(|k| Position::new(k / side, k % side))
+            .collect()
+    }
+
+    fn is_possible(&self, values: Values, size: BoardSize) -> bool {
+        let side = size.side();
+
+        (0..side * side).all(|k| {
+            let pos = Position::new(k / side, k % side);
+
+            let Some(v) = values(pos.x, pos.y) else {
+                return true;
+            };
+
+            [(pos.x + 1, pos.y), (pos.x, pos.y + 1)]
+                .into_iter()
+                .filter(|(i, j)| *i < side && *j < side)
+                .all(|(i, j)| match values(i, j) {
+                    Some(o) => (v.abs_diff(o) == 1) == self.is_marked(&pos, &Position::new(i, j)),
+                    None => true,
+                })
+        })
+    }
+
+    fn candidates(&self, pos: &Position, values: Values, size: BoardSize) -> u32 {
+        let side = size.side();
+
+        [
+            (pos.x.wrapping_sub(1), pos.y),
+            (pos.x + 1, pos.y),
+            (pos.x, pos.y.wrapping_sub(1)),
+            (pos.x, pos.y + 1),
+        ]
+        .into_iter()
+        .filter(|(i, j)| *i < side && *j < side)
+        .fold(size.all_values(), |acc, (i, j)| match values(i, j) {
+            Some(o) if self.is_marked(pos, &Position::new(i, j)) => acc & adjacent_values(o),
+            Some(o) => acc & !adjacent_values(o),
+            None => acc,
+        })
+    }
+
+    fn to_str(&self) -> String {
+        if self.marked.is_empty() {
+            return "nonconsecutive".to_string();
+        }
+
+        let bars = self
+            .marked
+            .iter()
+            .map(|(a, b)| cells_to_str(&[a.clone(), b.clone()]))
+            .collect::<Vec<String>>();
+
+        format!("consecutive={}", bars.join("/"))
+    }
+}
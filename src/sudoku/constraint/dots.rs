@@ -0,0 +1,132 @@
+use std::fmt::Display;
+
+use super::{Constraint, Values, cell_to_str, cells_to_str};
+use crate::sudoku::{BoardSize, Position};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum KropkiDot {
+    /// the values are consecutive
+    White,
+    /// one value is double the other
+    Black,
+}
+
+/// dot between two neighbouring cells
+#[derive(Debug, Clone)]
+pub struct Kropki {
+    dot: KropkiDot,
+    a: Position,
+    b: Position,
+}
+
+impl Kropki {
+    pub fn new(dot: KropkiDot, a: Position, b: Position) -> Self {
+        Kropki { dot, a, b }
+    }
+
+    fn fits(&self, a: u8, b: u8) -> bool {
+        match self.dot {
+            KropkiDot::White => a.abs_diff(b) == 1,
+            KropkiDot::Black => a == b * 2 || b == a * 2,
+        }
+    }
+}
+
+impl Display for Kropki {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let dot = match self.dot {
+            KropkiDot::White => "white",
+            KropkiDot::Black => "black",
+        };
+
+        write!(
+            f,
+            "{dot} dot between {} and {}",
+            cell_to_str(&self.a),
+            cell_to_str(&self.b)
+        )
+    }
+}
+
+impl Constraint for Kropki {
+    fn cells(&self, _: BoardSize) -> Vec<Position> {
+        vec![self.a.clone(), self.b.clone()]
+    }
+
+    fn is_possible(&self, values: Values, size: BoardSize) -> bool {
+        let side = size.side() as u8;
+
+        match (values(self.a.x, self.a.y), values(self.b.x, self.b.y)) {
+            (Some(a), Some(b)) => self.fits(a, b),
+            (Some(v), None) | (None, Some(v)) => (1..=side).any(|o| self.fits(v, o)),
+            (None, None) => true,
+        }
+    }
+
+    fn to_str(&self) -> String {
+        let name = match self.dot {
+            KropkiDot::White => "white",
+            KropkiDot::Black => "black",
+        };
+
+        format!("{name}={}", cells_to_str(&[self.a.clone(), self.b.clone()]))
+    }
+}
+
+/// an X (sum of 10) or a V (sum of 5) between two neighbouring cells
+#[derive(Debug, Clone)]
+pub struct Xv {
+    sum: u8,
+    a: Position,
+    b: Position,
+}
+
+impl Xv {
+    pub fn new(sum: u8, a: Position, b: Position) -> Self {
+        Xv { sum, a, b }
+    }
+
+    fn letter(&self) -> &'static str {
+        match self.sum {
+            10 => "x",
+            _ => "v",
+        }
+    }
+}
+
+impl Display for Xv {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} between {} and {} (sum of {})",
+            self.letter().to_uppercase(),
+            cell_to_str(&self.a),
+            cell_to_str(&self.b),
+            self.sum
+        )
+    }
+}
+
+impl Constraint for Xv {
+    fn cells(&self, _: BoardSize) -> Vec<Position> {
+        vec![self.a.clone(), self.b.clone()]
+    }
+
+    fn is_possible(&self, values: Values, size: BoardSize) -> bool {
+        match (values(self.a.x, self.a.y), values(self.b.x, self.b.y)) {
+            (Some(a), Some(b)) => a + b == self.sum,
+            (Some(v), None) | (None, Some(v)) => {
+                v < self.sum && (self.sum - v) as usize <= size.side()
+            }
+            (None, None) => true,
+        }
+    }
+
+    fn to_str(&self) -> String {
+        format!(
+            "{}={}",
+            self.letter(),
+            cells_to_str(&[self.a.clone(), self.b.clone()])
+        )
+    }
+}
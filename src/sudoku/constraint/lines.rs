@@ -0,0 +1,160 @@
+use std::fmt::Display;
+
+use super::{Constraint, Values, cell_to_str, cells_to_str};
+use crate::sudoku::{BoardSize, Position};
+
+/// values strictly increase from the bulb (first cell) to the tip
+#[derive(Debug, Clone)]
+pub struct Thermometer {
+    cells: Vec<Position>,
+}
+
+impl Thermometer {
+    pub fn new(cells: Vec<Position>) -> Self {
+        Thermometer { cells }
+    }
+}
+
+impl Display for Thermometer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cells = self.cells.iter().map(cell_to_str).collect::<Vec<String>>();
+        write!(f, "thermometer {}", cells.join(" < "))
+    }
+}
+
+impl Constraint for Thermometer {
+    fn cells(&self, _: BoardSize) -> Vec<Position> {
+        self.cells.clone()
+    }
+
+    fn is_possible(&self, values: Values, size: BoardSize) -> bool {
+        let len = self.cells.len();
+        let placed = self
+            .cells
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| values(p.x, p.y).map(|v| (i, v as usize)))
+            .collect::<Vec<(usize, usize)>>();
+
+        // every cell needs room for the cells before and after it
+        if placed
+            .iter()
+            .any(|&(i, v)| v < i + 1 || v + (len - 1 - i) > size.side())
+        {
+            return false;
+        }
+
+        placed
+            .windows(2)
+            .all(|w| w[1].1 >= w[0].1 + (w[1].0 - w[0].0))
+    }
+
+    fn to_str(&self) -> String {
+        format!("thermo={}", cells_to_str(&self.cells))
+    }
+}
+
+/// values along the arrow add up to the value in its circle
+#[derive(Debug, Clone)]
+pub struct Arrow {
+    circle: Position,
+    arrow: Vec<Position>,
+}
+
+impl Arrow {
+    pub fn new(circle: Position, arrow: Vec<Position>) -> Self {
+        Arrow { circle, arrow }
+    }
+}
+
+impl Display for Arrow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cells = self.arrow.iter().map(cell_to_str).collect::<Vec<String>>();
+        write!(
+            f,
+            "arrow {} = {}",
+            cells.join(" + "),
+            cell_to_str(&self.circle)
+        )
+    }
+}
+
+impl Constraint for Arrow {
+    fn cells(&self, _: BoardSize) -> Vec<Position> {
+        let mut cells = vec![self.circle.clone()];
+        cells.extend(self.arrow.iter().cloned());
+        cells
+    }
+
+    fn is_possible(&self, values: Values, size: BoardSize) -> bool {
+        let placed = self.arrow.iter().filter_map(|p| values(p.x, p.y));
+        let empty = self.arrow.len() - placed.clone().count();
+        let sum = placed.map(|v| v as usize).sum::<usize>();
+
+        // every empty cell on the arrow adds at least 1
+        match values(self.circle.x, self.circle.y) {
+            Some(c) if empty == 0 => sum == c as usize,
+            Some(c) => sum + empty <= c as usize,
+            None => sum + empty <= size.side(),
+        }
+    }
+
+    fn to_str(&self) -> String {
+        format!(
+            "arrow={}>{}",
+            cell_to_str(&self.circle),
+            cells_to_str(&self.arrow)
+        )
+    }
+}
+
+/// neighbours along a german whisper line differ by at least half the side, 5 on a 9x9 board
+#[derive(Debug, Clone)]
+pub struct Whisper {
+    cells: Vec<Position>,
+}
+
+impl Whisper {
+    pub fn new(cells: Vec<Position>) -> Self {
+        Whisper { cells }
+    }
+
+    fn min_difference(size: BoardSize) -> usize {
+        size.side().div_ceil(2)
+    }
+}
+
+impl Display for Whisper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cells = self.cells.iter().map(cell_to_str).collect::<Vec<String>>();
+        write!(f, "german whisper {}", cells.join(" - "))
+    }
+}
+
+impl Constraint for Whisper {
+    fn cells(&self, _: BoardSize) -> Vec<Position> {
+        self.cells.clone()
+    }
+
+    fn is_possible(&self, values: Values, size: BoardSize) -> bool {
+        let diff = Whisper::min_difference(size);
+        let placed = self
+            .cells
+            .iter()
+            .map(|p| values(p.x, p.y).map(|v| v as usize))
+            .collect::<Vec<Option<usize>>>();
+
+        // a value needs some other value far enough from it
+        let has_partner = |v: usize| v > diff || v + diff <= size.side();
+
+        placed.iter().flatten().all(|v| has_partner(*v))
+            && placed.windows(2).all(|w| match (w[0], w[1]) {
+                (Some(a), Some(b)) => a.abs_diff(b) >= diff,
+                _ => true,
+            })
+    }
+
+    fn to_str(&self) -> String {
+        format!("whisper={}", cells_to_str(&self.cells))
+    }
+}
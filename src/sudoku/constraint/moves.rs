@@ -0,0 +1,109 @@
+use std::fmt::Display;
+
+use super::{Constraint, Values};
+use crate::sudoku::{BoardSize, Position};
+
+const KNIGHT_MOVES: [(isize, isize); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+const KING_MOVES: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// values a chess piece could reach from the cell in a single move
+fn reachable_values(moves: &[(isize, isize)], pos: &Position, values: Values, side: usize) -> u32 {
+    moves
+        .iter()
+        .filter_map(|(dx, dy)| {
+            let x = pos.x.checked_add_signed(*dx).filter(|x| *x < side)?;
+            let y = pos.y.checked_add_signed(*dy).filter(|y| *y < side)?;
+            values(x, y)
+        })
+        .fold(0, |acc, v| acc | (1 << v))
+}
+
+/// no placed value is a move away from the same value
+fn no_repeats(moves: &[(isize, isize)], values: Values, side: usize) -> bool {
+    (0..side * side).all(|k| {
+        let pos = Position::new(k / side, k % side);
+
+        match values(pos.x, pos.y) {
+            Some(v) => reachable_values(moves, &pos, values, side) & (1 << v) == 0,
+            None => true,
+        }
+    })
+}
+
+/// cells a knight's move apart never hold the same value
+#[derive(Debug, Clone, Copy)]
+pub struct AntiKnight;
+
+impl Display for AntiKnight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "anti-knight, cells a knight's move apart differ")
+    }
+}
+
+impl Constraint for AntiKnight {
+    fn cells(&self, size: BoardSize) -> Vec<Position> {
+        let side = size.side();
+        (0..side * side)
+            .map(|k| Position::new(k / side, k % side))
+            .collect()
+    }
+
+    fn is_possible(&self, values: Values, size: BoardSize) -> bool {
+        no_repeats(&KNIGHT_MOVES, values, size.side())
+    }
+
+    fn candidates(&self, pos: &Position, values: Values, size: BoardSize) -> u32 {
+        size.all_values() & !reachable_values(&KNIGHT_MOVES, pos, values, size.side())
+    }
+
+    fn to_str(&self) -> String {
+        "antiknight".to_string()
+    }
+}
+
+/// touching cells, diagonals included, never hold the same value
+#[derive(Debug, Clone, Copy)]
+pub struct AntiKing;
+
+impl Display for AntiKing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "anti-king, touching cells differ")
+    }
+}
+
+impl Constraint for AntiKing {
+    fn cells(&self, size: BoardSize) -> Vec<Position> {
+        AntiKnight.cells(size)
+    }
+
+    fn is_possible(&self, values: Values, size: BoardSize) -> bool {
+        no_repeats(&KING_MOVES, values, size.side())
+    }
+
+    fn candidates(&self, pos: &Position, values: Values, size: BoardSize) -> u32 {
+        size.all_values() & !reachable_values(&KING_MOVES, pos, values, size.side())
+    }
+
+    fn to_str(&self) -> String {
+        "antiking".to_string()
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt::Display;
+
+use super::{Constraint, Values, cell_to_str, cells_to_str};
+use crate::sudoku::{BoardSize, Position};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Parity {
+    Odd,
+    Even,
+}
+
+impl Parity {
+    pub fn of(v: u8) -> Self {
+        match v % 2 {
+            1 => Parity::Odd,
+            _ => Parity::Even,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Parity::Odd => "odd",
+            Parity::Even => "even",
+        }
+    }
+}
+
+/// shaded cells only hold odd or only hold even values
+#[derive(Debug, Clone)]
+pub struct OddEven {
+    parity: Parity,
+    cells: Vec<Position>,
+}
+
+impl OddEven {
+    pub fn new(parity: Parity, cells: Vec<Position>) -> Self {
+        OddEven { parity, cells }
+    }
+
+    /// every value of the board with the parity, as a bitmap
+    fn allowed(&self, size: BoardSize) -> u32 {
+        (1..=size.side() as u8)
+            .filter(|v| Parity::of(*v) == self.parity)
+            .fold(0, |acc, v| acc | (1 << v))
+    }
+}
+
+impl Display for OddEven {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cells = self.cells.iter().map(cell_to_str).collect::<Vec<String>>();
+        write!(f, "{} cells {}", self.parity.name(), cells.join(", "))
+    }
+}
+
+impl Constraint for OddEven {
+    fn cells(&self, _: BoardSize) -> Vec<Position> {
+        self.cells.clone()
+    }
+
+    fn is_possible(&self, values: Values, _: BoardSize) -> bool {
+        self.cells
+            .iter()
+            .filter_map(|p| values(p.x, p.y))
+            .all(|v| Parity::of(v) == self.parity)
+    }
+
+    fn candidates(&self, _: &Position, _: Values, size: BoardSize) -> u32 {
+        self.allowed(size)
+    }
+
+    fn to_str(&self) -> String {
+        format!("{}={}", self.parity.name(), cells_to_str(&self.cells))
+    }
+}
@@ -0,0 +1,115 @@
+use std::{error::Error, fmt::Display};
+
+use super::{Constraint, Values};
+use crate::sudoku::{BoardSize, Position, char_to_digit, coordinate_to_char};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SandwichLine {
+    Row(usize),
+    Column(usize),
+}
+
+/// the values between the lowest and the highest value of a row or column add up to `sum`
+#[derive(Debug, Clone)]
+pub struct Sandwich {
+    line: SandwichLine,
+    sum: usize,
+}
+
+impl Sandwich {
+    pub fn new(line: SandwichLine, sum: usize) -> Self {
+        Sandwich { line, sum }
+    }
+
+    /// written as `<sum>@<r|c><index>`, ex: 15@r3
+    pub fn parse(inp: &str) -> Result<Self, Box<dyn Error>> {
+        let invalid = || format!("invalid sandwich found, expected sum@r0 or sum@c0: {inp}");
+
+        let (sum, line) = inp.trim().split_once('@').ok_or_else(invalid)?;
+        let sum = sum.trim().parse::<usize>()?;
+
+        let chars = line.trim().chars().collect::<Vec<char>>();
+
+        let line = match chars[..] {
+            ['r', i] => char_to_digit(i).map(|i| SandwichLine::Row(i as usize)),
+            ['c', i] => char_to_digit(i).map(|i| SandwichLine::Column(i as usize)),
+            _ => None,
+        };
+
+        Ok(Sandwich::new(line.ok_or_else(invalid)?, sum))
+    }
+
+    /// whether the cells between the crusts at `from` and `to` can add up to the sum
+    fn fits_between(&self, line: &[Option<u8>], from: usize, to: usize, side: usize) -> bool {
+        let (from, to) = (from.min(to), from.max(to));
+        let between = &line[from + 1..to];
+        let placed = between.iter().flatten().map(|v| *v as usize);
+        let empty = between.len() - placed.clone().count();
+        let sum = placed.sum::<usize>();
+
+        // the cells in between hold neither the lowest nor the highest value
+        sum + empty * 2 <= self.sum && sum + empty * (side - 1) >= self.sum
+    }
+}
+
+impl Display for Sandwich {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            SandwichLine::Row(i) => write!(
+                f,
+                "sandwich sum of row {} is {}",
+                coordinate_to_char(i),
+                self.sum
+            ),
+            SandwichLine::Column(i) => {
+                write!(
+                    f,
+                    "sandwich sum of column {} is {}",
+                    coordinate_to_char(i),
+                    self.sum
+                )
+            }
+        }
+    }
+}
+
+impl Constraint for Sandwich {
+    fn cells(&self, size: BoardSize) -> Vec<Position> {
+        (0..size.side())
+            .map(|k| match self.line {
+                SandwichLine::Row(i) => Position::new(i, k),
+                SandwichLine::Column(i) => Position::new(k, i),
+            })
+            .collect()
+    }
+
+    fn is_possible(&self, values: Values, size: BoardSize) -> bool {
+        let side = size.side();
+        let line = self
+            .cells(size)
+            .iter()
+            .map(|p| values(p.x, p.y))
+            .collect::<Vec<Option<u8>>>();
+
+        let low = line.iter().position(|v| *v == Some(1));
+        let high = line.iter().position(|v| *v == Some(side as u8));
+
+        // the missing crust has to go into some empty cell
+        let fits_somewhere = |crust: usize| {
+            (0..side).any(|k| line[k].is_none() && self.fits_between(&line, crust, k, side))
+        };
+
+        match (low, high) {
+            (Some(l), Some(h)) => self.fits_between(&line, l, h, side),
+            (Some(c), None) | (None, Some(c)) => fits_somewhere(c),
+            (None, None) => true,
+        }
+    }
+
+    fn to_str(&self) -> String {
+        match self.line {
+            SandwichLine::Row(i) => format!("sandwich={}@r{}", self.sum, coordinate_to_char(i)),
+            SandwichLine::Column(i) => format!("sandwich={}@c{}", self.sum, coordinate_to_char(i)),
+        }
+    }
+}
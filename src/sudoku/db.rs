@@ -0,0 +1,675 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt::Display,
+    fs::{self, File},
+    io::{self, BufRead, ErrorKind},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rand::{Rng, seq::IndexedRandom};
+
+use super::{Grade, Sudoku, digit_to_char, storage};
+
+const DB_FILE_NAME: &str = "sudoku_puzzles.db";
+
+/// first line of the file, bumped whenever the record format changes
+const DB_HEADER: &str = "sudoku-puzzle-db 1";
+
+/// where a puzzle came from
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Source {
+    /// batch run, along with the seed of the run and the index of the puzzle in it
+    Generated { seed: u64, puzzle_index: usize },
+    /// `valid_puzzles_N` file without the seed next to the board
+    Imported,
+    /// pasted in by the player
+    Loaded,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Source::Generated { seed, puzzle_index } => {
+                write!(f, "generated:{seed}/{puzzle_index}")
+            }
+            Source::Imported => write!(f, "imported"),
+            Source::Loaded => write!(f, "loaded"),
+        }
+    }
+}
+
+impl Source {
+    fn parse(inp: &str) -> Result<Self, Box<dyn Error>> {
+        match inp {
+            "imported" => Ok(Source::Imported),
+            "loaded" => Ok(Source::Loaded),
+            _ => match inp
+                .strip_prefix("generated:")
+                .and_then(|s| s.split_once('/'))
+            {
+                Some((seed, puzzle_index)) => Ok(Source::Generated {
+                    seed: seed.parse()?,
+                    puzzle_index: puzzle_index.parse()?,
+                }),
+                None => Err(format!("unknown puzzle source found: {inp}").into()),
+            },
+        }
+    }
+}
+
+/// a single attempt at a puzzle
+#[derive(Debug, Clone)]
+pub struct Play {
+    /// seconds since the unix epoch
+    played_at: u64,
+    solved: bool,
+    time_taken: Duration,
+}
+
+impl Play {
+    pub fn get_played_at(&self) -> u64 {
+        self.played_at
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.solved
+    }
+
+    pub fn get_time_taken(&self) -> Duration {
+        self.time_taken
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PuzzleRecord {
+    key: String,
+    clues: u8,
+    grade: Grade,
+    source: Source,
+    /// the puzzle as written by `Sudoku::to_str`, givens only
+    layout: String,
+    /// every cell of the solution, one char per cell
+    solution: String,
+    plays: Vec<Play>,
+}
+
+impl PuzzleRecord {
+    pub fn get_key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn get_clues(&self) -> u8 {
+        self.clues
+    }
+
+    pub fn get_grade(&self) -> Grade {
+        self.grade
+    }
+
+    pub fn get_source(&self) -> Source {
+        self.source
+    }
+
+    pub fn get_layout(&self) -> &str {
+        &self.layout
+    }
+
+    pub fn get_solution(&self) -> &str {
+        &self.solution
+    }
+
+    pub fn get_plays(&self) -> &[Play] {
+        &self.plays
+    }
+
+    pub fn is_played(&self) -> bool {
+        !self.plays.is_empty()
+    }
+
+    pub fn to_board(&self) -> Result<Sudoku, Box<dyn Error>> {
+        Sudoku::from_str(&self.layout)
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "P\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.key, self.clues, self.grade, self.source, self.layout, self.solution
+        )
+    }
+}
+
+/// every puzzle generated or imported so far along with its play history, in a single append only
+/// file. Puzzles are written as `P <key> <clues> <grade> <source> <layout> <solution>` and every
+/// attempt as `H <key> <played at> <solved|gave_up> <milliseconds taken>`, tab separated. The
+/// file is read into memory and indexed by key and by clue count and grade when opened
+#[derive(Debug)]
+pub struct PuzzleDb {
+    path: PathBuf,
+    records: Vec<PuzzleRecord>,
+    by_key: HashMap<String, usize>,
+    by_clues_and_grade: BTreeMap<(u8, Grade), Vec<usize>>,
+}
+
+impl PuzzleDb {
+    /// the database next to the stats and puzzle files
+    pub fn open() -> Result<Self, Box<dyn Error>> {
+        PuzzleDb::open_at(DB_FILE_NAME)
+    }
+
+    pub fn open_at<P>(path: P) -> Result<Self, Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+    {
+        let mut db = PuzzleDb {
+            path: path.as_ref().to_path_buf(),
+            records: vec![],
+            by_key: HashMap::new(),
+            by_clues_and_grade: BTreeMap::new(),
+        };
+
+        let file = match File::open(&db.path) {
+            Ok(f) => f,
+            Err(e) => {
+                if e.kind() == ErrorKind::NotFound {
+                    return Ok(db);
+                }
+
+                return Err(e.into());
+            }
+        };
+
+        let _lock = storage::lock_shared(&db.path)?;
+        let mut reader = io::BufReader::new(file);
+        let mut line = String::new();
+        let mut line_number = 0;
+
+        loop {
+            line.clear();
+
+            // a last line without its newline is a write that didn't finish, it is left out and
+            // cut off by the next append
+            if reader.read_line(&mut line)? == 0 || !line.ends_with('\n') {
+                return Ok(db);
+            }
+
+            line_number += 1;
+
+            if line_number == 1 {
+                if line.trim() != DB_HEADER {
+                    return Err(format!("{} isn't a puzzle database", db.path.display()).into());
+                }
+
+                continue;
+            }
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            db.apply_line(line.trim_end_matches(['\n', '\r']))
+                .map_err(|e| format!("invalid record on line {line_number}: {e}"))?;
+        }
+    }
+
+    fn apply_line(&mut self, line: &str) -> Result<(), Box<dyn Error>> {
+        let parts = line.split('\t').collect::<Vec<&str>>();
+
+        match parts[..] {
+            ["P", key, clues, grade, source, layout, solution] => {
+                self.index(PuzzleRecord {
+                    key: key.to_string(),
+                    clues: clues.parse()?,
+                    grade: Grade::parse(grade)?,
+                    source: Source::parse(source)?,
+                    layout: layout.to_string(),
+                    solution: solution.to_string(),
+                    plays: vec![],
+                });
+            }
+            ["H", key, played_at, outcome, millis] => {
+                let idx = match self.by_key.get(key) {
+                    Some(i) => *i,
+                    None => return Err(format!("play found for an unknown puzzle: {key}").into()),
+                };
+
+                let solved = match outcome {
+                    "solved" => true,
+                    "gave_up" => false,
+                    o => return Err(format!("unknown outcome found: {o}").into()),
+                };
+
+                self.records[idx].plays.push(Play {
+                    played_at: played_at.parse()?,
+                    solved,
+                    time_taken: Duration::from_millis(millis.parse()?),
+                });
+            }
+            _ => return Err(format!("unknown record found: {line}").into()),
+        }
+
+        Ok(())
+    }
+
+    /// later copies of a puzzle are ignored
+    fn index(&mut self, record: PuzzleRecord) -> bool {
+        if self.by_key.contains_key(&record.key) {
+            return false;
+        }
+
+        let idx = self.records.len();
+
+        self.by_key.insert(record.key.clone(), idx);
+        self.by_clues_and_grade
+            .entry((record.clues, record.grade))
+            .or_default()
+            .push(idx);
+        self.records.push(record);
+
+        true
+    }
+
+    /// adds the line at the end of the file, other processes can append to it at the same time
+    fn append(&self, line: &str) -> Result<(), Box<dyn Error>> {
+        let lock = storage::lock(&self.path)?;
+        let mut file = storage::open_for_append(&self.path, &lock)?;
+
+        storage::truncate_torn_line(&mut file)?;
+
+        let mut bytes = String::new();
+
+        if file.metadata()?.len() == 0 {
+            bytes.push_str(DB_HEADER);
+            bytes.push('\n');
+        }
+
+        bytes.push_str(line);
+        bytes.push('\n');
+
+        storage::append_synced(&mut file, bytes.as_bytes())
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&PuzzleRecord> {
+        self.by_key.get(key).map(|i| &self.records[*i])
+    }
+
+    pub fn contains(&self, board: &Sudoku) -> bool {
+        self.by_key.contains_key(&board.get_puzzle_key())
+    }
+
+    /// stores the givens of the board, graded. Returns false when the puzzle is already stored
+    pub fn insert(&mut self, board: &Sudoku, source: Source) -> Result<bool, Box<dyn Error>> {
+        let mut puzzle = board.clone();
+        puzzle.hard_reset();
+
+        let record = PuzzleRecord {
+            key: puzzle.get_puzzle_key(),
+            clues: puzzle.number_of_initial_clues(),
+            grade: puzzle.grade(),
+            source,
+            layout: puzzle.to_str(),
+            solution: puzzle
+                .solved_grid
+                .iter()
+                .flatten()
+                .map(|c| c.0.map_or('.', digit_to_char))
+                .collect(),
+            plays: vec![],
+        };
+
+        let line = record.to_line();
+
+        if !self.index(record) {
+            return Ok(false);
+        }
+
+        self.append(&line)?;
+
+        Ok(true)
+    }
+
+    pub fn record_play(
+        &mut self,
+        key: &str,
+        solved: bool,
+        time_taken: Duration,
+    ) -> Result<(), Box<dyn Error>> {
+        let idx = match self.by_key.get(key) {
+            Some(i) => *i,
+            None => return Err(format!("puzzle not found in the database: {key}").into()),
+        };
+
+        let play = Play {
+            played_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            solved,
+            time_taken,
+        };
+
+        self.append(&format!(
+            "H\t{key}\t{}\t{}\t{}",
+            play.played_at,
+            if solved { "solved" } else { "gave_up" },
+            time_taken.as_millis()
+        ))?;
+
+        self.records[idx].plays.push(play);
+
+        Ok(())
+    }
+
+    /// puzzles matching every given filter, in the order they were stored
+    pub fn query(
+        &self,
+        clues: Option<u8>,
+        grade: Option<Grade>,
+        unplayed_only: bool,
+    ) -> Vec<&PuzzleRecord> {
+        let mut matches = self
+            .by_clues_and_grade
+            .iter()
+            .filter(|((c, g), _)| clues.is_none_or(|v| v == *c) && grade.is_none_or(|v| v == *g))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .filter(|i| !unplayed_only || !self.records[*i].is_played())
+            .collect::<Vec<usize>>();
+
+        matches.sort_unstable();
+
+        matches.into_iter().map(|i| &self.records[i]).collect()
+    }
+
+    /// random puzzle matching the filters, ex: an unplayed hard puzzle with 26 clues
+    pub fn pick<R: Rng>(
+        &self,
+        clues: Option<u8>,
+        grade: Option<Grade>,
+        unplayed_only: bool,
+        rng: &mut R,
+    ) -> Option<&PuzzleRecord> {
+        self.query(clues, grade, unplayed_only).choose(rng).copied()
+    }
+
+    /// reads every `clues_N/valid_puzzles_N` file under the directory. Returns the number of
+    /// puzzles added and the number that were already stored. The `invalid_N_K` files only hold
+    /// rejected boards and stay where they are
+    pub fn import_flat_files<P>(&mut self, dir: P) -> Result<(usize, usize), Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+    {
+        let mut added = 0;
+        let mut skipped = 0;
+
+        let mut files = vec![];
+
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if let Some(clues) = name.strip_prefix("clues_") {
+                let path = entry.path().join(format!("valid_puzzles_{clues}"));
+
+                if path.is_file() {
+                    files.push(path);
+                }
+            }
+        }
+
+        files.sort();
+
+        for path in files {
+            let file = File::open(&path)?;
+
+            for (idx, line) in io::BufReader::new(file).lines().enumerate() {
+                let line = line?;
+                let parts = line.split_whitespace().collect::<Vec<&str>>();
+
+                let source = match parts[..] {
+                    [] => continue,
+                    [_, seed, puzzle_index] => Source::Generated {
+                        seed: seed.parse()?,
+                        puzzle_index: puzzle_index.parse()?,
+                    },
+                    _ => Source::Imported,
+                };
+
+                let board = Sudoku::from_str(parts[0]).map_err(|e| {
+                    format!(
+                        "invalid puzzle in {} on line {}: {e}",
+                        path.display(),
+                        idx + 1
+                    )
+                })?;
+
+                match self.insert(&board, source)? {
+                    true => added += 1,
+                    false => skipped += 1,
+                }
+            }
+        }
+
+        Ok((added, skipped))
+    }
+}
+
+impl Sudoku {
+    /// identifies the puzzle in the database, values entered by the player are left out. Classic
+    /// boards are keyed by their canonical form so equivalent copies are stored once
+    pub fn get_puzzle_key(&self) -> String {
+        self.get_canonical_form().unwrap_or_else(|| {
+            let mut puzzle = self.clone();
+            puzzle.hard_reset();
+            puzzle.to_str()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::{
+        CancellationToken, DEFAULT_FALSE_POSITIVE_RATE, Layout, NoProgress, Symmetry,
+    };
+
+    /// empty database file in the temp dir, one per test
+    fn temp_db(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("sudoku_db_{}_{name}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        path
+    }
+
+    #[test]
+    fn resumes_only_the_boards_that_fit_the_layout() {
+        let path = temp_db("resume");
+        let layout = Layout::Random(36, Symmetry::Rotational180);
+        let (boards, _, seed) = Sudoku::generate_boards(
+            layout.clone(),
+            false,
+            2,
+            1,
+            true,
+            DEFAULT_FALSE_POSITIVE_RATE,
+            Some(3),
+            false,
+            &CancellationToken::new(),
+            &mut NoProgress,
+        );
+
+        let mut db = PuzzleDb::open_at(&path).unwrap();
+
+        for (puzzle_index, board) in boards.iter().enumerate() {
+            db.insert(board, Source::Generated { seed, puzzle_index })
+                .unwrap();
+        }
+
+        let resumed = Sudoku::resumed_boards(&db, &layout, false, 2, true, seed).unwrap();
+
+        assert_eq!(
+            resumed
+                .iter()
+                .map(|(i, b)| (*i, b.to_thonky_str()))
+                .collect::<Vec<_>>(),
+            boards
+                .iter()
+                .map(|b| b.to_thonky_str())
+                .enumerate()
+                .collect::<Vec<_>>()
+        );
+
+        for seeded in [true, false] {
+            let other = Layout::Random(36, Symmetry::Rotational90);
+
+            assert!(
+                Sudoku::resumed_boards(&db, &other, false, 2, seeded, seed)
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn drops_a_torn_last_line() {
+        let path = temp_db("torn");
+        let boards = [BOARDS[0], BOARDS[1]].map(|b| Sudoku::from_str(b).unwrap());
+
+        let mut db = PuzzleDb::open_at(&path).unwrap();
+        db.insert(&boards[0], Source::Imported).unwrap();
+
+        // a write cut short by a crash, without its newline
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        io::Write::write_all(&mut file, b"P\t..82...3").unwrap();
+
+        let mut db = PuzzleDb::open_at(&path).unwrap();
+        assert_eq!(db.len(), 1);
+
+        db.insert(&boards[1], Source::Imported).unwrap();
+
+        let db = PuzzleDb::open_at(&path).unwrap();
+        assert_eq!(db.len(), 2);
+        assert!(boards.iter().all(|b| db.contains(b)));
+        assert!(fs::read_to_string(&path).unwrap().ends_with('\n'));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    const BOARDS: [&str; 3] = [
+        "..82...3...147..65.5..6.49.8.6..53.2.......7..2....8.6..9....43..431.....7.694..1",
+        ".6..17..4241.38.5.9....41823...465917...21.......8....6.7..28....8.6.7.9....7.4.5",
+        "3.9....7..6..7..1.........8.37.94......6.723482..3..6.283...495.765.812..5..296..",
+    ];
+
+    #[test]
+    fn queries_by_clues_grade_and_plays() {
+        let path = temp_db("query");
+        let mut db = PuzzleDb::open_at(&path).unwrap();
+
+        for b in BOARDS {
+            assert!(
+                db.insert(&Sudoku::from_str(b).unwrap(), Source::Imported)
+                    .unwrap()
+            );
+        }
+
+        assert!(
+            !db.insert(&Sudoku::from_str(BOARDS[0]).unwrap(), Source::Imported)
+                .unwrap()
+        );
+        assert_eq!(db.len(), 3);
+        assert_eq!(db.query(Some(32), None, false).len(), 1);
+        assert_eq!(db.query(Some(36), None, false).len(), 2);
+        assert!(db.query(Some(30), None, false).is_empty());
+
+        for record in db.query(None, None, false) {
+            let found = db.query(Some(record.get_clues()), Some(record.get_grade()), false);
+
+            assert!(found.iter().any(|r| r.get_key() == record.get_key()));
+            assert!(found.iter().all(
+                |r| (r.get_clues(), r.get_grade()) == (record.get_clues(), record.get_grade())
+            ));
+        }
+
+        let played = db.query(Some(36), None, false)[0].get_key().to_string();
+        db.record_play(&played, true, Duration::from_secs(90))
+            .unwrap();
+
+        // plays are read back along with the puzzles
+        let db = PuzzleDb::open_at(&path).unwrap();
+        let unplayed = db.query(Some(36), None, true);
+
+        assert_eq!(unplayed.len(), 1);
+        assert_ne!(unplayed[0].get_key(), played);
+        assert_eq!(db.get(&played).unwrap().get_plays().len(), 1);
+        assert!(db.get(&played).unwrap().get_plays()[0].is_solved());
+
+        let picked = db.pick(Some(32), None, true, &mut rand::rng()).unwrap();
+        assert_eq!(picked.to_board().unwrap().to_thonky_str(), BOARDS[0]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn imports_the_flat_files() {
+        let dir = std::env::temp_dir().join(format!("sudoku_import_{}", std::process::id()));
+        let path = dir.join("puzzles.db");
+        fs::create_dir_all(dir.join("clues_32")).unwrap();
+        fs::create_dir_all(dir.join("clues_36")).unwrap();
+
+        fs::write(
+            dir.join("clues_32/valid_puzzles_32"),
+            format!("{}\n\n", BOARDS[0]),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("clues_36/valid_puzzles_36"),
+            format!("{} 42 0\n{} 42 1\n", BOARDS[1], BOARDS[2]),
+        )
+        .unwrap();
+        // rejected boards aren't puzzles
+        fs::write(
+            dir.join("clues_36/invalid_36_0"),
+            format!("{}\n", BOARDS[0]),
+        )
+        .unwrap();
+
+        let mut db = PuzzleDb::open_at(&path).unwrap();
+
+        assert_eq!(db.import_flat_files(&dir).unwrap(), (3, 0));
+        assert_eq!(db.import_flat_files(&dir).unwrap(), (0, 3));
+
+        let sources = db
+            .query(None, None, false)
+            .iter()
+            .map(|r| r.get_source())
+            .collect::<Vec<Source>>();
+
+        assert_eq!(
+            sources,
+            [
+                Source::Imported,
+                Source::Generated {
+                    seed: 42,
+                    puzzle_index: 0
+                },
+                Source::Generated {
+                    seed: 42,
+                    puzzle_index: 1
+                },
+            ]
+        );
+
+        fs::write(dir.join("clues_32/valid_puzzles_32"), "not a board\n").unwrap();
+        assert!(db.import_flat_files(&dir).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
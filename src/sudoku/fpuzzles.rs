@@ -0,0 +1,766 @@
+use std::{error::Error, sync::Arc};
+
+use super::{
+    AntiKing, AntiKnight, Arrow, Board, BoardSize, Cage, CellState, Consecutive, Constraint,
+    Kropki, KropkiDot, OddEven, Parity, Position, Sandwich, SandwichLine, Sudoku, Thermometer,
+    Variant, Whisper, Xv, char_to_digit,
+    constraint::{cell_from_str, cells_from_str},
+};
+
+mod json;
+mod lz;
+
+use json::Json;
+
+const FPUZZLES_LINK: &str = "https://www.f-puzzles.com/?load=";
+const SUDOKUPAD_LINK: &str = "https://sudokupad.app/fpuzzles";
+
+/// colour whispers are drawn with, f-puzzles has no whispers of its own so they are plain lines
+const WHISPER_COLOUR: &str = "#67F067";
+
+/// f-puzzles rules the board model has no counterpart for, a board using them is refused
+/// instead of being played with fewer rules
+const UNSUPPORTED_RULES: [&str; 9] = [
+    "disjointgroups",
+    "cage",
+    "littlekillersum",
+    "palindrome",
+    "clone",
+    "quadruple",
+    "betweenline",
+    "minimum",
+    "maximum",
+];
+
+/// whether the input is an f-puzzles or sudokupad link, or the json behind one
+pub(super) fn is_fpuzzles(inp: &str) -> bool {
+    let inp = inp.trim();
+
+    inp.starts_with('{')
+        || inp.starts_with("fpuzzles")
+        || inp.contains("f-puzzles.com")
+        || inp.contains("sudokupad.app")
+}
+
+/// json behind a link, the compressed board follows `load=` on f-puzzles and `fpuzzles` on
+/// sudokupad
+fn decode(inp: &str) -> Result<Json, Box<dyn Error>> {
+    let inp = inp.trim();
+
+    if inp.starts_with('{') {
+        return Json::parse(inp);
+    }
+
+    let compressed = match (inp.split_once("load="), inp.split_once("fpuzzles")) {
+        (Some((_, c)), _) | (None, Some((_, c))) => c,
+        (None, None) => {
+            return Err(format!("no f-puzzles board found in the link: {inp}").into());
+        }
+    };
+
+    let compressed = compressed
+        .split('&')
+        .next()
+        .unwrap_or_default()
+        .replace("%2B", "+")
+        .replace("%2F", "/")
+        .replace("%3D", "=");
+
+    Json::parse(&lz::decompress_from_base64(&compressed)?)
+}
+
+/// cell written the f-puzzles way, 1 based, ex: R1C1
+fn cell_to_rc(pos: &Position) -> Json {
+    Json::Str(format!("R{}C{}", pos.x + 1, pos.y + 1))
+}
+
+fn cells_to_rc(cells: &[Position]) -> Json {
+    Json::Array(cells.iter().map(cell_to_rc).collect())
+}
+
+/// row and column of an `R<row>C<column>` cell, rows and columns outside the board are 0
+fn rc_from_json(cell: &Json) -> Result<(usize, usize), Box<dyn Error>> {
+    let invalid = || format!("invalid f-puzzles cell found, expected R1C1: {cell}");
+
+    let cell = cell.as_str().ok_or_else(invalid)?.trim().to_uppercase();
+
+    cell.strip_prefix('R')
+        .and_then(|c| c.split_once('C'))
+        .and_then(|(r, c)| Some((r.parse::<usize>().ok()?, c.parse::<usize>().ok()?)))
+        .ok_or_else(|| invalid().into())
+}
+
+fn cell_from_rc(cell: &Json) -> Result<Position, Box<dyn Error>> {
+    match rc_from_json(cell)? {
+        (r, c) if r > 0 && c > 0 => Ok(Position::new(r - 1, c - 1)),
+        _ => Err(format!("f-puzzles cell is outside the board: {cell}").into()),
+    }
+}
+
+fn cells_from_rc(cells: &Json) -> Result<Vec<Position>, Box<dyn Error>> {
+    cells.as_array().iter().map(cell_from_rc).collect()
+}
+
+/// cells of every line of the entry, thermos and arrows can have several
+fn lines_of(entry: &Json) -> Result<Vec<Vec<Position>>, Box<dyn Error>> {
+    entry
+        .get("lines")
+        .map(|l| l.as_array())
+        .unwrap_or_default()
+        .iter()
+        .map(cells_from_rc)
+        .collect()
+}
+
+/// the two cells a dot or an xv sits between
+fn pair_of(entry: &Json, name: &str) -> Result<(Position, Position), Box<dyn Error>> {
+    match &cells_from_rc(entry.get("cells").unwrap_or(&Json::Null))?[..] {
+        [a, b] => Ok((a.clone(), b.clone())),
+        _ => Err(format!("an f-puzzles {name} sits between two cells: {entry}").into()),
+    }
+}
+
+/// whether a `#rrggbb` colour is mostly green, how whispers are told apart from other lines
+fn is_green(colour: &str) -> bool {
+    let channel = |i: usize| {
+        colour
+            .get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .map(|c| c as i32)
+    };
+
+    match (colour.len(), channel(1), channel(3), channel(5)) {
+        (7, Some(r), Some(g), Some(b)) => g > r + 40 && g > b + 40,
+        _ => false,
+    }
+}
+
+/// entries of a rule, empty when the board doesn't use it
+fn entries<'a>(puzzle: &'a Json, key: &str) -> &'a [Json] {
+    puzzle.get(key).map(|e| e.as_array()).unwrap_or_default()
+}
+
+impl Sudoku {
+    /// reads an f-puzzles or sudokupad link, or the json behind one. Givens, cages, regions,
+    /// diagonals, windows and the constraints the board model knows about are carried over
+    pub fn from_fpuzzles(inp: &str) -> Result<Self, Box<dyn Error>> {
+        let puzzle = decode(inp)?;
+
+        let side = puzzle
+            .get("size")
+            .and_then(|s| s.as_number())
+            .unwrap_or(9.0) as usize;
+        let size = match BoardSize::from_side(side) {
+            Some(s) => s,
+            None => return Err(format!("f-puzzles board of size {side} isn't supported").into()),
+        };
+
+        if let Some(rule) = UNSUPPORTED_RULES
+            .iter()
+            .find(|r| puzzle.get(r).is_some_and(|v| v.is_set()))
+        {
+            return Err(format!("f-puzzles rule `{rule}` isn't supported").into());
+        }
+
+        let rows = puzzle.get("grid").map(|g| g.as_array()).unwrap_or_default();
+
+        if rows.len() != side || rows.iter().any(|r| r.as_array().len() != side) {
+            return Err(format!("f-puzzles grid has to be {side}x{side}").into());
+        }
+
+        let mut grid: Board = vec![vec![(None, CellState::Normal); side]; side];
+        let mut regions = (0..side)
+            .map(|i| (0..side).map(|j| size.block_id(i, j)).collect())
+            .collect::<Vec<Vec<usize>>>();
+
+        for (i, row) in rows.iter().enumerate() {
+            for (j, cell) in row.as_array().iter().enumerate() {
+                if let Some(v) = cell.get("value").and_then(|v| v.as_number()) {
+                    if v.fract() != 0.0 || !(1.0..=side as f64).contains(&v) {
+                        return Err(format!("f-puzzles value {v} doesn't fit the board").into());
+                    }
+
+                    let state = match cell.get("given").is_some_and(|g| g.is_set()) {
+                        true => CellState::Normal,
+                        false => CellState::UserMarkedDefault,
+                    };
+
+                    grid[i][j] = (Some(v as u8), state);
+                }
+
+                match cell.get("region") {
+                    Some(Json::Number(r)) if (*r as usize) < side => regions[i][j] = *r as usize,
+                    Some(Json::Null) | None => (),
+                    Some(r) => return Err(format!("invalid f-puzzles region found: {r}").into()),
+                }
+            }
+        }
+
+        let jigsaw = (0..side).any(|i| (0..side).any(|j| regions[i][j] != size.block_id(i, j)));
+
+        let mut cages = vec![];
+
+        for cage in entries(&puzzle, "killercage") {
+            let cells = cells_from_rc(cage.get("cells").unwrap_or(&Json::Null))?;
+
+            match cage.get("value").and_then(|v| v.as_number()) {
+                Some(sum) => cages.push(Cage::new(sum as u32, cells)),
+                None => return Err(format!("killer cage without a sum found: {cage}").into()),
+            }
+        }
+
+        let diagonals =
+            ["diagonal+", "diagonal-"].map(|d| puzzle.get(d).is_some_and(|v| v.is_set()));
+
+        if diagonals[0] != diagonals[1] {
+            return Err("f-puzzles board with a single diagonal isn't supported".into());
+        }
+
+        let windows = entries(&puzzle, "extraregion");
+
+        if !windows.is_empty() {
+            let mut expected = (0..size.window_count()).collect::<Vec<usize>>();
+
+            for window in windows {
+                let cells = cells_from_rc(window.get("cells").unwrap_or(&Json::Null))?;
+                let id = cells.first().and_then(|p| size.window_id(p.x, p.y));
+
+                match id {
+                    Some(id)
+                        if cells.len() == side
+                            && cells.iter().all(|p| size.window_id(p.x, p.y) == Some(id)) =>
+                    {
+                        expected.retain(|w| *w != id)
+                    }
+                    _ => {
+                        return Err(
+                            "extra regions other than the windoku windows aren't supported".into(),
+                        );
+                    }
+                }
+            }
+
+            if !expected.is_empty() {
+                return Err("extra regions have to cover every windoku window".into());
+            }
+        }
+
+        let rule_sets = [
+            (diagonals[0], Variant::Diagonal),
+            (!cages.is_empty(), Variant::Killer),
+            (jigsaw, Variant::Jigsaw),
+            (!windows.is_empty(), Variant::Windoku),
+        ]
+        .into_iter()
+        .filter(|(used, _)| *used)
+        .map(|(_, v)| v)
+        .collect::<Vec<Variant>>();
+
+        let variant = match rule_sets[..] {
+            [] => Variant::Classic,
+            [v] => v,
+            [a, b, ..] => {
+                return Err(format!(
+                    "f-puzzles board combines {a} and {b}, only one rule set is supported"
+                )
+                .into());
+            }
+        };
+
+        let constraints = Sudoku::fpuzzles_constraints(&puzzle)?;
+
+        Sudoku::build(
+            size,
+            variant,
+            cages,
+            jigsaw.then_some(regions),
+            constraints,
+            grid,
+        )
+    }
+
+    fn fpuzzles_constraints(puzzle: &Json) -> Result<Vec<Arc<dyn Constraint>>, Box<dyn Error>> {
+        let mut constraints: Vec<Arc<dyn Constraint>> = vec![];
+
+        for thermo in entries(puzzle, "thermometer") {
+            for line in lines_of(thermo)? {
+                constraints.push(Arc::new(Thermometer::new(line)));
+            }
+        }
+
+        for arrow in entries(puzzle, "arrow") {
+            let circle = match &cells_from_rc(arrow.get("cells").unwrap_or(&Json::Null))?[..] {
+                [c] => c.clone(),
+                _ => {
+                    return Err(format!(
+                        "only arrows with a single cell circle are supported: {arrow}"
+                    )
+                    .into());
+                }
+            };
+
+            for line in lines_of(arrow)? {
+                let cells = line.into_iter().filter(|p| *p != circle).collect();
+                constraints.push(Arc::new(Arrow::new(circle.clone(), cells)));
+            }
+        }
+
+        for line in entries(puzzle, "line") {
+            match line.get("outlineC").and_then(|c| c.as_str()) {
+                Some(c) if is_green(c) => {
+                    for cells in lines_of(line)? {
+                        constraints.push(Arc::new(Whisper::new(cells)));
+                    }
+                }
+                _ => return Err("lines other than green whispers aren't supported".into()),
+            }
+        }
+
+        let negative = entries(puzzle, "negative")
+            .iter()
+            .filter_map(|n| n.as_str())
+            .collect::<Vec<&str>>();
+
+        if let Some(n) = negative.iter().find(|n| **n != "difference") {
+            return Err(format!("f-puzzles negative constraint `{n}` isn't supported").into());
+        }
+
+        // with the negative constraint the white dots are every consecutive pair there is
+        let bars = negative.contains(&"difference");
+        let mut marked = vec![];
+
+        for (key, dot, value) in [
+            ("difference", KropkiDot::White, 1.0),
+            ("ratio", KropkiDot::Black, 2.0),
+        ] {
+            for entry in entries(puzzle, key) {
+                if entry
+                    .get("value")
+                    .and_then(|v| v.as_number())
+                    .is_some_and(|v| v != value)
+                {
+                    return Err(
+                        format!("f-puzzles {key} other than {value} isn't supported").into(),
+                    );
+                }
+
+                let (a, b) = pair_of(entry, key)?;
+
+                match bars && dot == KropkiDot::White {
+                    true => marked.push((a, b)),
+                    false => constraints.push(Arc::new(Kropki::new(dot, a, b))),
+                }
+            }
+        }
+
+        let nonconsecutive = puzzle.get("nonconsecutive").is_some_and(|v| v.is_set());
+
+        if nonconsecutive && !bars && !entries(puzzle, "difference").is_empty() {
+            return Err("f-puzzles white dots on a non-consecutive board aren't supported".into());
+        }
+
+        if bars || nonconsecutive {
+            constraints.push(Arc::new(Consecutive::new(marked)));
+        }
+
+        for (key, parity) in [("odd", Parity::Odd), ("even", Parity::Even)] {
+            let cells = entries(puzzle, key)
+                .iter()
+                .map(|e| cell_from_rc(e.get("cell").unwrap_or(&Json::Null)))
+                .collect::<Result<Vec<Position>, Box<dyn Error>>>()?;
+
+            if !cells.is_empty() {
+                constraints.push(Arc::new(OddEven::new(parity, cells)));
+            }
+        }
+
+        for entry in entries(puzzle, "xv") {
+            let sum = match entry.get("value").and_then(|v| v.as_str()) {
+                Some(v) if v.eq_ignore_ascii_case("x") => 10,
+                Some(v) if v.eq_ignore_ascii_case("v") => 5,
+                _ => return Err(format!("xv has to be either an X or a V: {entry}").into()),
+            };
+
+            let (a, b) = pair_of(entry, "xv")?;
+            constraints.push(Arc::new(Xv::new(sum, a, b)));
+        }
+
+        for entry in entries(puzzle, "sandwichsum") {
+            let sum = entry.get("value").and_then(|v| v.as_number());
+
+            let line = match rc_from_json(entry.get("cell").unwrap_or(&Json::Null))? {
+                (0, c) if c > 0 => SandwichLine::Column(c - 1),
+                (r, 0) if r > 0 => SandwichLine::Row(r - 1),
+                _ => {
+                    return Err(
+                        format!("sandwich sum has to sit outside the board: {entry}").into(),
+                    );
+                }
+            };
+
+            match sum {
+                Some(sum) => constraints.push(Arc::new(Sandwich::new(line, sum as usize))),
+                None => return Err(format!("sandwich without a sum found: {entry}").into()),
+            }
+        }
+
+        if puzzle.get("antiknight").is_some_and(|v| v.is_set()) {
+            constraints.push(Arc::new(AntiKnight));
+        }
+
+        if puzzle.get("antiking").is_some_and(|v| v.is_set()) {
+            constraints.push(Arc::new(AntiKing));
+        }
+
+        Ok(constraints)
+    }
+
+    /// the board as f-puzzles json, user entries are kept as values that aren't given. Fails
+    /// on constraints f-puzzles has no counterpart for
+    fn to_fpuzzles_json(&self) -> Result<Json, Box<dyn Error>> {
+        let side = self.size.side();
+        let mut puzzle = vec![("size".to_string(), Json::Number(side as f64))];
+
+        let grid = (0..side)
+            .map(|i| {
+                Json::Array(
+                    (0..side)
+                        .map(|j| {
+                            let mut cell = vec![];
+
+                            if let Some(v) = self.placed_value(i, j) {
+                                cell.push(("value".to_string(), Json::Number(v as f64)));
+
+                                if self.prefilled_positions.contains_key(&Position::new(i, j)) {
+                                    cell.push(("given".to_string(), Json::Bool(true)));
+                                }
+                            }
+
+                            if self.variant.has_regions() {
+                                cell.push((
+                                    "region".to_string(),
+                                    Json::Number(self.regions[i][j] as f64),
+                                ));
+                            }
+
+                            Json::Object(cell)
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+
+        puzzle.push(("grid".to_string(), Json::Array(grid)));
+
+        if self.variant.has_diagonals() {
+            puzzle.push(("diagonal+".to_string(), Json::Bool(true)));
+            puzzle.push(("diagonal-".to_string(), Json::Bool(true)));
+        }
+
+        if self.variant.has_cages() {
+            let cages = self
+                .cages
+                .iter()
+                .map(|c| {
+                    Json::Object(vec![
+                        ("cells".to_string(), cells_to_rc(c.get_cells())),
+                        ("value".to_string(), Json::Str(c.get_sum().to_string())),
+                    ])
+                })
+                .collect();
+
+            puzzle.push(("killercage".to_string(), Json::Array(cages)));
+        }
+
+        if self.variant.has_windows() {
+            let windows = (0..self.size.window_count())
+                .map(|w| {
+                    let cells = (0..side * side)
+                        .map(|k| Position::new(k / side, k % side))
+                        .filter(|p| self.size.window_id(p.x, p.y) == Some(w))
+                        .collect::<Vec<Position>>();
+
+                    Json::Object(vec![("cells".to_string(), cells_to_rc(&cells))])
+                })
+                .collect();
+
+            puzzle.push(("extraregion".to_string(), Json::Array(windows)));
+        }
+
+        for c in &self.constraints {
+            for (key, entry) in fpuzzles_entries(c.as_ref())? {
+                match (puzzle.iter_mut().find(|(k, _)| *k == key), entry) {
+                    (Some((_, Json::Array(items))), entry) => items.push(entry),
+                    (_, Json::Bool(on)) => puzzle.push((key.to_string(), Json::Bool(on))),
+                    (_, entry) => puzzle.push((key.to_string(), Json::Array(vec![entry]))),
+                }
+            }
+        }
+
+        Ok(Json::Object(puzzle))
+    }
+
+    /// link that opens the board on f-puzzles
+    pub fn to_fpuzzles_url(&self) -> Result<String, Box<dyn Error>> {
+        Ok(format!(
+            "{FPUZZLES_LINK}{}",
+            lz::compress_to_base64(&self.to_fpuzzles_json()?.to_string())
+        ))
+    }
+
+    /// link that opens the board on sudokupad
+    pub fn to_sudokupad_url(&self) -> Result<String, Box<dyn Error>> {
+        Ok(format!(
+            "{SUDOKUPAD_LINK}{}",
+            lz::compress_to_base64(&self.to_fpuzzles_json()?.to_string())
+        ))
+    }
+}
+
+/// f-puzzles keys and list entries of a constraint, read off its layout form. Rules that are
+/// switched on as a whole, like anti knight, are `true` instead
+fn fpuzzles_entries(
+    constraint: &dyn Constraint,
+) -> Result<Vec<(&'static str, Json)>, Box<dyn Error>> {
+    let layout = constraint.to_str();
+    let (name, args) = layout.split_once('=').unwrap_or((&layout, ""));
+
+    let object = |entries: Vec<(&str, Json)>| {
+        Json::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        )
+    };
+
+    let entry = match name {
+        "odd" | "even" => {
+            let key = if name == "odd" { "odd" } else { "even" };
+
+            return Ok(cells_from_str(args)?
+                .iter()
+                .map(|p| (key, object(vec![("cell", cell_to_rc(p))])))
+                .collect());
+        }
+        "consecutive" => {
+            let mut entries = vec![("negative", Json::Str("difference".to_string()))];
+
+            for pair in args.split('/') {
+                entries.push((
+                    "difference",
+                    object(vec![("cells", cells_to_rc(&cells_from_str(pair)?))]),
+                ));
+            }
+
+            return Ok(entries);
+        }
+        "nonconsecutive" => ("nonconsecutive", Json::Bool(true)),
+        "thermo" => (
+            "thermometer",
+            object(vec![(
+                "lines",
+                Json::Array(vec![cells_to_rc(&cells_from_str(args)?)]),
+            )]),
+        ),
+        "arrow" => {
+            let (circle, arrow) = args.split_once('>').ok_or("arrow without a circle")?;
+            let circle = cell_from_str(circle)?;
+            let mut line = vec![circle.clone()];
+            line.extend(cells_from_str(arrow)?);
+
+            (
+                "arrow",
+                object(vec![
+                    ("cells", cells_to_rc(&[circle])),
+                    ("lines", Json::Array(vec![cells_to_rc(&line)])),
+                ]),
+            )
+        }
+        "whisper" => (
+            "line",
+            object(vec![
+                (
+                    "lines",
+                    Json::Array(vec![cells_to_rc(&cells_from_str(args)?)]),
+                ),
+                ("outlineC", Json::Str(WHISPER_COLOUR.to_string())),
+                ("width", Json::Number(0.3)),
+            ]),
+        ),
+        "white" | "black" => (
+            if name == "white" {
+                "difference"
+            } else {
+                "ratio"
+            },
+            object(vec![("cells", cells_to_rc(&cells_from_str(args)?))]),
+        ),
+        "x" | "v" => (
+            "xv",
+            object(vec![
+                ("cells", cells_to_rc(&cells_from_str(args)?)),
+                ("value", Json::Str(name.to_uppercase())),
+            ]),
+        ),
+        "sandwich" => {
+            let (sum, line) = args.split_once('@').ok_or("sandwich without a line")?;
+            let index = line[1..]
+                .chars()
+                .next()
+                .and_then(char_to_digit)
+                .ok_or("sandwich without a line")? as usize;
+
+            let cell = match &line[..1] {
+                "r" => format!("R{}C0", index + 1),
+                _ => format!("R0C{}", index + 1),
+            };
+
+            (
+                "sandwichsum",
+                object(vec![
+                    ("cell", Json::Str(cell)),
+                    ("value", Json::Str(sum.to_string())),
+                ]),
+            )
+        }
+        "antiknight" => ("antiknight", Json::Bool(true)),
+        "antiking" => ("antiking", Json::Bool(true)),
+        _ => return Err(format!("constraint `{constraint}` has no f-puzzles counterpart").into()),
+    };
+
+    Ok(vec![entry])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::{constraint::parse_constraint, seeded_rng::SeededRng};
+
+    /// the classic board from wikipedia the way f-puzzles stores it, compressed by lz-string
+    const CLASSIC: &str = "N4IgzglgXgpiBcBOANCALhNAbO8QGEsBDMSAYxFSIFc0ALAewCcEQB1CAawgAcYATCEUogm1HGBhpWAOWYBbIlgAEYavwadqysROVEePLAE8AdCIDmTCPwQBtO6ABuS6rgCsqCxCcwAdghoYjAAvsjOrrgAzF4+/oHBYcBJSSAuWG4IAOyxvgHwQW4p4cXJALrIjmmRCABsufEFiSUtERm4AIwN+YWh4dXtCCgg3nkJRf3pmfCeI3E9zcmtIRWOqVO4w6ONves18AAc3eN9S2elA9P1c2NNE+WVbdNHNzuLpXuD8NfbC/cfk32MVef1CqyeuAALMc7qcLhsEC9fid4UCYbtloCvl0QSjwZdcDlcbCAWcCQgAEzo96YiF1alFcGfK4MuG05m4KnEjF0w6slJM2nk+DQ7mLYU45EkrHTLbzFFChEzfmC86tYVI+XStW8olSnnCuW3XZlMohIA=";
+
+    const CLASSIC_GIVENS: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    /// board filled under the rule set and the constraint, every cell is a user entry so the
+    /// board has a single solution whatever the constraint is
+    fn filled(variant: Variant, constraint: Option<&str>) -> Sudoku {
+        let mut board = Sudoku::empty(BoardSize::CLASSIC, variant);
+
+        if let Some(c) = constraint {
+            board.add_constraint(parse_constraint(c).unwrap()).unwrap();
+        }
+
+        assert!(board.fill_randomly(&mut SeededRng::new(1)));
+        board
+    }
+
+    fn assert_round_trip(layout: &str) {
+        let board = Sudoku::from_str(layout).unwrap();
+
+        for link in [board.to_fpuzzles_url(), board.to_sudokupad_url()] {
+            assert_eq!(Sudoku::from_str(&link.unwrap()).unwrap().to_str(), layout);
+        }
+    }
+
+    #[test]
+    fn decodes_links() {
+        for link in [
+            format!("{FPUZZLES_LINK}{CLASSIC}"),
+            format!("{SUDOKUPAD_LINK}{CLASSIC}"),
+            format!(
+                "{FPUZZLES_LINK}{}",
+                CLASSIC.replace('+', "%2B").replace('/', "%2F")
+            ),
+            format!("{FPUZZLES_LINK}{}&lang=en", CLASSIC.replace('+', " ")),
+        ] {
+            let board = Sudoku::from_fpuzzles(&link).unwrap();
+
+            assert_eq!(board.get_variant(), Variant::Classic);
+            assert_eq!(board.to_thonky_str(), CLASSIC_GIVENS);
+        }
+    }
+
+    #[test]
+    fn rejects_broken_links() {
+        for link in [
+            format!("{FPUZZLES_LINK}{}", &CLASSIC[..CLASSIC.len() / 2]),
+            format!("{FPUZZLES_LINK}{}", &CLASSIC[..CLASSIC.len() - 8]),
+            format!("{FPUZZLES_LINK}{}*", &CLASSIC[..20]),
+            FPUZZLES_LINK.to_string(),
+            "https://www.f-puzzles.com/".to_string(),
+        ] {
+            assert!(Sudoku::from_fpuzzles(&link).is_err(), "{link}");
+        }
+    }
+
+    #[test]
+    fn rejects_values_off_the_board() {
+        for value in ["0", "10", "2.5", "-1"] {
+            let puzzle = format!(
+                "{{\"size\":4,\"grid\":[[{{\"value\":{value}}},{{}},{{}},{{}}],[{{}},{{}},{{}},{{}}],[{{}},{{}},{{}},{{}}],[{{}},{{}},{{}},{{}}]]}}"
+            );
+
+            assert!(Sudoku::from_fpuzzles(&puzzle).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn round_trips_values() {
+        let layout = Sudoku::from_str(CLASSIC_GIVENS)
+            .unwrap()
+            .to_str()
+            .replacen(",,", ",u4,", 1);
+
+        assert_round_trip(&layout);
+    }
+
+    #[test]
+    fn round_trips_constraints() {
+        for constraint in [
+            "thermo=00+01+02",
+            "arrow=11>01+02",
+            "whisper=00+01+02",
+            "white=00+01",
+            "black=00+10",
+            "x=00+01",
+            "v=00+10",
+            "sandwich=10@r0",
+            "sandwich=15@c3",
+            "odd=00+11",
+            "even=22",
+            "consecutive=00+01/10+11",
+            "nonconsecutive",
+            "antiknight",
+            "antiking",
+        ] {
+            assert_round_trip(&filled(Variant::Classic, Some(constraint)).to_str());
+        }
+    }
+
+    #[test]
+    fn round_trips_rule_sets() {
+        for variant in [Variant::Diagonal, Variant::Windoku] {
+            assert_round_trip(&filled(variant, None).to_str());
+        }
+
+        let jigsaw = filled(Variant::Jigsaw, None).with_random_regions(&mut SeededRng::new(1));
+        assert_round_trip(&jigsaw.to_str());
+
+        let classic = filled(Variant::Classic, None);
+        let sum = |cells: &[(usize, usize)]| {
+            cells
+                .iter()
+                .map(|&(x, y)| classic.grid[x][y].0.unwrap() as u32)
+                .sum::<u32>()
+        };
+
+        assert_round_trip(&format!(
+            "k:{};{}=00+01;{}=10+11+20",
+            classic.to_str(),
+            sum(&[(0, 0), (0, 1)]),
+            sum(&[(1, 0), (1, 1), (2, 0)])
+        ));
+    }
+}
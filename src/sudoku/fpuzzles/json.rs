@@ -0,0 +1,268 @@
+use std::{error::Error, fmt::Display, iter::Peekable, str::Chars};
+
+/// just enough json to read and write f-puzzles boards, objects keep the order of their keys
+#[derive(PartialEq, Debug, Clone)]
+pub(super) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Number(n) => write!(f, "{n}"),
+            Json::Str(s) => write_str(f, s),
+            Json::Array(items) => {
+                write!(f, "[")?;
+
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write!(f, "{item}")?;
+                }
+
+                write!(f, "]")
+            }
+            Json::Object(entries) => {
+                write!(f, "{{")?;
+
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write_str(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_str(f: &mut std::fmt::Formatter<'_>, s: &str) -> std::fmt::Result {
+    write!(f, "\"")?;
+
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+impl Json {
+    pub(super) fn parse(inp: &str) -> Result<Self, Box<dyn Error>> {
+        let mut chars = inp.chars().peekable();
+        let value = parse_value(&mut chars)?;
+
+        skip_whitespace(&mut chars);
+
+        match chars.next() {
+            Some(c) => Err(format!("unexpected `{c}` found after the json value").into()),
+            None => Ok(value),
+        }
+    }
+
+    /// value of the key, none for missing keys and for anything that isn't an object
+    pub(super) fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_array(&self) -> &[Json] {
+        match self {
+            Json::Array(items) => items,
+            _ => &[],
+        }
+    }
+
+    pub(super) fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// numbers are often written as strings, ex: the sum of a cage
+    pub(super) fn as_number(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            Json::Str(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// whether the value switches a rule on, `true` or a non empty list
+    pub(super) fn is_set(&self) -> bool {
+        match self {
+            Json::Null => false,
+            Json::Bool(b) => *b,
+            Json::Array(items) => !items.is_empty(),
+            _ => true,
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn expect_word(chars: &mut Peekable<Chars>, word: &str) -> Result<(), Box<dyn Error>> {
+    for expected in word.chars() {
+        if chars.next() != Some(expected) {
+            return Err(format!("invalid json found, expected `{word}`").into());
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<Json, Box<dyn Error>> {
+    skip_whitespace(chars);
+
+    match chars.peek() {
+        Some('{') => parse_object(chars),
+        Some('[') => parse_array(chars),
+        Some('"') => Ok(Json::Str(parse_string(chars)?)),
+        Some('t') => expect_word(chars, "true").map(|_| Json::Bool(true)),
+        Some('f') => expect_word(chars, "false").map(|_| Json::Bool(false)),
+        Some('n') => expect_word(chars, "null").map(|_| Json::Null),
+        Some(c) if *c == '-' || c.is_ascii_digit() => parse_number(chars),
+        Some(c) => Err(format!("unexpected `{c}` found in json").into()),
+        None => Err("json ends too early".into()),
+    }
+}
+
+fn parse_object(chars: &mut Peekable<Chars>) -> Result<Json, Box<dyn Error>> {
+    chars.next();
+
+    let mut entries = vec![];
+
+    skip_whitespace(chars);
+
+    if chars.next_if_eq(&'}').is_some() {
+        return Ok(Json::Object(entries));
+    }
+
+    loop {
+        skip_whitespace(chars);
+
+        if chars.peek() != Some(&'"') {
+            return Err("invalid json found, expected a key".into());
+        }
+
+        let key = parse_string(chars)?;
+
+        skip_whitespace(chars);
+
+        if chars.next() != Some(':') {
+            return Err(format!("invalid json found, expected `:` after the key {key}").into());
+        }
+
+        entries.push((key, parse_value(chars)?));
+
+        skip_whitespace(chars);
+
+        match chars.next() {
+            Some(',') => (),
+            Some('}') => return Ok(Json::Object(entries)),
+            _ => return Err("invalid json found, expected `,` or `}`".into()),
+        }
+    }
+}
+
+fn parse_array(chars: &mut Peekable<Chars>) -> Result<Json, Box<dyn Error>> {
+    chars.next();
+
+    let mut items = vec![];
+
+    skip_whitespace(chars);
+
+    if chars.next_if_eq(&']').is_some() {
+        return Ok(Json::Array(items));
+    }
+
+    loop {
+        items.push(parse_value(chars)?);
+
+        skip_whitespace(chars);
+
+        match chars.next() {
+            Some(',') => (),
+            Some(']') => return Ok(Json::Array(items)),
+            _ => return Err("invalid json found, expected `,` or `]`".into()),
+        }
+    }
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, Box<dyn Error>> {
+    chars.next();
+
+    // kept as utf-16 as surrogate pairs come as two separate escapes
+    let mut units: Vec<u16> = vec![];
+
+    loop {
+        let c = match chars.next() {
+            Some('"') => return Ok(String::from_utf16_lossy(&units)),
+            Some('\\') => match chars.next() {
+                Some('u') => {
+                    units.push(parse_unicode_escape(chars)?);
+                    continue;
+                }
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('t') => '\t',
+                Some('b') => '\u{8}',
+                Some('f') => '\u{c}',
+                Some(c) => c,
+                None => return Err("json ends within a string".into()),
+            },
+            Some(c) => c,
+            None => return Err("json ends within a string".into()),
+        };
+
+        units.extend_from_slice(c.encode_utf16(&mut [0; 2]));
+    }
+}
+
+/// the 4 hex digits following a `\u`
+fn parse_unicode_escape(chars: &mut Peekable<Chars>) -> Result<u16, Box<dyn Error>> {
+    let hex = chars.by_ref().take(4).collect::<String>();
+
+    match u16::from_str_radix(&hex, 16) {
+        Ok(u) => Ok(u),
+        Err(_) => Err(format!("invalid unicode escape found in json: {hex}").into()),
+    }
+}
+
+fn parse_number(chars: &mut Peekable<Chars>) -> Result<Json, Box<dyn Error>> {
+    let mut number = String::new();
+
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || "+-.eE".contains(*c)) {
+        number.push(c);
+    }
+
+    match number.parse() {
+        Ok(n) => Ok(Json::Number(n)),
+        Err(_) => Err(format!("invalid number found in json: {number}").into()),
+    }
+}
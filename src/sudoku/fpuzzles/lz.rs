@@ -0,0 +1,343 @@
+use std::{
+    collections::{HashMap, HashSet, hash_map::Entry},
+    error::Error,
+};
+
+/// alphabet of lz-string's base64 output, `=` pads the output to a multiple of 4 chars
+const BASE64_CHARS: &[u8; 65] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=";
+
+/// value of a char of the base64 output, the url safe alphabet (`-` and `$`) is accepted too
+fn base64_value(c: char) -> Option<u32> {
+    match c {
+        '-' => Some(63),
+        '$' => Some(64),
+        // `+` turns into a space when a link goes through a query string decoder
+        ' ' => Some(62),
+        _ => BASE64_CHARS
+            .iter()
+            .position(|b| *b as char == c)
+            .map(|p| p as u32),
+    }
+}
+
+/// compresses the text the way lz-string's `compressToBase64` does
+pub(super) fn compress_to_base64(inp: &str) -> String {
+    let mut resp = compress(&inp.encode_utf16().collect::<Vec<u16>>(), 6, |v: u32| {
+        BASE64_CHARS[v as usize] as char
+    });
+
+    match resp.len() % 4 {
+        1 => resp.push_str("==="),
+        2 => resp.push_str("=="),
+        3 => resp.push('='),
+        _ => (),
+    }
+
+    resp
+}
+
+/// reverses `compress_to_base64`, fails on chars outside the alphabet and on cut off input
+pub(super) fn decompress_from_base64(inp: &str) -> Result<String, Box<dyn Error>> {
+    let values = inp
+        .trim()
+        .chars()
+        .map(|c| base64_value(c).ok_or(format!("invalid char found in compressed puzzle: {c}")))
+        .collect::<Result<Vec<u32>, String>>()?;
+
+    if values.is_empty() {
+        return Err("compressed puzzle is empty".into());
+    }
+
+    match decompress(&values, 32)? {
+        units if units.is_empty() => Err("compressed puzzle holds nothing".into()),
+        units => Ok(String::from_utf16(&units)?),
+    }
+}
+
+/// writes values bit by bit, lowest bit first, into chars holding `bits_per_char` bits each
+struct BitWriter<F: Fn(u32) -> char> {
+    bits_per_char: usize,
+    to_char: F,
+    value: u32,
+    position: usize,
+    out: String,
+}
+
+impl<F: Fn(u32) -> char> BitWriter<F> {
+    fn write(&mut self, bits: usize, mut value: u32) {
+        for _ in 0..bits {
+            self.value = (self.value << 1) | (value & 1);
+            value >>= 1;
+
+            if self.position == self.bits_per_char - 1 {
+                self.out.push((self.to_char)(self.value));
+                self.position = 0;
+                self.value = 0;
+            } else {
+                self.position += 1;
+            }
+        }
+    }
+
+    /// pushes the last, partly filled char
+    fn finish(mut self) -> String {
+        loop {
+            self.value <<= 1;
+
+            if self.position == self.bits_per_char - 1 {
+                self.out.push((self.to_char)(self.value));
+                return self.out;
+            }
+
+            self.position += 1;
+        }
+    }
+}
+
+/// lz-string's dictionary coder, new single chars are written with a marker (0 for 8 bit,
+/// 1 for 16 bit chars) and 2 ends the stream
+struct Compressor<F: Fn(u32) -> char> {
+    writer: BitWriter<F>,
+    dictionary: HashMap<Vec<u16>, u32>,
+    /// single chars that are in the dictionary but weren't written out yet
+    to_create: HashSet<Vec<u16>>,
+    enlarge_in: u32,
+    num_bits: usize,
+}
+
+impl<F: Fn(u32) -> char> Compressor<F> {
+    /// every code is one bit wider once the dictionary outgrows the current width
+    fn count_code(&mut self) {
+        self.enlarge_in -= 1;
+
+        if self.enlarge_in == 0 {
+            self.enlarge_in = 1 << self.num_bits;
+            self.num_bits += 1;
+        }
+    }
+
+    fn emit(&mut self, w: &[u16]) {
+        if self.to_create.remove(w) {
+            match w[0] < 256 {
+                true => {
+                    self.writer.write(self.num_bits, 0);
+                    self.writer.write(8, w[0] as u32);
+                }
+                false => {
+                    self.writer.write(self.num_bits, 1);
+                    self.writer.write(16, w[0] as u32);
+                }
+            }
+
+            self.count_code();
+        } else {
+            self.writer.write(self.num_bits, self.dictionary[w]);
+        }
+
+        self.count_code();
+    }
+}
+
+fn compress<F: Fn(u32) -> char>(inp: &[u16], bits_per_char: usize, to_char: F) -> String {
+    let mut compressor = Compressor {
+        writer: BitWriter {
+            bits_per_char,
+            to_char,
+            value: 0,
+            position: 0,
+            out: String::new(),
+        },
+        dictionary: HashMap::new(),
+        to_create: HashSet::new(),
+        enlarge_in: 2,
+        num_bits: 2,
+    };
+
+    // the first three codes stand for the markers
+    let mut dict_size = 3u32;
+    let mut w: Vec<u16> = vec![];
+
+    for &c in inp {
+        if let Entry::Vacant(e) = compressor.dictionary.entry(vec![c]) {
+            e.insert(dict_size);
+            dict_size += 1;
+            compressor.to_create.insert(vec![c]);
+        }
+
+        let mut wc = w.clone();
+        wc.push(c);
+
+        if compressor.dictionary.contains_key(&wc) {
+            w = wc;
+            continue;
+        }
+
+        compressor.emit(&w);
+        compressor.dictionary.insert(wc, dict_size);
+        dict_size += 1;
+        w = vec![c];
+    }
+
+    if !w.is_empty() {
+        compressor.emit(&w);
+    }
+
+    compressor.writer.write(compressor.num_bits, 2);
+    compressor.writer.finish()
+}
+
+/// reads values bit by bit, highest bit of every input value first
+struct BitReader<'a> {
+    inp: &'a [u32],
+    reset_value: u32,
+    value: u32,
+    position: u32,
+    index: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, bits: usize) -> Result<u32, Box<dyn Error>> {
+        let mut resp = 0;
+
+        for i in 0..bits {
+            if self.value & self.position != 0 {
+                resp |= 1 << i;
+            }
+
+            self.position >>= 1;
+
+            if self.position == 0 {
+                if self.index >= self.inp.len() {
+                    return Err("compressed puzzle ends too early".into());
+                }
+
+                self.position = self.reset_value;
+                self.value = self.inp[self.index];
+                self.index += 1;
+            }
+        }
+
+        Ok(resp)
+    }
+}
+
+fn decompress(inp: &[u32], reset_value: u32) -> Result<Vec<u16>, Box<dyn Error>> {
+    let mut reader = BitReader {
+        inp,
+        reset_value,
+        value: inp[0],
+        position: reset_value,
+        index: 1,
+    };
+
+    // the first three entries stand for the markers
+    let mut dictionary: Vec<Vec<u16>> = vec![vec![], vec![], vec![]];
+    let mut enlarge_in = 4u32;
+    let mut num_bits = 3usize;
+
+    let first = match reader.read(2)? {
+        0 => reader.read(8)? as u16,
+        1 => reader.read(16)? as u16,
+        _ => return Ok(vec![]),
+    };
+
+    dictionary.push(vec![first]);
+    let mut w = vec![first];
+    let mut resp = vec![first];
+
+    loop {
+        let mut c = reader.read(num_bits)? as usize;
+
+        match c {
+            0 | 1 => {
+                let bits = if c == 0 { 8 } else { 16 };
+                dictionary.push(vec![reader.read(bits)? as u16]);
+                c = dictionary.len() - 1;
+                enlarge_in -= 1;
+            }
+            2 => return Ok(resp),
+            _ => (),
+        }
+
+        if enlarge_in == 0 {
+            enlarge_in = 1 << num_bits;
+            num_bits += 1;
+        }
+
+        let entry = match c {
+            c if c < dictionary.len() => dictionary[c].clone(),
+            c if c == dictionary.len() => {
+                let mut entry = w.clone();
+                entry.push(w[0]);
+                entry
+            }
+            _ => return Err("compressed puzzle is corrupt".into()),
+        };
+
+        resp.extend_from_slice(&entry);
+
+        let mut next = w;
+        next.push(entry[0]);
+        dictionary.push(next);
+
+        enlarge_in -= 1;
+        w = entry;
+
+        if enlarge_in == 0 {
+            enlarge_in = 1 << num_bits;
+            num_bits += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compresses_like_lz_string() {
+        assert_eq!(
+            compress_to_base64("Hello, world"),
+            "BIUwNmD2A0AEDukBOYAmQ==="
+        );
+        assert_eq!(
+            decompress_from_base64("BIUwNmD2A0AEDukBOYAmQ===").unwrap(),
+            "Hello, world"
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let long = "{\"value\":5,\"given\":true},{},".repeat(200);
+
+        for inp in [
+            "a",
+            "aaaaaaaaaaaaaaaa",
+            "abcabcabcabd",
+            "sudoku ★ 数独 ü",
+            &long,
+        ] {
+            assert_eq!(
+                decompress_from_base64(&compress_to_base64(inp)).unwrap(),
+                inp
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let compressed = compress_to_base64(&"{\"value\":5,\"given\":true}".repeat(20));
+
+        for len in [1, 2, compressed.len() / 2, compressed.len() - 6] {
+            assert!(decompress_from_base64(&compressed[..len]).is_err(), "{len}");
+        }
+    }
+
+    #[test]
+    fn rejects_corrupt_input() {
+        assert!(decompress_from_base64("").is_err());
+        assert!(decompress_from_base64("N4Ig*zgl").is_err());
+        assert!(decompress_from_base64("////").is_err());
+    }
+}
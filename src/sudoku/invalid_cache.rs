@@ -0,0 +1,418 @@
+use std::{
+    error::Error,
+    f64::consts::LN_2,
+    fs::{self, File, OpenOptions},
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{BufReader, ErrorKind, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use super::{Sudoku, storage};
+
+/// first bytes of every cache file, the version goes up whenever the encoding changes. The
+/// records of every append form a frame, preceded by their length in bytes as a little endian
+/// u32, so an append that didn't finish can be told apart
+const CACHE_HEADER: &[u8] = b"sudoku-invalid-cache 1\n";
+
+/// bytes of the length a frame starts with
+const FRAME_LEN_BYTES: u64 = 4;
+
+/// cells of the classic boards the cache is kept for
+const CELLS: usize = 81;
+
+/// bytes of the bitset of given cells a packed board starts with
+const BITSET_LEN: usize = CELLS.div_ceil(8);
+
+/// givens of a classic board as the invalid board cache keeps them: a bitset of the given cells,
+/// cell `k` at bit `k % 8` of byte `k / 8`, followed by their values in row order, two to a byte
+/// with the first one in the low nibble. 26 bytes for a board with 30 clues instead of 81
+pub(super) type PackedBoard = Box<[u8]>;
+
+/// packs the 81 cells of a classic board, empty cells are 0
+pub(super) fn pack(cells: &[u8]) -> PackedBoard {
+    assert_eq!(cells.len(), CELLS, "only classic boards are cached");
+
+    let givens = cells.iter().filter(|v| **v != 0).count();
+    let mut resp = vec![0u8; BITSET_LEN + givens.div_ceil(2)];
+
+    for (i, (k, v)) in cells
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| **v != 0)
+        .enumerate()
+    {
+        resp[k / 8] |= 1 << (k % 8);
+        resp[BITSET_LEN + i / 2] |= v << (4 * (i % 2));
+    }
+
+    resp.into_boxed_slice()
+}
+
+/// bytes of the packed board the bitset belongs to
+fn packed_len(bitset: &[u8]) -> usize {
+    let givens = bitset
+        .iter()
+        .map(|b| b.count_ones() as usize)
+        .sum::<usize>();
+    BITSET_LEN + givens.div_ceil(2)
+}
+
+/// share of the boards the filter over the cache reports as invalid without being so, unless
+/// the run asks for another one
+pub const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.001;
+
+/// bloom filter over the cached invalid boards, under 2 bytes a board at the default rate where
+/// the records take 26 or more. It never misses a board that was added, but also reports a share
+/// of the others, which a batch run then skips without checking them
+#[derive(Debug)]
+pub(super) struct InvalidFilter {
+    bits: Vec<u64>,
+    hashes: u64,
+}
+
+impl InvalidFilter {
+    /// filter sized for `capacity` boards, adding more raises the false positive rate
+    pub(super) fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        let capacity = capacity.max(1) as f64;
+        let rate = false_positive_rate.clamp(1e-9, 0.5);
+        let words = (-capacity * rate.ln() / (LN_2 * LN_2) / 64.0).ceil() as usize;
+        let hashes = (words as f64 * 64.0 / capacity * LN_2).round();
+
+        InvalidFilter {
+            bits: vec![0; words.max(1)],
+            hashes: hashes.clamp(1.0, 32.0) as u64,
+        }
+    }
+
+    /// bits of the board, two independent hashes combined as in Kirsch and Mitzenmacher
+    fn positions(&self, board: &[u8]) -> impl Iterator<Item = usize> + use<> {
+        let hash = |salt: u8| {
+            let mut hasher = DefaultHasher::new();
+            salt.hash(&mut hasher);
+            board.hash(&mut hasher);
+            hasher.finish()
+        };
+        let (first, second) = (hash(0), hash(1) | 1);
+        let len = self.bits.len() as u64 * 64;
+
+        (0..self.hashes).map(move |i| (first.wrapping_add(i.wrapping_mul(second)) % len) as usize)
+    }
+
+    pub(super) fn insert(&mut self, board: &[u8]) {
+        for k in self.positions(board).collect::<Vec<usize>>() {
+            self.bits[k / 64] |= 1 << (k % 64);
+        }
+    }
+
+    pub(super) fn contains(&self, board: &[u8]) -> bool {
+        self.positions(board)
+            .all(|k| self.bits[k / 64] & (1 << (k % 64)) != 0)
+    }
+}
+
+/// single file holding every invalid board tried with the number of clues, under the directory
+/// the puzzle files are kept in
+fn cache_file_name(dir: &Path, number_of_clues: u8) -> PathBuf {
+    dir.join(format!(
+        "clues_{number_of_clues}/invalid_{number_of_clues}.bin"
+    ))
+}
+
+/// records of a frame, one after another. Fails when the last one runs past the end
+fn split_records(mut bytes: &[u8]) -> Result<Vec<PackedBoard>, Box<dyn Error>> {
+    let mut resp = vec![];
+
+    while !bytes.is_empty() {
+        let len = match bytes.get(..BITSET_LEN) {
+            Some(bitset) => packed_len(bitset),
+            None => return Err("the invalid board cache holds a cut off record".into()),
+        };
+
+        let record = match bytes.get(..len) {
+            Some(r) => r,
+            None => return Err("the invalid board cache holds a cut off record".into()),
+        };
+
+        resp.push(record.into());
+        bytes = &bytes[len..];
+    }
+
+    Ok(resp)
+}
+
+/// length of the file up to the end of its last whole frame, 0 when even the header is torn.
+/// Fails when the file isn't a cache in the current format
+fn whole_frames_len(file: &mut File) -> Result<u64, Box<dyn Error>> {
+    let len = file.metadata()?.len();
+
+    if len < CACHE_HEADER.len() as u64 {
+        return Ok(0);
+    }
+
+    let mut header = vec![0u8; CACHE_HEADER.len()];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut header)?;
+
+    if header != CACHE_HEADER {
+        return Err("the file isn't an invalid board cache".into());
+    }
+
+    let mut end = CACHE_HEADER.len() as u64;
+    let mut frame_len = [0u8; FRAME_LEN_BYTES as usize];
+
+    while end + FRAME_LEN_BYTES <= len {
+        file.seek(SeekFrom::Start(end))?;
+        file.read_exact(&mut frame_len)?;
+
+        let next = end + FRAME_LEN_BYTES + u32::from_le_bytes(frame_len) as u64;
+
+        if next > len {
+            break;
+        }
+
+        end = next;
+    }
+
+    Ok(end)
+}
+
+impl Sudoku {
+    /// filter over the invalid boards cached in `dir` with the number of clues, see
+    /// `load_invalid_cache`. It is sized from the file, the records of a number of clues all
+    /// take the same number of bytes
+    pub(super) fn load_invalid_filter(
+        dir: &Path,
+        number_of_clues: u8,
+        false_positive_rate: f64,
+    ) -> Result<InvalidFilter, Box<dyn Error>> {
+        Sudoku::migrate_legacy_invalid_files(dir, number_of_clues)?;
+
+        let record_len = (BITSET_LEN + (number_of_clues as usize).div_ceil(2)) as u64;
+        let capacity = match fs::metadata(cache_file_name(dir, number_of_clues)) {
+            Ok(m) => m.len() / record_len,
+            Err(e) if e.kind() == ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut filter = InvalidFilter::new(capacity as usize, false_positive_rate);
+        Sudoku::load_invalid_cache(dir, number_of_clues, |v| filter.insert(&v))?;
+
+        Ok(filter)
+    }
+
+    /// feeds every cached invalid board with the number of clues to `process`, returns how many
+    /// there were. Boards still in the old `invalid_N_K` text files are moved into the cache
+    /// first, an append that didn't finish is cut off
+    pub(super) fn load_invalid_cache<F>(
+        dir: &Path,
+        number_of_clues: u8,
+        mut process: F,
+    ) -> Result<usize, Box<dyn Error>>
+    where
+        F: FnMut(PackedBoard),
+    {
+        Sudoku::migrate_legacy_invalid_files(dir, number_of_clues)?;
+
+        let path = cache_file_name(dir, number_of_clues);
+        let path = path.as_path();
+
+        if !path.exists() {
+            return Ok(0);
+        }
+
+        let _lock = storage::lock(path)?;
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let mut reader = BufReader::new(&file);
+        let mut header = vec![0u8; CACHE_HEADER.len()];
+
+        match reader.read_exact(&mut header) {
+            Ok(_) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                storage::truncate(&file, 0)?;
+                return Ok(0);
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        if header != CACHE_HEADER {
+            return Err(format!("{} is not an invalid board cache", path.display()).into());
+        }
+
+        let mut count = 0;
+        let mut end = CACHE_HEADER.len() as u64;
+        let mut frame_len = [0u8; FRAME_LEN_BYTES as usize];
+
+        loop {
+            match reader.read_exact(&mut frame_len) {
+                Ok(_) => (),
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            }
+
+            let mut frame = vec![0u8; u32::from_le_bytes(frame_len) as usize];
+
+            match reader.read_exact(&mut frame) {
+                Ok(_) => (),
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            }
+
+            for record in split_records(&frame)? {
+                process(record);
+                count += 1;
+            }
+
+            end += FRAME_LEN_BYTES + frame.len() as u64;
+        }
+
+        if end < file.metadata()?.len() {
+            storage::truncate(&file, end)?;
+        }
+
+        Ok(count)
+    }
+
+    /// adds the boards to the cache file of the number of clues as a single frame, other
+    /// processes can append to it at the same time
+    pub(super) fn append_to_invalid_cache(
+        dir: &Path,
+        number_of_clues: u8,
+        boards: &[PackedBoard],
+    ) -> Result<(), Box<dyn Error>> {
+        let path = cache_file_name(dir, number_of_clues);
+        let path = path.as_path();
+
+        let lock = storage::lock(path)?;
+        let mut file = storage::open_for_append(path, &lock)?;
+
+        let end = whole_frames_len(&mut file)?;
+
+        if end < file.metadata()?.len() {
+            storage::truncate(&file, end)?;
+        }
+
+        let records = boards.concat();
+        let mut bytes = vec![];
+
+        if end == 0 {
+            bytes.extend_from_slice(CACHE_HEADER);
+        }
+
+        bytes.extend_from_slice(&(records.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&records);
+
+        storage::append_synced(&mut file, &bytes)
+    }
+
+    /// moves the boards of the old text files into the cache, a file is removed once its boards
+    /// are written. An interrupted migration only leaves duplicates behind, which are harmless
+    fn migrate_legacy_invalid_files(dir: &Path, number_of_clues: u8) -> Result<(), Box<dyn Error>> {
+        let prefix = format!("invalid_{number_of_clues}_");
+
+        let entries = match fs::read_dir(dir.join(format!("clues_{number_of_clues}"))) {
+            Ok(e) => e,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        // numbered files, the numbers don't have to follow each other
+        let mut legacy = vec![];
+
+        for entry in entries {
+            let entry = entry?;
+
+            if let Some(n) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix(&prefix))
+                .and_then(|n| n.parse::<usize>().ok())
+            {
+                legacy.push((n, entry.path()));
+            }
+        }
+
+        legacy.sort();
+
+        for (_, path) in legacy {
+            let mut boards = vec![];
+
+            // another process may have migrated the file in the meantime
+            if !Sudoku::read_lines(&path, |v| boards.push(pack(&v)))? {
+                continue;
+            }
+
+            Sudoku::append_to_invalid_cache(dir, number_of_clues, &boards)?;
+
+            match fs::remove_file(&path) {
+                Ok(_) => (),
+                Err(e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// board with 32 givens, relabelled `n` times so every copy differs
+    fn board(n: u8) -> String {
+        "..82...3...147..65.5..6.49.8.6..53.2.......7..2....8.6..9....43..431.....7.694..1"
+            .chars()
+            .map(|c| match c.to_digit(10) {
+                Some(v) => char::from(b'0' + ((v as u8 - 1 + n) % 9 + 1)),
+                None => c,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn filter_finds_every_board_added() {
+        let boards = (0..2000u32)
+            .map(|i| i.to_le_bytes().repeat(7))
+            .collect::<Vec<Vec<u8>>>();
+        let mut filter = InvalidFilter::new(1000, 0.01);
+
+        for b in &boards[..1000] {
+            filter.insert(b);
+        }
+
+        assert!(boards[..1000].iter().all(|b| filter.contains(b)));
+
+        let false_positives = boards[1000..].iter().filter(|b| filter.contains(b)).count();
+        assert!(false_positives < 40, "{false_positives} false positives");
+    }
+
+    #[test]
+    fn moves_the_old_text_files_into_the_cache() {
+        let dir = std::env::temp_dir().join(format!("sudoku_invalid_{}", std::process::id()));
+        let clues = dir.join("clues_32");
+        fs::create_dir_all(&clues).unwrap();
+
+        let lines = |range: std::ops::Range<u8>| range.map(|n| board(n) + "\n").collect::<String>();
+        fs::write(clues.join("invalid_32_0"), lines(0..5)).unwrap();
+        fs::write(clues.join("invalid_32_3"), lines(5..9)).unwrap();
+
+        let expected = (0..9)
+            .map(|n| pack(&Sudoku::thonky_to_diet_board(&board(n)).unwrap()))
+            .collect::<Vec<PackedBoard>>();
+
+        for _ in 0..2 {
+            let mut cached = vec![];
+            Sudoku::load_invalid_cache(&dir, 32, |b| cached.push(b)).unwrap();
+
+            assert_eq!(cached, expected);
+        }
+
+        assert!(!clues.join("invalid_32_0").exists());
+        assert!(!clues.join("invalid_32_3").exists());
+
+        let filter = Sudoku::load_invalid_filter(&dir, 32, DEFAULT_FALSE_POSITIVE_RATE).unwrap();
+        assert!(expected.iter().all(|b| filter.contains(b)));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
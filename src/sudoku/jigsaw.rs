@@ -0,0 +1,278 @@
+use std::error::Error;
+
+use rand::Rng;
+
+use super::{BoardSize, Sudoku, char_to_digit, coordinate_to_char, seeded_rng};
+
+/// background of the regions, muted so the values stay readable on top
+pub const REGION_COLOURS: [(u8, u8, u8); 8] = [
+    (70, 70, 110),
+    (110, 65, 65),
+    (60, 100, 60),
+    (105, 95, 50),
+    (55, 95, 105),
+    (100, 60, 100),
+    (85, 85, 85),
+    (115, 80, 50),
+];
+
+/// swaps tried per cell of the board while shuffling the regions
+const SHUFFLE_ROUNDS: usize = 50;
+
+impl Sudoku {
+    /// region map of the board, the region of every cell
+    pub fn get_regions(&self) -> &[Vec<usize>] {
+        &self.regions
+    }
+
+    /// sets the regions of an empty board, every region has to be connected and `side` cells big
+    pub fn set_regions(&mut self, regions: Vec<Vec<usize>>) -> Result<(), Box<dyn Error>> {
+        let side = self.size.side();
+
+        if regions.len() != side || regions.iter().any(|r| r.len() != side) {
+            return Err(format!("region map has to be {side}x{side}").into());
+        }
+
+        for id in 0..side {
+            let cells = Sudoku::region_cells(&regions, id);
+
+            if cells.len() != side {
+                return Err(format!(
+                    "region {} has {} cells, expected {side}",
+                    coordinate_to_char(id),
+                    cells.len()
+                )
+                .into());
+            }
+
+            if !Sudoku::is_connected(&regions, id) {
+                return Err(format!("region {} isn't connected", coordinate_to_char(id)).into());
+            }
+        }
+
+        self.regions = regions;
+
+        Ok(())
+    }
+
+    /// rows and columns the box lines are drawn after, jigsaw boards only get the outer border
+    /// as their regions are told apart by colour
+    pub fn get_drawn_box(&self) -> (usize, usize) {
+        match self.variant.has_regions() {
+            true => (self.size.side(), self.size.side()),
+            false => (self.size.get_box_rows(), self.size.get_box_cols()),
+        }
+    }
+
+    /// region map written row by row, one char per cell
+    pub(super) fn regions_to_str(&self) -> String {
+        self.regions
+            .iter()
+            .flat_map(|r| r.iter().map(|id| coordinate_to_char(*id)))
+            .collect()
+    }
+
+    pub(super) fn regions_from_str(
+        size: BoardSize,
+        inp: &str,
+    ) -> Result<Vec<Vec<usize>>, Box<dyn Error>> {
+        let ids = inp
+            .trim()
+            .chars()
+            .map(|c| char_to_digit(c).map(|id| id as usize))
+            .collect::<Option<Vec<usize>>>();
+
+        match ids {
+            Some(ids) if ids.len() == size.total_positions() => {
+                Ok(ids.chunks(size.side()).map(|c| c.to_vec()).collect())
+            }
+            _ => Err(format!(
+                "invalid region map found, expected {} region ids",
+                size.total_positions()
+            )
+            .into()),
+        }
+    }
+
+    /// colour index of every region, neighbouring regions never share one. A region without a
+    /// free colour sends the search back to the ones before it, a map never needs more than four
+    /// colours so the palette is always enough
+    pub fn region_colours(&self) -> Vec<usize> {
+        let side = self.size.side();
+        let mut adjacent = vec![vec![false; side]; side];
+
+        for x in 0..side {
+            for y in 0..side {
+                for (i, j) in Sudoku::neighbours(side, x, y) {
+                    let (a, b) = (self.regions[x][y], self.regions[i][j]);
+                    adjacent[a][b] |= a != b;
+                }
+            }
+        }
+
+        let mut colours = vec![0; side];
+
+        assert!(
+            Sudoku::colour_regions(&adjacent, &mut colours, 0),
+            "four colours are enough for any map"
+        );
+
+        colours
+    }
+
+    /// colours regions `id..` given the colours of the ones before, false when they can't be
+    fn colour_regions(adjacent: &[Vec<bool>], colours: &mut [usize], id: usize) -> bool {
+        if id == colours.len() {
+            return true;
+        }
+
+        for c in 0..REGION_COLOURS.len() {
+            if (0..id).any(|other| adjacent[id][other] && colours[other] == c) {
+                continue;
+            }
+
+            colours[id] = c;
+
+            if Sudoku::colour_regions(adjacent, colours, id + 1) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// reshapes the regions of a solved board with regular boxes. Two cells holding the same
+    /// value keep swapping regions, as long as both regions stay connected, so every region
+    /// still holds every value and the board stays solved
+    pub(super) fn with_random_regions<R: Rng>(&self, rng: &mut R) -> Self {
+        let side = self.size.side();
+        let value = |x: usize, y: usize| self.grid[x][y].0;
+        let mut regions = self.regions.clone();
+
+        for _ in 0..self.size.total_positions() * SHUFFLE_ROUNDS {
+            let (ax, ay) = (seeded_rng::below(rng, side), seeded_rng::below(rng, side));
+            let a = regions[ax][ay];
+
+            // a region next to the cell
+            let others = Sudoku::neighbours(side, ax, ay)
+                .map(|(x, y)| regions[x][y])
+                .filter(|r| *r != a)
+                .collect::<Vec<usize>>();
+
+            if others.is_empty() {
+                continue;
+            }
+
+            let b = others[seeded_rng::below(rng, others.len())];
+
+            // the cell of that region with the same value, it has to touch the first region
+            let Some((cx, cy)) = Sudoku::region_cells(&regions, b)
+                .into_iter()
+                .find(|&(x, y)| value(x, y) == value(ax, ay))
+            else {
+                continue;
+            };
+
+            if !Sudoku::neighbours(side, cx, cy).any(|(i, j)| regions[i][j] == a) {
+                continue;
+            }
+
+            regions[ax][ay] = b;
+            regions[cx][cy] = a;
+
+            if !Sudoku::is_connected(&regions, a) || !Sudoku::is_connected(&regions, b) {
+                regions[ax][ay] = a;
+                regions[cx][cy] = b;
+            }
+        }
+
+        let mut board = Sudoku::empty(self.size, self.variant);
+        board
+            .set_regions(regions)
+            .expect("shuffled regions stay valid");
+        board.constraints = self.constraints.clone();
+        board.constraint_ids = self.constraint_ids.clone();
+        board
+            .fill_grid(self.grid.clone())
+            .expect("every region still holds every value");
+
+        board
+    }
+
+    fn region_cells(regions: &[Vec<usize>], id: usize) -> Vec<(usize, usize)> {
+        regions
+            .iter()
+            .enumerate()
+            .flat_map(|(i, r)| {
+                r.iter()
+                    .enumerate()
+                    .filter(move |(_, r)| **r == id)
+                    .map(move |(j, _)| (i, j))
+            })
+            .collect()
+    }
+
+    fn neighbours(side: usize, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ]
+        .into_iter()
+        .filter(move |(i, j)| *i < side && *j < side)
+    }
+
+    fn is_connected(regions: &[Vec<usize>], id: usize) -> bool {
+        let cells = Sudoku::region_cells(regions, id);
+
+        let Some(start) = cells.first() else {
+            return false;
+        };
+
+        let mut seen = vec![*start];
+        let mut stack = vec![*start];
+
+        while let Some((x, y)) = stack.pop() {
+            for n in Sudoku::neighbours(regions.len(), x, y) {
+                if regions[n.0][n.1] == id && !seen.contains(&n) {
+                    seen.push(n);
+                    stack.push(n);
+                }
+            }
+        }
+
+        seen.len() == cells.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::{Variant, seeded_rng::SeededRng};
+
+    #[test]
+    fn neighbouring_regions_get_different_colours() {
+        for size in BoardSize::SUPPORTED {
+            let mut rng = SeededRng::new(3);
+            let mut board = Sudoku::empty(size, Variant::Jigsaw);
+            assert!(board.fill_randomly(&mut rng));
+
+            let board = board.with_random_regions(&mut rng);
+            let colours = board.region_colours();
+            let side = size.side();
+
+            for x in 0..side {
+                for y in 0..side {
+                    let a = board.regions[x][y];
+                    assert!(colours[a] < REGION_COLOURS.len());
+
+                    for (i, j) in Sudoku::neighbours(side, x, y) {
+                        let b = board.regions[i][j];
+                        assert!(a == b || colours[a] != colours[b], "{size}: {a} and {b}");
+                    }
+                }
+            }
+        }
+    }
+}
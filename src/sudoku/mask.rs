@@ -0,0 +1,219 @@
+use std::error::Error;
+
+use super::{BoardSize, CancellationToken, Layout, ProgressObserver, Sudoku};
+
+/// no classic puzzle with fewer givens has a unique solution
+const MIN_MASK_CLUES: u8 = 17;
+
+/// cells of a classic board that have to be givens, the shape of a puzzle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClueMask {
+    cells: Vec<bool>,
+}
+
+impl ClueMask {
+    /// 81 cells in row order, `.`, `0` or `-` for an empty cell and anything else (ex: `x` or a
+    /// digit) for a given, so the givens of a thonky string work as well. Whitespace is skipped,
+    /// the mask can be drawn over several lines
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(inp: &str) -> Result<Self, Box<dyn Error>> {
+        let size = BoardSize::CLASSIC;
+
+        let cells = inp
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| !matches!(c, '.' | '0' | '-'))
+            .collect::<Vec<bool>>();
+
+        if cells.len() != size.total_positions() {
+            return Err(format!(
+                "a mask has {} cells but found {}",
+                size.total_positions(),
+                cells.len()
+            )
+            .into());
+        }
+
+        let mask = ClueMask { cells };
+        let clues = mask.number_of_clues();
+
+        if clues < MIN_MASK_CLUES || clues > size.max_clues() {
+            return Err(format!(
+                "a mask needs between {MIN_MASK_CLUES} and {} givens but has {clues}",
+                size.max_clues()
+            )
+            .into());
+        }
+
+        // the values of two lines without givens can always be swapped when they share a band,
+        // or a stack, so no board of the shape would have a unique solution
+        let side = size.side();
+        let empty_rows = (0..side)
+            .map(|x| (0..side).all(|y| !mask.contains(x, y)))
+            .collect::<Vec<bool>>();
+        let empty_columns = (0..side)
+            .map(|y| (0..side).all(|x| !mask.contains(x, y)))
+            .collect::<Vec<bool>>();
+
+        for a in 0..side {
+            for b in a + 1..side {
+                if empty_rows[a]
+                    && empty_rows[b]
+                    && a / size.get_box_rows() == b / size.get_box_rows()
+                {
+                    return Err(format!(
+                        "rows {} and {} share a band and have no givens, their values could always be swapped",
+                        a + 1,
+                        b + 1
+                    )
+                    .into());
+                }
+
+                if empty_columns[a]
+                    && empty_columns[b]
+                    && a / size.get_box_cols() == b / size.get_box_cols()
+                {
+                    return Err(format!(
+                        "columns {} and {} share a stack and have no givens, their values could always be swapped",
+                        a + 1,
+                        b + 1
+                    )
+                    .into());
+                }
+            }
+        }
+
+        Ok(mask)
+    }
+
+    /// `x` for a given and `.` for an empty cell, in row order
+    pub fn to_str(&self) -> String {
+        self.cells
+            .iter()
+            .map(|c| match c {
+                true => 'x',
+                false => '.',
+            })
+            .collect()
+    }
+
+    pub fn number_of_clues(&self) -> u8 {
+        self.cells.iter().filter(|c| **c).count() as u8
+    }
+
+    /// whether the cell at (x, y) is a given
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        self.cells[x * BoardSize::CLASSIC.side() + y]
+    }
+}
+
+impl Sudoku {
+    /// generates classic boards whose givens are exactly the cells of the mask, the digits are
+    /// searched for on all the physical cores. Seeds, progress and what is written to disk work
+    /// as with `generate_random_boards`, and so do resuming, cancelling and progress reports
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_masked_boards(
+        mask: &ClueMask,
+        number_of_puzzles: usize,
+        just_print: bool,
+        false_positive_rate: f64,
+        seed: Option<u64>,
+        resume: bool,
+        cancel: &CancellationToken,
+        observer: &mut dyn ProgressObserver,
+    ) -> (Vec<Self>, usize, u64) {
+        Sudoku::generate_boards(
+            Layout::Mask(mask.clone()),
+            false,
+            number_of_puzzles,
+            num_cpus::get_physical(),
+            just_print,
+            false_positive_rate,
+            seed,
+            resume,
+            cancel,
+            observer,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::{DEFAULT_FALSE_POSITIVE_RATE, NoProgress};
+
+    const GIVENS: &str =
+        "..82...3...147..65.5..6.49.8.6..53.2.......7..2....8.6..9....43..431.....7.694..1";
+
+    /// mask with a given everywhere but the listed rows and columns
+    fn mask_without(rows: &[usize], columns: &[usize]) -> String {
+        (0..81)
+            .map(
+                |k| match rows.contains(&(k / 9)) || columns.contains(&(k % 9)) {
+                    true => '.',
+                    false => 'x',
+                },
+            )
+            .collect()
+    }
+
+    #[test]
+    fn reads_masks_and_thonky_strings() {
+        let mask = ClueMask::from_str(GIVENS).unwrap();
+
+        assert_eq!(mask.number_of_clues(), 32);
+        assert!(mask.contains(0, 2) && !mask.contains(0, 0));
+        assert_eq!(ClueMask::from_str(&mask.to_str()).unwrap(), mask);
+
+        let lines = mask
+            .to_str()
+            .as_bytes()
+            .chunks(9)
+            .map(|l| String::from_utf8_lossy(l).to_string())
+            .collect::<Vec<String>>()
+            .join("\n ");
+        assert_eq!(ClueMask::from_str(&lines).unwrap(), mask);
+    }
+
+    #[test]
+    fn turns_away_masks_no_board_fits() {
+        let too_few = format!("{}{}", "x".repeat(16), ".".repeat(65));
+
+        for inp in [
+            &GIVENS[1..],
+            &format!("{GIVENS}."),
+            &too_few,
+            &"x".repeat(81),
+            // rows 1 and 3 share the first band
+            &mask_without(&[0, 2], &[]),
+            // columns 5 and 6 share the second stack
+            &mask_without(&[], &[4, 5]),
+        ] {
+            assert!(ClueMask::from_str(inp).is_err(), "{inp}");
+        }
+
+        // empty lines in different bands and stacks can still be told apart
+        assert!(ClueMask::from_str(&mask_without(&[0, 3], &[2, 8])).is_ok());
+    }
+
+    #[test]
+    fn generated_boards_fit_the_mask() {
+        let mask = ClueMask::from_str(GIVENS).unwrap();
+        let (boards, _, _) = Sudoku::generate_masked_boards(
+            &mask,
+            1,
+            true,
+            DEFAULT_FALSE_POSITIVE_RATE,
+            Some(9),
+            false,
+            &CancellationToken::new(),
+            &mut NoProgress,
+        );
+
+        assert_eq!(boards.len(), 1);
+        assert_eq!(
+            ClueMask::from_str(&boards[0].to_thonky_str()).unwrap(),
+            mask
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::error::Error;
+
+use super::{Position, Sudoku, UpdateMapsType};
+
+impl Sudoku {
+    /// whether the givens alone lead to exactly one solution
+    fn has_unique_solution(&self) -> bool {
+        let mut puzzle = self.clone();
+        puzzle.hard_reset();
+
+        let mut solutions = 0;
+        let mut budget = usize::MAX;
+        puzzle.search_solutions(2, &mut solutions, &mut budget);
+
+        solutions == 1
+    }
+
+    /// takes the clue at `pos` off the board, it is a regular empty cell afterwards
+    fn remove_clue(&mut self, pos: &Position) {
+        if let Some(v) = self.grid[pos.x][pos.y].0 {
+            self.update_maps(pos, v, UpdateMapsType::Remove)
+                .expect("removal doesn't trigger error");
+        }
+
+        self.grid[pos.x][pos.y].0 = None;
+        self.prefilled_positions.remove(pos);
+    }
+
+    /// givens in row order
+    fn clue_positions(&self) -> Vec<Position> {
+        let mut resp = self
+            .prefilled_positions
+            .keys()
+            .cloned()
+            .collect::<Vec<Position>>();
+        resp.sort_by_key(|p| (p.x, p.y));
+        resp
+    }
+
+    /// the puzzle has a unique solution and loses it as soon as any single clue is removed
+    pub fn is_minimal(&self) -> bool {
+        if !self.has_unique_solution() {
+            return false;
+        }
+
+        let mut puzzle = self.clone();
+        puzzle.hard_reset();
+
+        self.clue_positions().iter().all(|pos| {
+            let mut without = puzzle.clone();
+            without.remove_clue(pos);
+            !without.has_unique_solution()
+        })
+    }
+
+    /// the givens without the redundant ones, a clue is dropped whenever the rest still lead to
+    /// the same single solution. Values entered on the board are cleared. Fails when the puzzle
+    /// doesn't have a unique solution to begin with
+    pub fn minimize(&self) -> Result<Self, Box<dyn Error>> {
+        if !self.has_unique_solution() {
+            return Err("only puzzles with a unique solution can be minimised".into());
+        }
+
+        let mut puzzle = self.clone();
+        puzzle.hard_reset();
+
+        // dropping clues only ever adds solutions, so a clue that is needed once stays needed
+        // and a single pass is enough
+        for pos in self.clue_positions() {
+            let mut without = puzzle.clone();
+            without.remove_clue(&pos);
+
+            if without.has_unique_solution() {
+                puzzle = without;
+            }
+        }
+
+        Ok(puzzle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::{BoardSize, Variant};
+
+    const GIVENS: &str =
+        "..82...3...147..65.5..6.49.8.6..53.2.......7..2....8.6..9....43..431.....7.694..1";
+
+    /// the solution of the board as a thonky string
+    fn solution(board: &Sudoku) -> String {
+        let mut solved = board.clone();
+        assert!(solved.solve());
+        solved.to_thonky_str()
+    }
+
+    #[test]
+    fn minimize_keeps_the_solution_and_drops_every_redundant_clue() {
+        // every cell of the solution but the first one
+        let solved = solution(&Sudoku::from_str(GIVENS).unwrap());
+        let full = Sudoku::from_str(&format!(".{}", &solved[1..])).unwrap();
+
+        assert!(!full.is_minimal());
+
+        let minimal = full.minimize().unwrap();
+
+        assert!(minimal.is_minimal());
+        assert!(minimal.number_of_initial_clues() < 32);
+        assert_eq!(solution(&minimal), solved);
+
+        // minimising again has nothing left to drop
+        assert_eq!(
+            minimal.minimize().unwrap().to_thonky_str(),
+            minimal.to_thonky_str()
+        );
+    }
+
+    #[test]
+    fn boards_without_a_unique_solution_arent_minimal() {
+        let empty = Sudoku::empty(BoardSize::CLASSIC, Variant::Classic);
+
+        assert!(!empty.is_minimal());
+        assert!(empty.minimize().is_err());
+    }
+}
@@ -0,0 +1,194 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// time between two reports while nothing is found
+pub(super) const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// told how a batch run is going, on the thread that started the run. Closures taking a
+/// `&Progress` are observers too, ex: `&mut |p: &Progress| println!("{}", p.get_found())`
+pub trait ProgressObserver {
+    fn on_progress(&mut self, progress: &Progress);
+}
+
+impl<F: FnMut(&Progress)> ProgressObserver for F {
+    fn on_progress(&mut self, progress: &Progress) {
+        self(progress)
+    }
+}
+
+/// observer for runs nobody watches
+pub struct NoProgress;
+
+impl ProgressObserver for NoProgress {
+    fn on_progress(&mut self, _: &Progress) {}
+}
+
+/// boards a worker thread went through, only the ones it actually checked count. Boards skipped
+/// because the run had already tried the same one don't
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThreadStats {
+    searched: usize,
+    found: usize,
+    invalid: usize,
+}
+
+impl ThreadStats {
+    pub fn get_searched(&self) -> usize {
+        self.searched
+    }
+
+    pub fn get_found(&self) -> usize {
+        self.found
+    }
+
+    /// boards without a unique solution
+    pub fn get_invalid(&self) -> usize {
+        self.invalid
+    }
+}
+
+/// state of a batch run at the time of the report
+#[derive(Debug, Clone)]
+pub struct Progress {
+    found: usize,
+    resumed: usize,
+    target: usize,
+    elapsed: Duration,
+    threads: Vec<ThreadStats>,
+}
+
+impl Progress {
+    /// boards of the run found so far, the resumed ones included
+    pub fn get_found(&self) -> usize {
+        self.found
+    }
+
+    /// boards an earlier run had already stored
+    pub fn get_resumed(&self) -> usize {
+        self.resumed
+    }
+
+    pub fn get_target(&self) -> usize {
+        self.target
+    }
+
+    pub fn get_elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// stats of every worker thread, in the order they were started
+    pub fn get_threads(&self) -> &[ThreadStats] {
+        &self.threads
+    }
+
+    pub fn get_searched(&self) -> usize {
+        self.threads.iter().map(|t| t.searched).sum()
+    }
+
+    pub fn get_invalid(&self) -> usize {
+        self.threads.iter().map(|t| t.invalid).sum()
+    }
+
+    /// share of the checked boards without a unique solution, 0 before any is checked
+    pub fn invalid_rate(&self) -> f64 {
+        match self.get_searched() {
+            0 => 0.0,
+            s => self.get_invalid() as f64 / s as f64,
+        }
+    }
+
+    /// boards checked per second
+    pub fn searched_per_sec(&self) -> f64 {
+        self.per_sec(self.get_searched())
+    }
+
+    /// boards found per second by this run, the resumed ones don't count
+    pub fn puzzles_per_sec(&self) -> f64 {
+        self.per_sec(self.found - self.resumed)
+    }
+
+    /// time left at the current rate, none until this run finds its first board
+    pub fn eta(&self) -> Option<Duration> {
+        let rate = self.puzzles_per_sec();
+
+        if rate <= 0.0 {
+            return None;
+        }
+
+        Some(Duration::from_secs_f64(
+            self.target.saturating_sub(self.found) as f64 / rate,
+        ))
+    }
+
+    fn per_sec(&self, n: usize) -> f64 {
+        if self.elapsed.is_zero() {
+            return 0.0;
+        }
+
+        n as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// counters a worker bumps while the thread running the batch reads them
+#[derive(Debug, Default)]
+pub(super) struct ThreadCounters {
+    searched: AtomicUsize,
+    found: AtomicUsize,
+    invalid: AtomicUsize,
+}
+
+impl ThreadCounters {
+    pub(super) fn add_searched(&self) {
+        self.searched.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn add_found(&self) {
+        self.found.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn add_invalid(&self) {
+        self.invalid.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> ThreadStats {
+        ThreadStats {
+            searched: self.searched.load(Ordering::Relaxed),
+            found: self.found.load(Ordering::Relaxed),
+            invalid: self.invalid.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// builds the reports of a batch run out of the counters of its threads
+pub(super) struct ProgressTracker {
+    start: Instant,
+    resumed: usize,
+    target: usize,
+    threads: Vec<Arc<ThreadCounters>>,
+}
+
+impl ProgressTracker {
+    pub(super) fn new(resumed: usize, target: usize, threads: Vec<Arc<ThreadCounters>>) -> Self {
+        ProgressTracker {
+            start: Instant::now(),
+            resumed,
+            target,
+            threads,
+        }
+    }
+
+    pub(super) fn report(&self, found: usize, observer: &mut dyn ProgressObserver) {
+        observer.on_progress(&Progress {
+            found,
+            resumed: self.resumed,
+            target: self.target,
+            elapsed: self.start.elapsed(),
+            threads: self.threads.iter().map(|t| t.stats()).collect(),
+        });
+    }
+}
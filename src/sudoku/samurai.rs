@@ -0,0 +1,620 @@
+use std::{error::Error, fmt::Display};
+
+use colored::Colorize;
+use rand::Rng;
+
+use super::{
+    Board, BoardSize, Cell, CellState, HintStatus, InsertStatus, Position, Sudoku, Variant,
+    coordinate_to_char, digit_to_char, seeded_rng,
+};
+
+/// top left corner of every grid, in reading order so the centre grid is the third one
+const GRID_OFFSETS: [(usize, usize); 5] = [(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)];
+
+/// cells per row of the whole samurai board
+const SIDE: usize = 21;
+
+/// boxes per row of the whole samurai board, some of them aren't part of any grid
+const BOXES: usize = 7;
+
+/// cells the uniqueness check may visit before a removal is given up on
+const UNIQUENESS_BUDGET: usize = 200_000;
+
+/// five classic grids overlapping at the corner boxes, the centre grid shares one box with each
+/// of the others. Shared cells are kept in sync across the grids they belong to
+#[derive(Debug, Clone)]
+pub struct Samurai {
+    grids: Vec<Sudoku>,
+    /// every cell of the whole board with its position in the grids holding it, shared cells
+    /// only once
+    cells: Vec<Vec<(usize, Position)>>,
+}
+
+impl Display for Samurai {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cell = |g: usize, i: usize, j: usize| match self.grids[g].grid[i][j].0 {
+            Some(v) => format!(" {} ", digit_to_char(v).to_string().bold()),
+            None => "   ".to_string(),
+        };
+
+        for line in self.draw(&cell) {
+            writeln!(f, "{line}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Samurai {
+    pub fn empty() -> Self {
+        Samurai {
+            grids: GRID_OFFSETS
+                .iter()
+                .map(|_| Sudoku::empty(BoardSize::CLASSIC, Variant::Classic))
+                .collect(),
+            cells: (0..SIDE)
+                .flat_map(|x| (0..SIDE).map(move |y| Samurai::grids_at(x, y)))
+                .filter(|grids| !grids.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn get_grids(&self) -> &[Sudoku] {
+        &self.grids
+    }
+
+    /// grids the cell of the whole board belongs to, along with its position in them
+    fn grids_at(x: usize, y: usize) -> Vec<(usize, Position)> {
+        let side = BoardSize::CLASSIC.side();
+
+        GRID_OFFSETS
+            .iter()
+            .enumerate()
+            .filter(|(_, (ox, oy))| (*ox..ox + side).contains(&x) && (*oy..oy + side).contains(&y))
+            .map(|(g, (ox, oy))| (g, Position::new(x - ox, y - oy)))
+            .collect()
+    }
+
+    /// index into `cells` of a cell of a grid
+    fn cell_index(&self, grid: usize, pos: &Position) -> usize {
+        self.cells
+            .iter()
+            .position(|c| c.iter().any(|(g, p)| *g == grid && p == pos))
+            .expect("every cell of a grid is on the board")
+    }
+
+    fn cell_at(&self, k: usize) -> Cell {
+        let (g, pos) = &self.cells[k][0];
+        self.grids[*g].grid[pos.x][pos.y]
+    }
+
+    /// values that fit the cell in every grid it belongs to, as a bitmap
+    fn candidates_at(&self, k: usize) -> u32 {
+        self.cells[k].iter().fold(u32::MAX, |acc, (g, pos)| {
+            acc & self.grids[*g].candidates_at(pos.x, pos.y)
+        })
+    }
+
+    /// sets the cell in every grid it belongs to, nothing changes when one of them rejects it
+    fn insert(
+        &mut self,
+        k: usize,
+        val: Option<u8>,
+        cell_state: CellState,
+    ) -> Result<(), Box<dyn Error>> {
+        let before = self.cell_at(k);
+        let grids = self.cells[k].clone();
+
+        for (i, (g, pos)) in grids.iter().enumerate() {
+            if let Err(e) = self.grids[*g].insert(pos, val, cell_state) {
+                for (g, pos) in &grids[..i] {
+                    self.grids[*g]
+                        .insert(pos, before.0, before.1)
+                        .expect("restoring the previous value");
+                }
+
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn insert_at(&mut self, grid: usize, pos: &Position, val: Option<u8>) -> InsertStatus {
+        let k = self.cell_index(grid, pos);
+        let mut cell_state = CellState::Normal;
+        let mut resp = InsertStatus::Right;
+
+        if val.is_some() {
+            if self.cell_at(k).0.is_some() {
+                return InsertStatus::ValuePresent;
+            }
+
+            if self.grids[grid].solved_grid[pos.x][pos.y].0 != val {
+                cell_state = CellState::Wrong;
+                resp = InsertStatus::Wrong;
+            }
+        }
+
+        if self.insert(k, val, cell_state).is_err() {
+            return InsertStatus::Wrong;
+        }
+
+        resp
+    }
+
+    pub fn hint(&mut self, grid: usize, pos: &Position) -> HintStatus {
+        let k = self.cell_index(grid, pos);
+
+        if self.cell_at(k).0.is_some() {
+            return HintStatus::ValuePresent;
+        }
+
+        let val = self.grids[grid].solved_grid[pos.x][pos.y].0;
+
+        if self.insert(k, val, CellState::Hinted).is_err() {
+            return HintStatus::Conflict;
+        }
+
+        HintStatus::Ok
+    }
+
+    /// fills every empty or wrong cell with its value from the solution
+    pub fn reveal_solution(&mut self) {
+        let solution = |board: &Samurai, k: usize| {
+            let (g, pos) = &board.cells[k][0];
+            board.grids[*g].solved_grid[pos.x][pos.y].0
+        };
+
+        // wrong values are cleared first so they can't collide with the values being revealed
+        let differing = (0..self.cells.len())
+            .filter(|&k| self.cell_at(k).0 != solution(self, k))
+            .collect::<Vec<usize>>();
+
+        for &k in &differing {
+            self.insert(k, None, CellState::Normal)
+                .expect("this is removal");
+        }
+
+        for k in differing {
+            self.insert(k, solution(self, k), CellState::Hinted)
+                .expect("solution fits the board");
+        }
+    }
+
+    pub fn is_board_solved_completely(&self) -> bool {
+        self.grids.iter().all(|g| g.is_board_solved_completely())
+    }
+
+    /// clues of the whole board, shared cells count once
+    pub fn number_of_initial_clues(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|c| self.grids[c[0].0].prefilled_positions.contains_key(&c[0].1))
+            .count()
+    }
+
+    pub fn reset(&mut self) {
+        for g in &mut self.grids {
+            g.reset();
+        }
+    }
+
+    /// solves every grid at once, returns false (leaving the board untouched) when there is no
+    /// solution or more than one
+    pub fn solve(&mut self) -> bool {
+        self.solve_within(usize::MAX).unwrap_or(false)
+    }
+
+    /// same as `solve` but gives up after visiting `budget` cells, None when it gave up
+    fn solve_within(&mut self, budget: usize) -> Option<bool> {
+        let mut solutions = 0;
+        let mut budget = budget;
+        self.search_solutions(2, &mut solutions, &mut budget);
+
+        if budget == 0 {
+            return None;
+        }
+
+        if solutions != 1 {
+            return Some(false);
+        }
+
+        for k in 0..self.cells.len() {
+            if self.cell_at(k).0.is_none() {
+                let (g, pos) = &self.cells[k][0];
+                let val = self.grids[*g].solved_grid[pos.x][pos.y].0;
+
+                self.insert(k, val, CellState::Normal)
+                    .expect("solution fits the board");
+            }
+        }
+
+        Some(self.is_board_solved_completely())
+    }
+
+    /// backtracking over the cell with the fewest candidates across all grids, the first
+    /// solution found is kept in the `solved_grid` of every grid
+    fn search_solutions(&mut self, limit: usize, solutions: &mut usize, budget: &mut usize) {
+        if *budget == 0 {
+            return;
+        }
+
+        *budget -= 1;
+
+        let mut next: Option<(usize, u32)> = None;
+
+        for k in 0..self.cells.len() {
+            if self.cell_at(k).0.is_some() {
+                continue;
+            }
+
+            let candidates = self.candidates_at(k);
+
+            // dead end, some cell can't take any value
+            if candidates == 0 {
+                return;
+            }
+
+            if next.is_none_or(|n| candidates.count_ones() < n.1.count_ones()) {
+                next = Some((k, candidates));
+
+                if candidates.count_ones() == 1 {
+                    break;
+                }
+            }
+        }
+
+        let (k, mut candidates) = match next {
+            Some(n) => n,
+            None => {
+                *solutions += 1;
+
+                if *solutions == 1 {
+                    for g in &mut self.grids {
+                        g.solved_grid = g.grid.clone();
+                    }
+                }
+
+                return;
+            }
+        };
+
+        while candidates != 0 && *solutions < limit {
+            let v = candidates.trailing_zeros() as u8;
+            candidates &= candidates - 1;
+
+            self.insert(k, Some(v), CellState::Normal)
+                .expect("candidate fits the cell");
+            self.search_solutions(limit, solutions, budget);
+        }
+
+        self.insert(k, None, CellState::Normal)
+            .expect("this is removal");
+    }
+
+    /// fills every empty cell with random values that respect the rules of every grid
+    fn fill_randomly<R: Rng>(&mut self, rng: &mut R) -> bool {
+        let next = (0..self.cells.len())
+            .filter(|&k| self.cell_at(k).0.is_none())
+            .map(|k| (k, self.candidates_at(k)))
+            .min_by_key(|n| n.1.count_ones());
+
+        let (k, candidates) = match next {
+            Some(n) => n,
+            None => return true,
+        };
+
+        let mut values = (1..=BoardSize::CLASSIC.side() as u8)
+            .filter(|v| candidates & (1 << v) != 0)
+            .collect::<Vec<u8>>();
+
+        seeded_rng::shuffle(rng, &mut values);
+
+        for v in values {
+            self.insert(k, Some(v), CellState::Normal)
+                .expect("candidate fits the cell");
+
+            if self.fill_randomly(rng) {
+                return true;
+            }
+        }
+
+        self.insert(k, None, CellState::Normal)
+            .expect("this is removal");
+
+        false
+    }
+
+    /// random samurai board with a unique solution. Clues are removed one at a time for as long
+    /// as the solution stays unique, so the board can end up with more clues than asked for
+    pub fn generate<R: Rng>(
+        number_of_clues: usize,
+        mut callback: Option<&mut dyn FnMut(usize)>,
+        rng: &mut R,
+    ) -> Self {
+        let mut board = Samurai::empty();
+
+        while !board.fill_randomly(rng) {
+            board = Samurai::empty();
+        }
+
+        let mut order = (0..board.cells.len()).collect::<Vec<usize>>();
+        let mut clues = order.len();
+        seeded_rng::shuffle(rng, &mut order);
+
+        for (counter, k) in order.into_iter().enumerate() {
+            if clues <= number_of_clues {
+                break;
+            }
+
+            let before = board.cell_at(k);
+            board
+                .insert(k, None, CellState::Normal)
+                .expect("this is removal");
+
+            // the solution has to stay unique, the removed value is put back otherwise
+            if board.clone().solve_within(UNIQUENESS_BUDGET) == Some(true) {
+                clues -= 1;
+            } else {
+                board
+                    .insert(k, before.0, before.1)
+                    .expect("value was there before");
+            }
+
+            if let Some(cb) = callback.as_mut() {
+                cb(counter + 1);
+            }
+        }
+
+        Samurai::from_grids(board.grids.iter().map(|g| g.grid.clone()).collect())
+            .expect("board has a unique solution")
+    }
+
+    /// board made of the cells of every grid, the cells shared by two grids have to agree
+    fn from_grids(grids: Vec<Board>) -> Result<Self, Box<dyn Error>> {
+        if grids.len() != GRID_OFFSETS.len() {
+            return Err(format!(
+                "expected {} grids but found {}",
+                GRID_OFFSETS.len(),
+                grids.len()
+            )
+            .into());
+        }
+
+        let mut board = Samurai::empty();
+
+        for shared in &board.cells {
+            let (g, pos) = &shared[0];
+
+            if shared
+                .iter()
+                .any(|(o, p)| grids[*o][p.x][p.y] != grids[*g][pos.x][pos.y])
+            {
+                return Err(format!(
+                    "cell at {} of grid {} differs between the grids sharing it",
+                    pos,
+                    g + 1
+                )
+                .into());
+            }
+        }
+
+        for (sudoku, grid) in board.grids.iter_mut().zip(grids) {
+            sudoku.fill_grid(grid)?;
+        }
+
+        if board.solve() {
+            board.reset();
+            return Ok(board);
+        }
+
+        Err("invalid samurai board given".into())
+    }
+
+    /// the grids in reading order, each written like a classic board and separated by a `/`.
+    /// Prefixed with `samurai:` so it isn't mistaken for a single board
+    pub fn to_str(&self) -> String {
+        format!(
+            "samurai:{}",
+            self.grids
+                .iter()
+                .map(|g| g.to_str())
+                .collect::<Vec<String>>()
+                .join("/")
+        )
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(inp: &str) -> Result<Self, Box<dyn Error>> {
+        let cells = match inp.trim().split_once(':') {
+            Some((tag, cells)) if tag.trim() == "samurai" => cells,
+            _ => return Err("expected the samurai board to start with `samurai:`".into()),
+        };
+
+        let mut grids = vec![];
+
+        for grid in cells.split('/') {
+            match Sudoku::grid_from_str(grid.trim())? {
+                (size, grid) if size == BoardSize::CLASSIC => grids.push(grid),
+                (size, _) => {
+                    return Err(format!("samurai grids have to be 9x9, found {size}").into());
+                }
+            }
+        }
+
+        Samurai::from_grids(grids)
+    }
+
+    /// lines of the whole board. Rows are labelled on the left and right with the row in the
+    /// outermost grid holding them, columns above and below the same way. `cell` renders a cell
+    /// of a grid 3 chars wide
+    pub fn draw(&self, cell: &dyn Fn(usize, usize, usize) -> String) -> Vec<String> {
+        let box_side = BoardSize::CLASSIC.get_box_rows();
+        let has_box = |bx: usize, by: usize| -> bool {
+            bx < BOXES && by < BOXES && !Samurai::grids_at(bx * box_side, by * box_side).is_empty()
+        };
+
+        // the cells on the edge of the board only belong to a single grid
+        let label = |x: Option<usize>, y: Option<usize>, by_row: bool| -> char {
+            match x.zip(y).map(|(x, y)| Samurai::grids_at(x, y)) {
+                Some(grids) if by_row => coordinate_to_char(grids[0].1.x),
+                Some(grids) => coordinate_to_char(grids[0].1.y),
+                None => ' ',
+            }
+        };
+
+        let holds = |x: usize, y: usize| !Samurai::grids_at(x, y).is_empty();
+
+        let header = |last: bool| -> String {
+            let mut line = String::from("  ");
+
+            for y in 0..SIDE {
+                if y % box_side == 0 {
+                    line.push(' ');
+                }
+
+                let x = match last {
+                    true => (0..SIDE).rev().find(|&x| holds(x, y)),
+                    false => (0..SIDE).find(|&x| holds(x, y)),
+                };
+
+                line.push_str(&format!(" {} ", label(x, Some(y), false)));
+            }
+
+            format!("{}", line.trim_end().italic())
+        };
+
+        let separator = |bx: usize| -> String {
+            let mut line = String::from("  ");
+            let edge = |by: usize| (bx > 0 && has_box(bx - 1, by)) || has_box(bx, by);
+
+            for by in 0..=BOXES {
+                let joint = (by > 0 && edge(by - 1)) || edge(by);
+                line.push(if joint { '+' } else { ' ' });
+
+                if by < BOXES {
+                    line.push_str(&match edge(by) {
+                        true => "-".repeat(box_side * 3),
+                        false => " ".repeat(box_side * 3),
+                    });
+                }
+            }
+
+            format!("{}", line.trim_end().blue())
+        };
+
+        let mut lines = vec![header(false)];
+
+        for x in 0..SIDE {
+            let bx = x / box_side;
+
+            if x % box_side == 0 {
+                lines.push(separator(bx));
+            }
+
+            let first = (0..SIDE).find(|&y| holds(x, y));
+            let mut line = format!("{} ", label(Some(x), first, true).to_string().italic());
+
+            for y in 0..SIDE {
+                let by = y / box_side;
+
+                if y % box_side == 0 {
+                    match (by > 0 && has_box(bx, by - 1)) || has_box(bx, by) {
+                        true => line.push_str(&format!("{}", "|".blue())),
+                        false => line.push(' '),
+                    }
+                }
+
+                match Samurai::grids_at(x, y).first() {
+                    Some((g, pos)) => line.push_str(&cell(*g, pos.x, pos.y)),
+                    None => line.push_str("   "),
+                }
+            }
+
+            if has_box(bx, BOXES - 1) {
+                line.push_str(&format!("{}", "|".blue()));
+            }
+
+            let last = (0..SIDE).rev().find(|&y| holds(x, y));
+            line.push_str(&format!(
+                " {}",
+                label(Some(x), last, true).to_string().italic()
+            ));
+
+            lines.push(line);
+        }
+
+        lines.push(separator(BOXES));
+        lines.push(header(true));
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::SeededRng;
+
+    /// cells shared by more than one grid
+    fn shared(board: &Samurai) -> Vec<Vec<(usize, Position)>> {
+        board
+            .cells
+            .iter()
+            .filter(|c| c.len() > 1)
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn shared_cells_agree_across_grids() {
+        let mut board = Samurai::generate(300, None, &mut SeededRng::new(5));
+
+        assert_eq!(shared(&board).len(), 4 * 9);
+
+        let agree = |board: &Samurai| {
+            shared(board).iter().all(|c| {
+                c.iter().all(|(g, p)| {
+                    board.grids[*g].grid[p.x][p.y] == board.grids[c[0].0].grid[c[0].1.x][c[0].1.y]
+                })
+            })
+        };
+        assert!(agree(&board));
+
+        // a value entered through one grid shows up in the other one too
+        let (g, pos) = shared(&board)
+            .into_iter()
+            .find(|c| board.grids[c[0].0].grid[c[0].1.x][c[0].1.y].0.is_none())
+            .map(|c| c[1].clone())
+            .unwrap();
+        let val = board.grids[g].solved_grid[pos.x][pos.y].0;
+
+        assert!(matches!(board.insert_at(g, &pos, val), InsertStatus::Right));
+        assert!(agree(&board));
+
+        let copy = Samurai::from_str(&board.to_str()).unwrap();
+        assert_eq!(copy.to_str(), board.to_str());
+    }
+
+    #[test]
+    fn from_grids_turns_away_grids_that_disagree() {
+        let board = Samurai::generate(300, None, &mut SeededRng::new(5));
+        let grids = board
+            .grids
+            .iter()
+            .map(|g| g.grid.clone())
+            .collect::<Vec<Board>>();
+
+        assert!(Samurai::from_grids(grids[..4].to_vec()).is_err());
+        assert!(Samurai::from_grids(grids.clone()).is_ok());
+
+        // the top left corner of the centre grid is the bottom right box of the first one
+        let mut changed = grids.clone();
+        let (x, y) = (6, 6);
+        let other = (1..=9).find(|v| Some(*v) != grids[0][x][y].0).unwrap();
+        changed[0][x][y] = (Some(other), CellState::Normal);
+
+        assert!(Samurai::from_grids(changed).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use std::convert::Infallible;
+
+use rand::{Rng, TryRng};
+
+/// SplitMix64, used for everything that has to be reproduced from a seed (daily puzzles, batch
+/// runs). rand documents neither `StdRng` nor its samplers as stable across releases or
+/// platforms, so the generator and the helpers below turning its output into indices and
+/// shuffles are kept here, where a `cargo update` can't change them
+#[derive(Debug, Clone)]
+pub(super) struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub(super) fn new(seed: u64) -> Self {
+        SeededRng { state: seed }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl TryRng for SeededRng {
+    type Error = Infallible;
+
+    fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+        Ok((self.next() >> 32) as u32)
+    }
+
+    fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+        Ok(self.next())
+    }
+
+    fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Self::Error> {
+        for chunk in dst.chunks_mut(8) {
+            let bytes = self.next().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+
+        Ok(())
+    }
+}
+
+/// uniform index in `0..n`, values past the last whole multiple of `n` are drawn again so the
+/// small indices aren't favoured
+pub(super) fn below<R: Rng + ?Sized>(rng: &mut R, n: usize) -> usize {
+    assert!(n > 0, "no index below 0");
+
+    let n = n as u64;
+    let limit = u64::MAX - u64::MAX % n;
+
+    loop {
+        let v = rng.next_u64();
+
+        if v < limit {
+            return (v % n) as usize;
+        }
+    }
+}
+
+/// heads or tails
+pub(super) fn coin<R: Rng + ?Sized>(rng: &mut R) -> bool {
+    rng.next_u64() >> 63 == 1
+}
+
+/// Fisher-Yates, from the last element down
+pub(super) fn shuffle<R: Rng + ?Sized, T>(rng: &mut R, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, below(rng, i + 1));
+    }
+}
+
+/// random element, none when there are none
+pub(super) fn choose<'a, R: Rng + ?Sized, T>(rng: &mut R, items: &'a [T]) -> Option<&'a T> {
+    match items.len() {
+        0 => None,
+        n => Some(&items[below(rng, n)]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::{
+        CancellationToken, DEFAULT_FALSE_POSITIVE_RATE, Layout, NoProgress, Sudoku, Symmetry,
+    };
+    use std::collections::HashSet;
+
+    #[test]
+    fn matches_the_splitmix64_reference_stream() {
+        let mut rng = SeededRng::new(0);
+
+        assert_eq!(rng.next_u64(), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(rng.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+        assert_eq!(rng.next_u64(), 0x06c4_5d18_8009_454f);
+    }
+
+    #[test]
+    fn below_stays_in_range() {
+        let mut rng = SeededRng::new(7);
+
+        for n in 1..50 {
+            assert!((0..100).all(|_| below(&mut rng, n) < n));
+        }
+    }
+
+    #[test]
+    fn shuffle_keeps_every_item() {
+        let mut rng = SeededRng::new(7);
+        let mut items = (0..20).collect::<Vec<usize>>();
+
+        shuffle(&mut rng, &mut items);
+        assert_ne!(items, (0..20).collect::<Vec<usize>>());
+
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn daily_puzzle_stays_the_same() {
+        let board = Sudoku::generate_seeded_board(32, "2026-10-18", |_| {}).unwrap();
+
+        assert_eq!(
+            board.to_thonky_str(),
+            "..82...3...147..65.5..6.49.8.6..53.2.......7..2....8.6..9....43..431.....7.694..1"
+        );
+    }
+
+    #[test]
+    fn batch_run_stays_the_same() {
+        let (boards, _, seed) = Sudoku::generate_random_boards(
+            36,
+            Symmetry::None,
+            false,
+            2,
+            true,
+            DEFAULT_FALSE_POSITIVE_RATE,
+            Some(42),
+            false,
+            &CancellationToken::new(),
+            &mut NoProgress,
+        );
+
+        assert_eq!(seed, 42);
+        assert_eq!(
+            boards
+                .iter()
+                .map(|b| b.to_thonky_str())
+                .collect::<Vec<String>>(),
+            [
+                ".6..17..4241.38.5.9....41823...465917...21.......8....6.7..28....8.6.7.9....7.4.5",
+                "3.9....7..6..7..1.........8.37.94......6.723482..3..6.283...495.765.812..5..296..",
+            ]
+        );
+    }
+
+    #[test]
+    fn batch_run_ignores_the_thread_timing() {
+        let run = |num_threads| {
+            Sudoku::generate_boards(
+                Layout::Random(30, Symmetry::None),
+                false,
+                6,
+                num_threads,
+                true,
+                DEFAULT_FALSE_POSITIVE_RATE,
+                Some(7),
+                false,
+                &CancellationToken::new(),
+                &mut NoProgress,
+            )
+            .0
+        };
+        let thonky = |boards: &[Sudoku]| {
+            boards
+                .iter()
+                .map(|b| b.to_thonky_str())
+                .collect::<Vec<String>>()
+        };
+
+        let boards = run(4);
+
+        assert_eq!(boards.len(), 6);
+        assert_eq!(thonky(&run(4)), thonky(&boards));
+        assert_eq!(thonky(&run(1)), thonky(&boards));
+
+        let distinct = boards
+            .iter()
+            .map(|b| b.canonical_pack())
+            .collect::<HashSet<_>>();
+
+        assert_eq!(distinct.len(), 6);
+    }
+}
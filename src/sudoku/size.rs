@@ -0,0 +1,187 @@
+use std::fmt::Display;
+
+/// dimensions of a board, a `side` x `side` grid split into boxes of `box_rows` x `box_cols`
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub struct BoardSize {
+    box_rows: usize,
+    box_cols: usize,
+}
+
+impl Default for BoardSize {
+    fn default() -> Self {
+        BoardSize::CLASSIC
+    }
+}
+
+impl Display for BoardSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{} ({}x{} boxes)",
+            self.side(),
+            self.side(),
+            self.box_rows,
+            self.box_cols
+        )
+    }
+}
+
+impl BoardSize {
+    pub const CLASSIC: BoardSize = BoardSize::new(3, 3);
+
+    pub const SUPPORTED: [BoardSize; 5] = [
+        BoardSize::CLASSIC,
+        BoardSize::new(2, 2),
+        BoardSize::new(2, 3),
+        BoardSize::new(3, 4),
+        BoardSize::new(4, 4),
+    ];
+
+    pub const fn new(box_rows: usize, box_cols: usize) -> Self {
+        BoardSize { box_rows, box_cols }
+    }
+
+    /// supported size with the given number of cells per row
+    pub fn from_side(side: usize) -> Option<Self> {
+        BoardSize::SUPPORTED.into_iter().find(|s| s.side() == side)
+    }
+
+    /// supported size with the given total number of cells
+    pub fn from_total_positions(total: usize) -> Option<Self> {
+        BoardSize::SUPPORTED
+            .into_iter()
+            .find(|s| s.total_positions() == total)
+    }
+
+    pub fn get_box_rows(&self) -> usize {
+        self.box_rows
+    }
+
+    pub fn get_box_cols(&self) -> usize {
+        self.box_cols
+    }
+
+    #[inline]
+    pub fn side(&self) -> usize {
+        self.box_rows * self.box_cols
+    }
+
+    #[inline]
+    pub fn total_positions(&self) -> usize {
+        self.side() * self.side()
+    }
+
+    /// boxes are laid out `box_rows` across and `box_cols` down
+    #[inline]
+    pub fn block_id(&self, row: usize, col: usize) -> usize {
+        (row / self.box_rows) * self.box_rows + (col / self.box_cols)
+    }
+
+    /// windows of a hyper board along one axis with `len` cells per window, they start one cell
+    /// in from the border and are one cell apart
+    fn windows_along(&self, len: usize) -> usize {
+        (self.side() - 1) / (len + 1)
+    }
+
+    pub fn window_count(&self) -> usize {
+        self.windows_along(self.box_rows) * self.windows_along(self.box_cols)
+    }
+
+    /// hyper window the cell lies in, windows are box sized and numbered row by row
+    #[inline]
+    pub fn window_id(&self, row: usize, col: usize) -> Option<usize> {
+        let along = |pos: usize, len: usize| {
+            let offset = pos.checked_sub(1)?;
+            let k = offset / (len + 1);
+
+            (offset % (len + 1) < len && k < self.windows_along(len)).then_some(k)
+        };
+
+        let wr = along(row, self.box_rows)?;
+        let wc = along(col, self.box_cols)?;
+
+        Some(wr * self.windows_along(self.box_cols) + wc)
+    }
+
+    /// bitmap with every value of the board set, values are stored at bit `v`
+    #[inline]
+    pub fn all_values(&self) -> u32 {
+        ((1u32 << (self.side() + 1)) - 1) & !1
+    }
+
+    pub fn min_clues(&self) -> u8 {
+        (self.side() + 1) as u8
+    }
+
+    pub fn max_clues(&self) -> u8 {
+        (self.total_positions() - 1).min(u8::MAX as usize) as u8
+    }
+
+    /// number of clues that generates quickly while leaving something to solve
+    pub fn default_clues(&self) -> u8 {
+        match self.side() {
+            4 => 8,
+            6 => 18,
+            9 => 40,
+            12 => 90,
+            _ => 160,
+        }
+    }
+
+    /// givens a killer board may get, bigger boards take too long to prove unique without any
+    pub fn default_killer_givens(&self) -> u8 {
+        match self.side() {
+            s if s <= 9 => 0,
+            s => (s * 2) as u8,
+        }
+    }
+
+    /// horizontal separator drawn between bands of boxes
+    pub fn separator(&self) -> String {
+        self.separator_for(self.box_cols)
+    }
+
+    /// separator for vertical lines drawn every `box_cols` columns
+    pub fn separator_for(&self, box_cols: usize) -> String {
+        format!(
+            "   {}",
+            "-".repeat(self.side() * 3 + self.side() / box_cols - 1)
+        )
+    }
+
+    /// column coordinates drawn above the board
+    pub fn column_header(&self) -> String {
+        self.column_header_for(self.box_cols)
+    }
+
+    /// column coordinates for vertical lines drawn every `box_cols` columns
+    pub fn column_header_for(&self, box_cols: usize) -> String {
+        let mut header = String::from("   ");
+
+        for j in 0..self.side() {
+            if j % box_cols == 0 {
+                header.push(' ');
+            }
+
+            header.push_str(&format!("{}  ", coordinate_to_char(j)));
+        }
+
+        header.trim_end().to_string() + " \n"
+    }
+}
+
+/// values above 9 are written as letters, A is 10 and G is 16
+pub fn digit_to_char(v: u8) -> char {
+    char::from_digit(v as u32, 36)
+        .unwrap_or('?')
+        .to_ascii_uppercase()
+}
+
+pub fn char_to_digit(c: char) -> Option<u8> {
+    c.to_digit(36).map(|v| v as u8)
+}
+
+/// coordinates above 9 are written as letters as well, A is 10 and F is 15
+pub fn coordinate_to_char(c: usize) -> char {
+    digit_to_char(c as u8)
+}
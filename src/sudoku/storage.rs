@@ -0,0 +1,161 @@
+use std::{
+    error::Error,
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// bytes read at a time while looking for the last newline
+const TAIL_CHUNK: u64 = 4096;
+
+/// lock over a puzzle file, released when dropped. It is taken on a `.lock` file next to the
+/// puzzle file, which is never truncated or removed along with the puzzle file
+pub(super) struct FileLock {
+    _file: File,
+}
+
+fn lock_file_name(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+fn create_parent_dir(path: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+
+    Ok(())
+}
+
+fn open_lock_file(path: &Path) -> Result<File, Box<dyn Error>> {
+    create_parent_dir(path)?;
+
+    Ok(OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_file_name(path))?)
+}
+
+/// waits until no other process reads or writes the file
+pub(super) fn lock(path: &Path) -> Result<FileLock, Box<dyn Error>> {
+    let file = open_lock_file(path)?;
+    file.lock()?;
+
+    Ok(FileLock { _file: file })
+}
+
+/// waits until no other process writes the file, readers share the lock
+pub(super) fn lock_shared(path: &Path) -> Result<FileLock, Box<dyn Error>> {
+    let file = open_lock_file(path)?;
+    file.lock_shared()?;
+
+    Ok(FileLock { _file: file })
+}
+
+/// opens the file to append to it, the caller holds its lock
+pub(super) fn open_for_append(path: &Path, _lock: &FileLock) -> Result<File, Box<dyn Error>> {
+    Ok(OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path)?)
+}
+
+/// writes the bytes at the end of the file and syncs them, every append is synced before its
+/// lock is released. A crash can only tear the write in flight, which is dropped when the file
+/// is next read or appended to
+pub(super) fn append_synced(file: &mut File, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+    file.write_all(bytes)?;
+    file.sync_data()?;
+
+    Ok(())
+}
+
+/// replaces the file with the bytes in one step. They are written and synced to a temp file
+/// next to it first, which is then renamed over it, so a crash leaves either the old file or
+/// the new one and never a part of each
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+    create_parent_dir(path)?;
+
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(".{}.tmp", std::process::id()));
+    let temp = PathBuf::from(temp);
+
+    let written = File::create(&temp).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    });
+
+    if let Err(e) = written.and_then(|_| fs::rename(&temp, path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(e.into());
+    }
+
+    // the rename itself is only durable once the directory is synced, not every platform lets
+    // a directory be opened for it
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty())
+        && let Ok(dir) = File::open(dir)
+    {
+        let _ = dir.sync_all();
+    }
+
+    Ok(())
+}
+
+/// cuts the file back to its first `len` bytes, dropping a torn write
+pub(super) fn truncate(file: &File, len: u64) -> Result<(), Box<dyn Error>> {
+    file.set_len(len)?;
+    file.sync_data()?;
+
+    Ok(())
+}
+
+/// cuts everything after the last newline, a line the writer didn't get to finish. Returns
+/// the number of bytes dropped
+pub(super) fn truncate_torn_line(file: &mut File) -> Result<u64, Box<dyn Error>> {
+    let len = file.metadata()?.len();
+    let mut end = len;
+    let mut buf = vec![0u8; TAIL_CHUNK as usize];
+
+    while end > 0 {
+        let start = end.saturating_sub(TAIL_CHUNK);
+        let chunk = &mut buf[..(end - start) as usize];
+
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+
+        if let Some(i) = chunk.iter().rposition(|b| *b == b'\n') {
+            end = start + i as u64 + 1;
+            break;
+        }
+
+        end = start;
+    }
+
+    if end < len {
+        truncate(file, end)?;
+    }
+
+    Ok(len - end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_atomic_replaces_the_file_and_leaves_no_temp_file() {
+        let dir = std::env::temp_dir().join(format!("sudoku_storage_{}", std::process::id()));
+        let path = dir.join("moves.txt");
+
+        write_atomic(&path, b"first\n").unwrap();
+        write_atomic(&path, b"second\n").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
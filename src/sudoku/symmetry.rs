@@ -0,0 +1,160 @@
+use std::fmt::Display;
+
+/// layout the givens of a generated board follow, clues are removed a whole orbit at a time
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, Default)]
+pub enum Symmetry {
+    /// givens are scattered at random
+    #[default]
+    None,
+    /// the layout looks the same turned upside down
+    Rotational180,
+    /// the layout looks the same after every quarter turn
+    Rotational90,
+    /// top half mirrors the bottom one
+    Horizontal,
+    /// left half mirrors the right one
+    Vertical,
+    /// mirrored along the main diagonal, top left to bottom right
+    Diagonal,
+    /// every rotation and mirror at once
+    Dihedral,
+}
+
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Symmetry::None => write!(f, "None (random scatter)"),
+            Symmetry::Rotational180 => write!(f, "180° rotational"),
+            Symmetry::Rotational90 => write!(f, "90° rotational"),
+            Symmetry::Horizontal => write!(f, "Horizontal mirror (top/bottom)"),
+            Symmetry::Vertical => write!(f, "Vertical mirror (left/right)"),
+            Symmetry::Diagonal => write!(f, "Diagonal mirror"),
+            Symmetry::Dihedral => write!(f, "Full dihedral (every rotation and mirror)"),
+        }
+    }
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 7] = [
+        Symmetry::None,
+        Symmetry::Rotational180,
+        Symmetry::Rotational90,
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::Diagonal,
+        Symmetry::Dihedral,
+    ];
+
+    /// cells the one at (x, y) is mapped onto, itself included, sorted and without duplicates.
+    /// They are given or removed together
+    pub fn orbit(&self, side: usize, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let n = side - 1;
+
+        let mut resp = match self {
+            Symmetry::None => vec![(x, y)],
+            Symmetry::Rotational180 => vec![(x, y), (n - x, n - y)],
+            Symmetry::Rotational90 => vec![(x, y), (y, n - x), (n - x, n - y), (n - y, x)],
+            Symmetry::Horizontal => vec![(x, y), (n - x, y)],
+            Symmetry::Vertical => vec![(x, y), (x, n - y)],
+            Symmetry::Diagonal => vec![(x, y), (y, x)],
+            Symmetry::Dihedral => vec![
+                (x, y),
+                (y, n - x),
+                (n - x, n - y),
+                (n - y, x),
+                (n - x, y),
+                (x, n - y),
+                (y, x),
+                (n - y, n - x),
+            ],
+        };
+
+        resp.sort_unstable();
+        resp.dedup();
+        resp
+    }
+    /// whether exactly `clues` givens can follow the layout, they come a whole orbit at a time
+    /// so some counts can't, ex: with 90° rotations a 9x9 board takes 4 givens at a time, plus
+    /// the center cell
+    pub fn reaches(&self, side: usize, clues: usize) -> bool {
+        let mut seen = vec![vec![false; side]; side];
+        let mut reachable = vec![false; side * side + 1];
+        reachable[0] = true;
+
+        for x in 0..side {
+            for y in 0..side {
+                if seen[x][y] {
+                    continue;
+                }
+
+                let orbit = self.orbit(side, x, y);
+
+                for (i, j) in &orbit {
+                    seen[*i][*j] = true;
+                }
+
+                for n in (orbit.len()..=side * side).rev() {
+                    reachable[n] |= reachable[n - orbit.len()];
+                }
+            }
+        }
+
+        reachable.get(clues).copied().unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::{BoardSize, Position, Sudoku, Variant};
+
+    #[test]
+    fn reaches_the_counts_the_orbits_add_up_to() {
+        assert!((0..=81).all(|n| Symmetry::None.reaches(9, n)));
+        assert!((0..=81).all(|n| Symmetry::Rotational180.reaches(9, n)));
+        assert!((0..=81).all(|n| Symmetry::Rotational90.reaches(9, n) == matches!(n % 4, 0 | 1)));
+        assert!((0..=36).all(|n| Symmetry::Rotational180.reaches(6, n) == (n % 2 == 0)));
+        assert!(!Symmetry::None.reaches(9, 82));
+    }
+
+    #[test]
+    fn boards_get_exactly_the_givens_asked_for() {
+        for (symmetry, clues) in [
+            (Symmetry::Rotational90, 29),
+            (Symmetry::Dihedral, 33),
+            (Symmetry::Horizontal, 32),
+        ] {
+            let board = Sudoku::generate_random_board(
+                BoardSize::CLASSIC,
+                Variant::Classic,
+                &[],
+                clues,
+                symmetry,
+                |_| {},
+            )
+            .unwrap();
+            let givens = board.get_prefilled_positions();
+
+            assert_eq!(givens.len(), clues as usize, "{symmetry}");
+            assert!(givens.keys().all(|p| {
+                symmetry
+                    .orbit(9, p.get_x(), p.get_y())
+                    .iter()
+                    .all(|(x, y)| givens.contains_key(&Position::new(*x, *y)))
+            }));
+        }
+
+        for (symmetry, clues) in [(Symmetry::Rotational90, 30), (Symmetry::Dihedral, 34)] {
+            let board = Sudoku::generate_random_board(
+                BoardSize::CLASSIC,
+                Variant::Classic,
+                &[],
+                clues,
+                symmetry,
+                |_| {},
+            );
+
+            assert!(board.is_none(), "{symmetry}");
+        }
+    }
+}
@@ -0,0 +1,445 @@
+use std::{error::Error, fmt::Display};
+
+use super::{BoardSize, CellState, Position, Sudoku, Variant, coordinate_to_char, digit_to_char};
+
+type Candidates = Vec<Vec<u32>>;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Technique {
+    FullHouse,
+    HiddenSingle,
+    NakedSingle,
+    LockedCandidates,
+    NakedPair,
+}
+
+impl Display for Technique {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Technique::FullHouse => write!(f, "full house"),
+            Technique::HiddenSingle => write!(f, "hidden single"),
+            Technique::NakedSingle => write!(f, "naked single"),
+            Technique::LockedCandidates => write!(f, "locked candidates"),
+            Technique::NakedPair => write!(f, "naked pair"),
+        }
+    }
+}
+
+/// difficulty of a puzzle, from the hardest technique a logical solve needs
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash)]
+pub enum Grade {
+    /// full houses and hidden singles only
+    Easy,
+    /// naked singles
+    Medium,
+    /// locked candidates or naked pairs
+    Hard,
+    /// beyond the known techniques
+    Expert,
+}
+
+impl Display for Grade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Grade::Easy => write!(f, "Easy"),
+            Grade::Medium => write!(f, "Medium"),
+            Grade::Hard => write!(f, "Hard"),
+            Grade::Expert => write!(f, "Expert"),
+        }
+    }
+}
+
+impl Grade {
+    pub const ALL: [Grade; 4] = [Grade::Easy, Grade::Medium, Grade::Hard, Grade::Expert];
+
+    pub fn parse(inp: &str) -> Result<Self, Box<dyn Error>> {
+        match Grade::ALL
+            .into_iter()
+            .find(|g| g.to_string().eq_ignore_ascii_case(inp.trim()))
+        {
+            Some(g) => Ok(g),
+            None => Err(format!("unknown grade found: {inp}").into()),
+        }
+    }
+
+    fn of(step: &LogicalStep) -> Self {
+        match (step.technique, step.assisted_by) {
+            (_, Some(_)) => Grade::Hard,
+            (Technique::NakedSingle, None) => Grade::Medium,
+            _ => Grade::Easy,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Region {
+    Row(usize),
+    Column(usize),
+    Block(usize),
+    /// 0 is the main diagonal, 1 the anti diagonal
+    Diagonal(usize),
+    Window(usize),
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Region::Row(i) => write!(f, "row {}", coordinate_to_char(*i)),
+            Region::Column(i) => write!(f, "column {}", coordinate_to_char(*i)),
+            Region::Block(i) => write!(f, "block {}", coordinate_to_char(*i)),
+            Region::Diagonal(0) => write!(f, "the main diagonal"),
+            Region::Diagonal(_) => write!(f, "the anti diagonal"),
+            Region::Window(i) => write!(f, "window {}", coordinate_to_char(*i)),
+        }
+    }
+}
+
+impl Region {
+    pub(super) fn cells(&self, board: &Sudoku) -> Vec<(usize, usize)> {
+        let side = board.size.side();
+
+        match self {
+            Region::Row(i) => (0..side).map(|k| (*i, k)).collect(),
+            Region::Column(i) => (0..side).map(|k| (k, *i)).collect(),
+            Region::Block(i) => (0..side * side)
+                .map(|k| (k / side, k % side))
+                .filter(|&(x, y)| board.get_block_id(x, y) == *i)
+                .collect(),
+            Region::Diagonal(0) => (0..side).map(|k| (k, k)).collect(),
+            Region::Diagonal(_) => (0..side).map(|k| (k, side - 1 - k)).collect(),
+            Region::Window(i) => (0..side * side)
+                .map(|k| (k / side, k % side))
+                .filter(|&(x, y)| board.get_window_id(x, y) == Some(*i))
+                .collect(),
+        }
+    }
+
+    pub(super) fn all(size: BoardSize, variant: Variant) -> Vec<Region> {
+        let diagonals = if variant.has_diagonals() { 0..2 } else { 0..0 };
+        let windows = if variant.has_windows() {
+            0..size.window_count()
+        } else {
+            0..0
+        };
+
+        (0..size.side())
+            .map(Region::Block)
+            .chain((0..size.side()).map(Region::Row))
+            .chain((0..size.side()).map(Region::Column))
+            .chain(diagonals.map(Region::Diagonal))
+            .chain(windows.map(Region::Window))
+            .collect()
+    }
+}
+
+/// a single placement that can be deduced from the current grid
+#[derive(Debug, Clone)]
+pub struct LogicalStep {
+    technique: Technique,
+    region: Region,
+    pos: Position,
+    value: u8,
+    /// elimination technique that had to be applied before the placement shows up
+    assisted_by: Option<Technique>,
+}
+
+impl LogicalStep {
+    pub fn get_technique(&self) -> Technique {
+        self.technique
+    }
+
+    pub fn get_region(&self) -> Region {
+        self.region
+    }
+
+    pub fn get_pos(&self) -> &Position {
+        &self.pos
+    }
+
+    pub fn get_value(&self) -> u8 {
+        self.value
+    }
+
+    pub fn get_assisted_by(&self) -> Option<Technique> {
+        self.assisted_by
+    }
+
+    /// why the value goes into the cell, in plain words
+    pub fn explain(&self) -> String {
+        let reason = match self.technique {
+            Technique::FullHouse => format!("it is the last empty cell in {}", self.region),
+            Technique::HiddenSingle => format!(
+                "it is the only cell in {} where {} can go",
+                self.region,
+                digit_to_char(self.value)
+            ),
+            Technique::NakedSingle => format!(
+                "{} is the only value the cells it sees and the rules of the board leave for it",
+                digit_to_char(self.value)
+            ),
+            _ => String::new(),
+        };
+
+        let value = digit_to_char(self.value);
+
+        match self.assisted_by {
+            None => format!("{} goes at {} as {}", value, self.pos, reason),
+            Some(t) => format!(
+                "{} goes at {} as {}, after the {} are used to rule out candidates",
+                value, self.pos, reason, t
+            ),
+        }
+    }
+}
+
+impl Sudoku {
+    /// solves a copy of the board step by step, always taking the easiest step, and grades it by
+    /// the hardest one that was needed
+    pub fn grade(&self) -> Grade {
+        let mut board = self.clone();
+        let mut grade = Grade::Easy;
+
+        while !board.is_board_solved_completely() {
+            let Some(step) = board.find_logical_step() else {
+                return Grade::Expert;
+            };
+
+            grade = grade.max(Grade::of(&step));
+
+            board
+                .insert(&step.pos, Some(step.value), CellState::Normal)
+                .expect("logical steps never clash with the filled cells");
+        }
+
+        grade
+    }
+
+    /// finds the easiest placement that follows logically from the filled cells, wrong guesses are ignored
+    pub fn find_logical_step(&self) -> Option<LogicalStep> {
+        let mut candidates = self.candidates();
+        let mut assisted_by = None;
+
+        loop {
+            if let Some(mut step) = self.find_single(&candidates) {
+                step.assisted_by = assisted_by;
+                return Some(step);
+            }
+
+            let used = self
+                .eliminate_locked_candidates(&mut candidates)
+                .or_else(|| self.eliminate_naked_pairs(&mut candidates))?;
+
+            // report the hardest technique that was needed
+            if assisted_by != Some(Technique::NakedPair) {
+                assisted_by = Some(used);
+            }
+        }
+    }
+
+    /// candidates bitmap per cell, zero for filled cells
+    fn candidates(&self) -> Candidates {
+        let side = self.size.side();
+        let mut rows = vec![0u32; side];
+        let mut columns = vec![0u32; side];
+        let mut blocks = vec![0u32; side];
+        let mut diagonals = [0u32; 2];
+        let mut windows = vec![0u32; self.size.window_count()];
+
+        for (i, row) in self.grid.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                if let (Some(v), state) = cell
+                    && *state != CellState::Wrong
+                {
+                    rows[i] |= 1 << v;
+                    columns[j] |= 1 << v;
+                    blocks[self.get_block_id(i, j)] |= 1 << v;
+
+                    for d in self.get_diagonal_ids(i, j) {
+                        diagonals[d] |= 1 << v;
+                    }
+
+                    if let Some(w) = self.get_window_id(i, j) {
+                        windows[w] |= 1 << v;
+                    }
+                }
+            }
+        }
+
+        let mut candidates = self.cage_restrictions();
+
+        for (i, row) in self.grid.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                if cell.0.is_none() || cell.1 == CellState::Wrong {
+                    let diagonals = self
+                        .get_diagonal_ids(i, j)
+                        .fold(0, |acc, d| acc | diagonals[d]);
+                    let window = self.get_window_id(i, j).map_or(0, |w| windows[w]);
+
+                    candidates[i][j] &= self.get_constraint_candidates(i, j)
+                        & !(rows[i]
+                            | columns[j]
+                            | blocks[self.get_block_id(i, j)]
+                            | diagonals
+                            | window);
+                } else {
+                    candidates[i][j] = 0;
+                }
+            }
+        }
+
+        candidates
+    }
+
+    fn find_single(&self, candidates: &Candidates) -> Option<LogicalStep> {
+        let regions = Region::all(self.size, self.variant);
+
+        for region in &regions {
+            let empty = region
+                .cells(self)
+                .into_iter()
+                .filter(|(i, j)| candidates[*i][*j] != 0)
+                .collect::<Vec<_>>();
+
+            if let [(i, j)] = empty[..]
+                && candidates[i][j].count_ones() == 1
+            {
+                return Some(LogicalStep {
+                    technique: Technique::FullHouse,
+                    region: *region,
+                    pos: Position::new(i, j),
+                    value: candidates[i][j].trailing_zeros() as u8,
+                    assisted_by: None,
+                });
+            }
+        }
+
+        for region in &regions {
+            for v in 1..=self.size.side() as u8 {
+                let places = region
+                    .cells(self)
+                    .into_iter()
+                    .filter(|(i, j)| candidates[*i][*j] & (1 << v) != 0)
+                    .collect::<Vec<_>>();
+
+                if let [(i, j)] = places[..] {
+                    return Some(LogicalStep {
+                        technique: Technique::HiddenSingle,
+                        region: *region,
+                        pos: Position::new(i, j),
+                        value: v,
+                        assisted_by: None,
+                    });
+                }
+            }
+        }
+
+        for (i, row) in candidates.iter().enumerate() {
+            for (j, c) in row.iter().enumerate() {
+                if c.count_ones() == 1 {
+                    return Some(LogicalStep {
+                        technique: Technique::NakedSingle,
+                        region: Region::Block(self.get_block_id(i, j)),
+                        pos: Position::new(i, j),
+                        value: c.trailing_zeros() as u8,
+                        assisted_by: None,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// pointing (block confined to a line) and claiming (line confined to a block)
+    fn eliminate_locked_candidates(&self, candidates: &mut Candidates) -> Option<Technique> {
+        for region in Region::all(self.size, self.variant) {
+            for v in 1..=self.size.side() as u8 {
+                let places = region
+                    .cells(self)
+                    .into_iter()
+                    .filter(|(i, j)| candidates[*i][*j] & (1 << v) != 0)
+                    .collect::<Vec<_>>();
+
+                if places.len() < 2 {
+                    continue;
+                }
+
+                let (x, y) = places[0];
+
+                let targets = match region {
+                    Region::Block(_) => {
+                        if places.iter().all(|p| p.0 == x) {
+                            vec![Region::Row(x)]
+                        } else if places.iter().all(|p| p.1 == y) {
+                            vec![Region::Column(y)]
+                        } else {
+                            vec![]
+                        }
+                    }
+                    _ => {
+                        let bid = self.get_block_id(x, y);
+
+                        if places.iter().all(|p| self.get_block_id(p.0, p.1) == bid) {
+                            vec![Region::Block(bid)]
+                        } else {
+                            vec![]
+                        }
+                    }
+                };
+
+                let mut eliminated = false;
+
+                for target in targets {
+                    for (i, j) in target.cells(self) {
+                        if !places.contains(&(i, j)) && candidates[i][j] & (1 << v) != 0 {
+                            candidates[i][j] &= !(1 << v);
+                            eliminated = true;
+                        }
+                    }
+                }
+
+                if eliminated {
+                    return Some(Technique::LockedCandidates);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn eliminate_naked_pairs(&self, candidates: &mut Candidates) -> Option<Technique> {
+        for region in Region::all(self.size, self.variant) {
+            let cells = region.cells(self);
+
+            for (a, &(ai, aj)) in cells.iter().enumerate() {
+                let pair = candidates[ai][aj];
+
+                if pair.count_ones() != 2 {
+                    continue;
+                }
+
+                for &(bi, bj) in &cells[a + 1..] {
+                    if candidates[bi][bj] != pair {
+                        continue;
+                    }
+
+                    let mut eliminated = false;
+
+                    for &(i, j) in &cells {
+                        if (i, j) != (ai, aj) && (i, j) != (bi, bj) && candidates[i][j] & pair != 0
+                        {
+                            candidates[i][j] &= !pair;
+                            eliminated = true;
+                        }
+                    }
+
+                    if eliminated {
+                        return Some(Technique::NakedPair);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,321 @@
+use std::{collections::HashSet, error::Error};
+
+use super::{
+    Cage, Cell, Position, Region, Sudoku, UpdateMapsType,
+    seeded_rng::{self, SeededRng},
+};
+
+impl Sudoku {
+    /// copy of the board with the cell at (x, y) moved to `cell(x, y)` and every value `v`
+    /// replaced by `digits[v]`. Fails when a row, column, block, diagonal or window doesn't land
+    /// on one of the copy, the copy wouldn't have the same solution then
+    fn remapped<F>(&self, cell: F, digits: &[u8]) -> Result<Self, Box<dyn Error>>
+    where
+        F: Fn(usize, usize) -> (usize, usize),
+    {
+        if !self.constraints.is_empty() {
+            return Err("boards with extra constraints can't be transformed".into());
+        }
+
+        let relabelled = digits.iter().enumerate().any(|(i, d)| i != *d as usize);
+
+        if self.variant.has_cages() && relabelled {
+            return Err("relabelling the values would change the cage sums".into());
+        }
+
+        let side = self.size.side();
+        let position = |p: &Position| {
+            let (x, y) = cell(p.x, p.y);
+            Position::new(x, y)
+        };
+
+        let mut board = Sudoku::empty(self.size, self.variant);
+
+        if self.variant.has_regions() {
+            let mut regions = vec![vec![0; side]; side];
+
+            for (x, row) in self.regions.iter().enumerate() {
+                for (y, r) in row.iter().enumerate() {
+                    let (i, j) = cell(x, y);
+                    regions[i][j] = *r;
+                }
+            }
+
+            board.set_regions(regions)?;
+        }
+
+        board.set_cages(
+            self.cages
+                .iter()
+                .map(|c| Cage::new(c.get_sum(), c.get_cells().iter().map(position).collect()))
+                .collect(),
+        )?;
+
+        let units = |b: &Sudoku| {
+            Region::all(b.size, b.variant)
+                .iter()
+                .map(|r| {
+                    let mut cells = r.cells(b);
+                    cells.sort_unstable();
+                    cells
+                })
+                .collect::<Vec<Vec<(usize, usize)>>>()
+        };
+
+        let targets = units(&board).into_iter().collect::<HashSet<_>>();
+
+        for unit in units(self) {
+            let mut moved = unit
+                .iter()
+                .map(|&(x, y)| cell(x, y))
+                .collect::<Vec<(usize, usize)>>();
+            moved.sort_unstable();
+
+            if !targets.contains(&moved) {
+                return Err(
+                    "the transformation breaks up a row, column, block, diagonal or window".into(),
+                );
+            }
+        }
+
+        let relabel = |c: Cell| (c.0.map(|v| digits[v as usize]), c.1);
+
+        for x in 0..side {
+            for y in 0..side {
+                let (i, j) = cell(x, y);
+
+                board.grid[i][j] = relabel(self.grid[x][y]);
+                board.solved_grid[i][j] = relabel(self.solved_grid[x][y]);
+
+                if let Some(v) = board.grid[i][j].0 {
+                    board
+                        .update_maps(&Position::new(i, j), v, UpdateMapsType::Add)
+                        .expect("every unit lands on a unit, values can't clash");
+                }
+            }
+        }
+
+        board.prefilled_positions = self
+            .prefilled_positions
+            .iter()
+            .map(|(p, v)| (position(p), digits[*v as usize]))
+            .collect();
+        board.highlighted = self.highlighted.map(|v| digits[v as usize]);
+
+        Ok(board)
+    }
+
+    /// every value mapped onto itself
+    fn same_digits(&self) -> Vec<u8> {
+        (0..=self.size.side() as u8).collect()
+    }
+
+    /// quarter turn clockwise, boxes have to be square unless the board has irregular regions
+    pub fn rotate(&self) -> Result<Self, Box<dyn Error>> {
+        let side = self.size.side();
+        self.remapped(|x, y| (y, side - 1 - x), &self.same_digits())
+    }
+
+    /// top row becomes the bottom one
+    pub fn mirror_top_bottom(&self) -> Result<Self, Box<dyn Error>> {
+        let side = self.size.side();
+        self.remapped(|x, y| (side - 1 - x, y), &self.same_digits())
+    }
+
+    /// left column becomes the right one
+    pub fn mirror_left_right(&self) -> Result<Self, Box<dyn Error>> {
+        let side = self.size.side();
+        self.remapped(|x, y| (x, side - 1 - y), &self.same_digits())
+    }
+
+    /// rows become columns, boxes have to be square unless the board has irregular regions
+    pub fn transpose(&self) -> Result<Self, Box<dyn Error>> {
+        self.remapped(|x, y| (y, x), &self.same_digits())
+    }
+
+    /// value `v` becomes `mapping[v - 1]`, the mapping has to hold every value once
+    pub fn permute_digits(&self, mapping: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let side = self.size.side();
+        let mut sorted = mapping.to_vec();
+        sorted.sort_unstable();
+
+        if sorted != (1..=side as u8).collect::<Vec<u8>>() {
+            return Err(
+                format!("the mapping has to hold every value from 1 to {side} once").into(),
+            );
+        }
+
+        let mut digits = vec![0];
+        digits.extend_from_slice(mapping);
+
+        self.remapped(|x, y| (x, y), &digits)
+    }
+
+    /// swaps two rows of the same band
+    pub fn swap_rows(&self, a: usize, b: usize) -> Result<Self, Box<dyn Error>> {
+        let swap = self.line_swap(a, b)?;
+        self.remapped(|x, y| (swap(x), y), &self.same_digits())
+    }
+
+    /// swaps two columns of the same stack
+    pub fn swap_columns(&self, a: usize, b: usize) -> Result<Self, Box<dyn Error>> {
+        let swap = self.line_swap(a, b)?;
+        self.remapped(|x, y| (x, swap(y)), &self.same_digits())
+    }
+
+    /// new index of every row (or column) once the two are swapped
+    fn line_swap(&self, a: usize, b: usize) -> Result<impl Fn(usize) -> usize, Box<dyn Error>> {
+        let side = self.size.side();
+
+        if a >= side || b >= side {
+            return Err(format!("rows and columns go from 0 to {}", side - 1).into());
+        }
+
+        Ok(move |i: usize| match i {
+            i if i == a => b,
+            i if i == b => a,
+            i => i,
+        })
+    }
+
+    /// swaps two bands, the rows of a box, as a whole
+    pub fn swap_bands(&self, a: usize, b: usize) -> Result<Self, Box<dyn Error>> {
+        let height = self.size.get_box_rows();
+        let bands = self.size.side() / height;
+
+        if a >= bands || b >= bands {
+            return Err(format!("bands go from 0 to {}", bands - 1).into());
+        }
+
+        let swap = |i: usize| match i / height {
+            band if band == a => b * height + i % height,
+            band if band == b => a * height + i % height,
+            _ => i,
+        };
+
+        self.remapped(|x, y| (swap(x), y), &self.same_digits())
+    }
+
+    /// swaps two stacks, the columns of a box, as a whole
+    pub fn swap_stacks(&self, a: usize, b: usize) -> Result<Self, Box<dyn Error>> {
+        let width = self.size.get_box_cols();
+        let stacks = self.size.side() / width;
+
+        if a >= stacks || b >= stacks {
+            return Err(format!("stacks go from 0 to {}", stacks - 1).into());
+        }
+
+        let swap = |i: usize| match i / width {
+            stack if stack == a => b * width + i % width,
+            stack if stack == b => a * width + i % width,
+            _ => i,
+        };
+
+        self.remapped(|x, y| (x, swap(y)), &self.same_digits())
+    }
+
+    /// equivalent board that looks different, made of random transformations. Transformations
+    /// the rule set doesn't allow, ex: swapping rows of an x-sudoku, are skipped. The same seed
+    /// always yields the same board. Fails when no transformation applies to the board at all,
+    /// ex: one with extra constraints
+    pub fn randomize_equivalent(&self, seed: u64) -> Result<Self, Box<dyn Error>> {
+        // leaving every cell and value in place only fails when nothing can be moved
+        self.remapped(|x, y| (x, y), &self.same_digits())?;
+
+        let mut rng = SeededRng::new(seed);
+        let mut board = self.clone();
+
+        let (height, width) = (self.size.get_box_rows(), self.size.get_box_cols());
+        let side = self.size.side();
+
+        if seeded_rng::coin(&mut rng) {
+            board = board.transpose().unwrap_or(board);
+        }
+
+        for _ in 0..seeded_rng::below(&mut rng, 4) {
+            board = board.rotate().unwrap_or(board);
+        }
+
+        if seeded_rng::coin(&mut rng) {
+            board = board.mirror_left_right().unwrap_or(board);
+        }
+
+        // shuffles of the bands, the stacks and the lines within them, one swap at a time
+        for i in (1..side / height).rev() {
+            let j = seeded_rng::below(&mut rng, i + 1);
+            board = board.swap_bands(i, j).unwrap_or(board);
+        }
+
+        for i in (1..side / width).rev() {
+            let j = seeded_rng::below(&mut rng, i + 1);
+            board = board.swap_stacks(i, j).unwrap_or(board);
+        }
+
+        for band in 0..side / height {
+            for i in (1..height).rev() {
+                let j = seeded_rng::below(&mut rng, i + 1);
+                board = board
+                    .swap_rows(band * height + i, band * height + j)
+                    .unwrap_or(board);
+            }
+        }
+
+        for stack in 0..side / width {
+            for i in (1..width).rev() {
+                let j = seeded_rng::below(&mut rng, i + 1);
+                board = board
+                    .swap_columns(stack * width + i, stack * width + j)
+                    .unwrap_or(board);
+            }
+        }
+
+        let mut mapping = (1..=side as u8).collect::<Vec<u8>>();
+        seeded_rng::shuffle(&mut rng, &mut mapping);
+
+        Ok(board.permute_digits(&mapping).unwrap_or(board))
+    }
+
+    /// equivalent copies of the board, copy `i` is always derived from `(seed, i)`. Fails when
+    /// no transformation applies to the board
+    pub fn equivalent_boards(&self, count: usize, seed: u64) -> Result<Vec<Self>, Box<dyn Error>> {
+        (0..count)
+            .map(|i| self.randomize_equivalent(Sudoku::hash_seed(&format!("{seed}/{i}"))))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::sudoku::Consecutive;
+
+    const GIVENS: &str =
+        "..82...3...147..65.5..6.49.8.6..53.2.......7..2....8.6..9....43..431.....7.694..1";
+
+    #[test]
+    fn copies_are_equivalent() {
+        let board = Sudoku::from_str(GIVENS).unwrap();
+        let copies = board.equivalent_boards(3, 11).unwrap();
+
+        assert_eq!(copies.len(), 3);
+        assert!(copies.iter().all(|c| c.to_thonky_str() != GIVENS));
+        assert!(
+            copies
+                .iter()
+                .all(|c| c.get_canonical_form() == board.get_canonical_form())
+        );
+    }
+
+    #[test]
+    fn boards_that_cant_be_transformed_are_an_error() {
+        let mut board = Sudoku::from_str(GIVENS).unwrap();
+        board
+            .add_constraint(Arc::new(Consecutive::new(vec![])))
+            .unwrap();
+
+        assert!(board.randomize_equivalent(11).is_err());
+        assert!(board.equivalent_boards(3, 11).is_err());
+    }
+}
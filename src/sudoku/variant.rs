@@ -0,0 +1,77 @@
+use std::fmt::Display;
+
+use super::BoardSize;
+
+/// rule set a board is played with, on top of the usual rows, columns and blocks
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, Default)]
+pub enum Variant {
+    #[default]
+    Classic,
+    /// both main diagonals hold every value once as well
+    Diagonal,
+    /// cells are grouped into cages with a sum, no value repeats within a cage
+    Killer,
+    /// blocks are irregular connected regions instead of boxes
+    Jigsaw,
+    /// extra box sized windows, one cell in from the border, hold every value once as well
+    Windoku,
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Variant::Classic => write!(f, "Classic"),
+            Variant::Diagonal => write!(f, "X-Sudoku (diagonals)"),
+            Variant::Killer => write!(f, "Killer (cages)"),
+            Variant::Jigsaw => write!(f, "Jigsaw (irregular regions)"),
+            Variant::Windoku => write!(f, "Windoku (hyper windows)"),
+        }
+    }
+}
+
+impl Variant {
+    pub const ALL: [Variant; 5] = [
+        Variant::Classic,
+        Variant::Diagonal,
+        Variant::Killer,
+        Variant::Jigsaw,
+        Variant::Windoku,
+    ];
+
+    /// short tag the board layout is prefixed with, classic boards have none
+    pub fn tag(&self) -> Option<&'static str> {
+        match self {
+            Variant::Classic => None,
+            Variant::Diagonal => Some("x"),
+            Variant::Killer => Some("k"),
+            Variant::Jigsaw => Some("j"),
+            Variant::Windoku => Some("w"),
+        }
+    }
+
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        Variant::ALL.into_iter().find(|v| v.tag() == Some(tag))
+    }
+
+    pub fn has_diagonals(&self) -> bool {
+        *self == Variant::Diagonal
+    }
+
+    pub fn has_cages(&self) -> bool {
+        *self == Variant::Killer
+    }
+
+    pub fn has_regions(&self) -> bool {
+        *self == Variant::Jigsaw
+    }
+
+    pub fn has_windows(&self) -> bool {
+        *self == Variant::Windoku
+    }
+
+    /// whether boards of the size can be generated with the rule set in reasonable time,
+    /// random fills of 16x16 boards with windows keep running into dead ends
+    pub fn supports(&self, size: BoardSize) -> bool {
+        !self.has_windows() || size.side() <= 12
+    }
+}
@@ -1,7 +1,7 @@
 use std::borrow::Cow;
 
 use colored::Colorize;
-use dialoguer::{Confirm, Input, Select, theme::ColorfulTheme};
+use dialoguer::{Confirm, Input, MultiSelect, Select, theme::ColorfulTheme};
 
 #[macro_export]
 macro_rules! display_error {
@@ -29,6 +29,18 @@ where
         .expect("error trying to render a select")
 }
 
+/// indices of the picked items, none picked by default
+pub fn prompt_multi_select<T>(q: &str, items: &Vec<T>) -> Vec<usize>
+where
+    T: std::fmt::Display,
+{
+    MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt(q)
+        .items(items)
+        .interact()
+        .expect("error trying to render a multi select")
+}
+
 pub fn prompt<'a>(q: &'a str, default: &str) -> Cow<'a, str> {
     Cow::Owned(
         Input::with_theme(&ColorfulTheme::default())